@@ -0,0 +1,227 @@
+use crate::calculator::{self, Calculator, FromStrValue, Operation, ParseOperand};
+use crate::error::CalculatorError;
+use crate::functions;
+use crate::math::Math;
+use crate::shunting_yard::ShuntingYard;
+use crate::tokenizer::{Token, Tokenize};
+
+/// An operand of a batch calculation, either one value shared by every row or a whole column
+enum Column<T> {
+    Scalar(T),
+    Values(Vec<T>),
+}
+
+impl<T: Clone> Column<T> {
+    fn map(self, operation: impl Fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        Ok(match self {
+            Column::Scalar(value) => Column::Scalar(operation(value)?),
+            Column::Values(values) => Column::Values(values.into_iter().map(operation).collect::<Result<_, _>>()?),
+        })
+    }
+
+    fn zip(self, rhs: Self, operation: fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        Ok(match (self, rhs) {
+            (Column::Scalar(left), Column::Scalar(right)) => Column::Scalar(operation(left, right)?),
+            (Column::Scalar(left), Column::Values(right)) => Column::Values(
+                right.into_iter().map(|right| operation(left.clone(), right)).collect::<Result<_, _>>()?
+            ),
+            (Column::Values(left), Column::Scalar(right)) => Column::Values(
+                left.into_iter().map(|left| operation(left, right.clone())).collect::<Result<_, _>>()?
+            ),
+            (Column::Values(left), Column::Values(right)) => Column::Values(
+                left.into_iter().zip(right).map(|(left, right)| operation(left, right)).collect::<Result<_, _>>()?
+            ),
+        })
+    }
+
+    fn into_rows(self, rows: usize) -> Vec<T> {
+        match self {
+            Column::Scalar(value) => vec![value; rows],
+            Column::Values(values) => values,
+        }
+    }
+}
+
+fn row_count<T>(columns: &[(&str, &[T])]) -> Result<usize, CalculatorError> {
+    let rows = columns.first().map_or(0, |(_, values)| values.len());
+    match columns.iter().find(|(_, values)| values.len() != rows) {
+        Some((name, values)) => Err(CalculatorError::ColumnLength { column: name.to_string(), rows: values.len(), expected: rows }),
+        None => Ok(rows),
+    }
+}
+
+/// Reads comma separated columns, the first line naming them, as (name, values) pairs. Rows are
+/// counted from 1 after the names in errors.
+pub(crate) fn read_columns(mut lines: impl Iterator<Item=String>) -> Result<Vec<(String, Vec<f64>)>, String> {
+    let mut columns: Vec<(String, Vec<f64>)> = match lines.next() {
+        Some(header) => header.split(',').map(|name| (name.trim().to_string(), vec![])).collect(),
+        None => return Ok(vec![]),
+    };
+    for (row, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            return Err(format!("Row {} is empty", row + 1));
+        }
+        let cells: Vec<&str> = line.split(',').collect();
+        if cells.len() != columns.len() {
+            return Err(format!("Row {} has {} columns, expected {}", row + 1, cells.len(), columns.len()));
+        }
+        for ((_, column), cell) in columns.iter_mut().zip(cells) {
+            match cell.trim().parse() {
+                Ok(value) => column.push(value),
+                Err(_) => return Err(format!("Cannot parse \"{}\" on row {}", cell.trim(), row + 1)),
+            }
+        }
+    }
+    Ok(columns)
+}
+
+impl Calculator {
+    /// Evaluates `expression` for every row of the named `columns`.
+    ///
+    /// The expression is shunted to reverse polish notation once and each operator is then applied
+    /// down whole columns, so the cost of interpreting the expression does not grow with the rows.
+    pub(crate) fn calculate_columns<T>(&self, expression: &str, columns: &[(&str, &[T])]) -> Result<Vec<T>, CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        let rows = row_count(columns)?;
+        let mut yard = ShuntingYard::new();
+        expression.chars().tokenize().for_each(|t| yard.push(t));
+
+        let tokens = if self.percentages {
+            calculator::relative_percentages(yard.into_vec().into_iter())
+        } else {
+            yard.into_vec()
+        };
+
+        let mut stack: Vec<Column<T>> = vec![];
+        for t in tokens {
+            let column = match t {
+                // Column names come before units, so columns may be called t, m or s
+                Token::T(v) | Token::Unit(v) => match columns.iter().find(|(name, _)| *name == v) {
+                    Some((_, values)) => Column::Values(values.to_vec()),
+                    None => Column::Scalar(v.parse_operand::<T>().map_err(|_| CalculatorError::Parse(v))?),
+                },
+                Token::Function(name, arguments) => match functions::elementary::<T>(&name) {
+                    Some(operation) if arguments == 1 => {
+                        stack.pop().ok_or(CalculatorError::EmptyStack)?.map(operation)?
+                    }
+                    _ => return Err(CalculatorError::UnsupportedOperation(format!("{} over columns", name))),
+                },
+                Token::OpenBrace | Token::CloseBrace | Token::OpenBracket | Token::CloseBracket =>
+                    return Err(CalculatorError::UnbalancedBrackets(t.to_string())),
+                Token::Then | Token::Else => return Err(CalculatorError::UnsupportedOperation("if over columns".to_string())),
+                Token::Vector(_) => return Err(CalculatorError::UnsupportedOperation("vector over columns".to_string())),
+                Token::Temporal(name) =>
+                    return Err(CalculatorError::UnsupportedOperation(format!("{} over columns", name))),
+                Token::Equal | Token::NotEqual | Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual
+                | Token::Approx | Token::And | Token::Or | Token::Not =>
+                    return Err(CalculatorError::UnsupportedOperation(format!("{} over columns", t))),
+                _ => match Operation::of(&t) {
+                    Operation::Unary(operation) => {
+                        stack.pop().ok_or(CalculatorError::EmptyStack)?.map(operation)?
+                    }
+                    Operation::Binary(operation) => {
+                        let right = stack.pop().ok_or(CalculatorError::EmptyStack)?;
+                        let left = stack.pop().ok_or(CalculatorError::EmptyStack)?;
+                        left.zip(right, operation)?
+                    }
+                },
+            };
+            stack.push(column);
+        }
+        Ok(stack.pop().ok_or(CalculatorError::EmptyStack)?.into_rows(rows))
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+
+    #[test]
+    fn single_column() {
+        let a = [1f64, 2f64, 3f64];
+        assert_eq!(Ok(vec![2f64, 4f64, 6f64]), Calculator::default().calculate_columns("2a", &[("a", &a)]));
+    }
+
+    #[test]
+    fn several_columns() {
+        let a = [1f64, 2f64, 3f64];
+        let b = [4f64, 5f64, 6f64];
+        let c = [1f64, 0f64, 2f64];
+        assert_eq!(
+            Ok(vec![5f64, 10f64, 22f64]),
+            Calculator::default().calculate_columns("a*b + c^2", &[("a", &a), ("b", &b), ("c", &c)])
+        );
+    }
+
+    #[test]
+    fn columns_named_as_units() {
+        let t = [1f64, 2f64];
+        let m = [3f64, 4f64];
+        assert_eq!(
+            Ok(vec![5f64, 10f64]),
+            Calculator::default().calculate_columns("t*m + 2", &[("t", &t), ("m", &m)])
+        );
+        assert_eq!(Err(CalculatorError::Parse("kg".to_string())), Calculator::default().calculate_columns("t*kg", &[("t", &t)]));
+    }
+
+    #[test]
+    fn constant_expression_fills_every_row() {
+        let a = [1f64, 2f64];
+        assert_eq!(Ok(vec![3f64, 3f64]), Calculator::default().calculate_columns("1+2", &[("a", &a)]));
+    }
+
+    #[test]
+    fn unary_operator() {
+        let a = [50f64, 25f64];
+        assert_eq!(Ok(vec![0.5f64, 0.25f64]), Calculator::default().calculate_columns("a%", &[("a", &a)]));
+    }
+
+    #[test]
+    fn percentage_mode() {
+        let a = [100f64, 200f64];
+        let mut calculator = Calculator::default();
+        assert_eq!(Ok(vec![100.15f64, 200.15f64]), calculator.calculate_columns("a + 15%", &[("a", &a)]));
+        calculator.percentages = true;
+        assert_eq!(Ok(vec![115f64, 230f64]), calculator.calculate_columns("a + 15%", &[("a", &a)]));
+        assert_eq!(Ok(vec![90f64, 180f64]), calculator.calculate_columns("a - 10%", &[("a", &a)]));
+    }
+
+    #[test]
+    fn function() {
+        let a = [1f64, 4f64, 9f64];
+        assert_eq!(Ok(vec![2f64, 3f64, 4f64]), Calculator::default().calculate_columns("sqrt(a) + 1", &[("a", &a)]));
+    }
+
+    #[test]
+    fn mismatched_column_lengths() {
+        let a = [1f64, 2f64];
+        let b = [1f64];
+        assert_eq!(
+            Err(CalculatorError::ColumnLength { column: "b".to_string(), rows: 1, expected: 2 }),
+            Calculator::default().calculate_columns("a+b", &[("a", &a), ("b", &b)])
+        );
+    }
+
+    #[test]
+    fn read_csv() {
+        let lines = |text: &str| text.lines().map(str::to_string).collect::<Vec<_>>().into_iter();
+        assert_eq!(
+            Ok(vec![("a".to_string(), vec![1f64, 3f64]), ("b".to_string(), vec![2f64, 4f64])]),
+            read_columns(lines("a, b\n1, 2\n3, 4"))
+        );
+        assert_eq!(Err("Row 2 is empty".to_string()), read_columns(lines("a,b\n1,2\n\n3,4")));
+        assert_eq!(Err("Row 1 has 1 columns, expected 2".to_string()), read_columns(lines("a,b\n1\n3,4")));
+        assert_eq!(Err("Row 1 has 3 columns, expected 2".to_string()), read_columns(lines("a,b\n1,2,3")));
+        assert_eq!(Err("Cannot parse \"x\" on row 2".to_string()), read_columns(lines("a,b\n1,2\n3,x")));
+    }
+
+    #[test]
+    fn unknown_column() {
+        let a = [1f64];
+        assert_eq!(
+            Err(CalculatorError::Parse("b".to_string())),
+            Calculator::default().calculate_columns::<f64>("a+b", &[("a", &a)])
+        );
+    }
+}
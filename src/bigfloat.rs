@@ -0,0 +1,384 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use bigdecimal::BigDecimal;
+use num::{BigInt, Signed, ToPrimitive};
+
+use crate::calculator::{FromStrValue, radix_literal};
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::{self, Rounding};
+use crate::math::{BigInteger, Bitwise, Checked, CheckedDiv, Compare, Elementary, Gamma, Inv, Magnitude, Modulo, One, Percent, Polar, Pow, Round, Real, ToInteger, Zero};
+
+/// Significant digits kept by arithmetic, whose exact results would otherwise grow without bound
+const PRECISION: u64 = 100;
+
+/// π to more digits than [PRECISION]
+const PI: &str = "3.14159265358979323846264338327950288419716939937510582097494459230781640628620899862803482534211706798";
+
+/// e to more digits than [PRECISION]
+const E: &str = "2.71828182845904523536028747135266249775724709369995957496696762772407663035354759457138217852516642743";
+
+/// A decimal floating point number with [PRECISION] significant digits and an exponent of any
+/// size, so `0.1 + 0.2` is exactly `0.3`
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct BigFloat(BigDecimal);
+
+impl BigFloat {
+    /// Rounds `value` to [PRECISION] significant digits
+    fn new(value: BigDecimal) -> Self {
+        if value.digits() > PRECISION {
+            BigFloat(value.with_prec(PRECISION))
+        } else {
+            BigFloat(value)
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<BigInt> {
+        if self.0.is_integer() {
+            Some(self.0.with_scale(0).into_bigint_and_exponent().0)
+        } else {
+            None
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        num::Zero::is_zero(&self.0)
+    }
+
+    /// Calculates a function without a decimal form through `f64`
+    fn through_f64(self, function: fn(f64) -> f64) -> Self {
+        Self::from_f64(function(self.to_f64()))
+    }
+}
+
+impl Add for BigFloat {
+    type Output = BigFloat;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        BigFloat::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for BigFloat {
+    type Output = BigFloat;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigFloat::new(self.0 - rhs.0)
+    }
+}
+
+impl Mul for BigFloat {
+    type Output = BigFloat;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        BigFloat::new(self.0 * rhs.0)
+    }
+}
+
+/// Panics when dividing by zero, [CheckedDiv] reports it instead
+impl Div for BigFloat {
+    type Output = BigFloat;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        BigFloat::new(self.0 / rhs.0)
+    }
+}
+
+impl Neg for BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> Self::Output {
+        BigFloat(-self.0)
+    }
+}
+
+impl CheckedDiv for BigFloat {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs.is_zero() {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+}
+
+impl Zero for BigFloat {
+    fn zero() -> Self {
+        BigFloat(num::Zero::zero())
+    }
+}
+
+impl One for BigFloat {
+    fn one() -> Self {
+        BigFloat(num::One::one())
+    }
+}
+
+impl Inv for BigFloat {
+    fn inv(self) -> Self {
+        BigFloat::new(self.0.inverse())
+    }
+}
+
+/// Integer powers are calculated to [PRECISION] digits, others through `f64`
+impl Pow for BigFloat {
+    fn pow(self, rhs: Self) -> Self {
+        match rhs.to_integer() {
+            Some(exponent) => BigFloat::new(self.0.powi(exponent)),
+            None => Self::from_f64(self.to_f64().powf(rhs.to_f64())),
+        }
+    }
+}
+
+impl Percent for BigFloat {
+    fn percent(self) -> Self {
+        BigFloat::new(self.0 / BigDecimal::from(100))
+    }
+}
+
+/// Square roots and exponentials are calculated to [PRECISION] digits, others through `f64`
+impl Elementary for BigFloat {
+    fn sqrt(self) -> Self {
+        match self.0.sqrt() {
+            Some(root) => BigFloat::new(root),
+            None => self.through_f64(f64::sqrt),
+        }
+    }
+
+    fn exp(self) -> Self {
+        BigFloat::new(self.0.exp())
+    }
+
+    fn ln(self) -> Self {
+        self.through_f64(f64::ln)
+    }
+
+    fn sin(self) -> Self {
+        self.through_f64(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.through_f64(f64::cos)
+    }
+
+    fn tan(self) -> Self {
+        self.through_f64(f64::tan)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(BigFloat(self.0.abs()))
+    }
+}
+
+impl Polar for BigFloat {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        Self::zero()
+    }
+
+    fn arg(self) -> Self {
+        self.through_f64(|value| 0f64.atan2(value))
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        Ok(Self::from_f64(self.to_f64().cis()?))
+    }
+
+    fn degrees(self) -> Self {
+        self.through_f64(f64::to_radians)
+    }
+}
+
+impl Magnitude for BigFloat {
+    const EXACT: bool = false;
+    const EPSILON: f64 = 1e-99;
+
+    fn magnitude(&self) -> f64 {
+        self.to_f64().abs()
+    }
+}
+
+impl Modulo for BigFloat {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let quotient = self.clone().quotient(rhs.clone())?;
+        Ok(self - rhs * quotient)
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let ratio = self.checked_div(rhs.clone())?.0;
+        let rounding = if rhs.0.is_negative() { Rounding::Ceiling } else { Rounding::Floor };
+        Ok(BigFloat(rounding::round_decimal(&ratio, 0, rounding)))
+    }
+}
+
+impl Checked for BigFloat {}
+
+impl Bitwise for BigFloat {}
+
+impl BigInteger for BigFloat {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        self.as_integer()
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        Ok(BigFloat::new(BigDecimal::from(value)))
+    }
+}
+
+impl Compare for BigFloat {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+}
+
+impl Round for BigFloat {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(BigFloat(rounding::round_decimal(&self.0, digits, rounding)))
+    }
+
+    fn sign(self) -> Self {
+        BigFloat(BigDecimal::from(self.0.sign() as i8))
+    }
+}
+
+/// Exact for integers, otherwise calculated through `f64`
+impl Gamma for BigFloat {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        if self.0.is_integer() {
+            return combinatorics::factorial(self - Self::one());
+        }
+        Ok(Self::from_f64(Gamma::gamma(self.to_f64())?))
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        Ok(Self::from_f64(Gamma::ln_gamma(self.to_f64())?))
+    }
+}
+
+impl ToInteger for BigFloat {
+    fn to_integer(&self) -> Option<i64> {
+        self.as_integer()?.to_i64()
+    }
+}
+
+impl Real for BigFloat {
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// The shortest decimal that reads back as `value`, rather than its exact binary expansion
+    fn from_f64(value: f64) -> Self {
+        if !value.is_finite() {
+            return Self::zero();
+        }
+        BigFloat(format!("{:e}", value).parse().expect("Finite floating point numbers are decimals"))
+    }
+}
+
+/// Parses decimals, optionally with an exponent, e.g. `2.5` or `1.5e3`, and integers in other bases such as `0x1F`
+impl FromStr for BigFloat {
+    type Err = CalculatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || CalculatorError::Parse(s.to_string());
+        if let Some((radix, digits)) = radix_literal(s) {
+            let integer = BigInt::parse_bytes(digits.as_bytes(), radix).ok_or_else(error)?;
+            return Ok(BigFloat::new(BigDecimal::from(integer)));
+        }
+        let mantissa = s.split_once(['e', 'E']).map_or(s, |(mantissa, _)| mantissa);
+        if !mantissa.chars().any(|c| c.is_ascii_digit()) || !mantissa.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return Err(error());
+        }
+        s.parse().map(BigFloat::new).map_err(|_| error())
+    }
+}
+
+/// The constants are given to [PRECISION] digits
+impl FromStrValue for BigFloat {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let constant = |digits: &str| BigFloat::new(digits.parse().expect("Constants are decimals"));
+        match s {
+            "tau" => Ok(constant(PI) * BigFloat(BigDecimal::from(2))),
+            "pi" => Ok(constant(PI)),
+            "e" => Ok(constant(E)),
+            _ => s.parse()
+        }
+    }
+}
+
+/// Without trailing zeros, so `2.50` is `2.5`
+impl Display for BigFloat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.normalized())
+    }
+}
+
+#[cfg(test)]
+mod bigfloat_tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<BigFloat, CalculatorError> {
+        <BigFloat as FromStrValue>::from_str(s)
+    }
+
+    fn decimal(s: &str) -> BigFloat {
+        parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_decimals() {
+        assert_eq!(Ok(BigFloat(BigDecimal::from(3))), parse("3"));
+        assert_eq!(Ok(decimal("2.5")), parse("25e-1"));
+        assert_eq!(Ok(decimal("0.125")), parse(".125"));
+        assert_eq!(Ok(decimal("1500")), parse("1.5e3"));
+        assert_eq!(Ok(decimal("31")), parse("0x1F"));
+        assert_eq!(Err(CalculatorError::Parse("x".to_string())), parse("x"));
+        assert_eq!(Err(CalculatorError::Parse("-1".to_string())), parse("-1"));
+    }
+
+    #[test]
+    fn decimal_arithmetic() {
+        assert_eq!(decimal("0.3"), decimal("0.1") + decimal("0.2"));
+        assert_eq!("0.3", (decimal("0.1") + decimal("0.2")).to_string());
+        assert_eq!(decimal("1.21"), decimal("1.1").pow(decimal("2")));
+        assert_eq!(decimal("0.25"), decimal("2").pow(-decimal("2")));
+    }
+
+    #[test]
+    fn precision() {
+        let third = decimal("1") / decimal("3");
+        assert_eq!(format!("0.{}", "3".repeat(100)), third.to_string());
+        assert_eq!(decimal("1"), decimal("1e99") + decimal("1") - decimal("1e99"));
+        assert_eq!(decimal("0"), decimal("1e100") + decimal("1") - decimal("1e100"));
+        assert_eq!("1.414213562373095048801688724209698078569671875376948073176679737990732478462107038850387534327641573",
+            decimal("2").sqrt().to_string());
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(Err(CalculatorError::DivisionByZero), BigFloat::one().checked_div(BigFloat::zero()));
+    }
+
+    #[test]
+    fn rounding() {
+        assert_eq!(Ok(decimal("2.67")), decimal("2.665").round_to(2, Rounding::HalfUp));
+        assert_eq!(Ok(decimal("2.66")), decimal("2.665").round_to(2, Rounding::HalfEven));
+        assert_eq!(Ok(decimal("1200")), decimal("1250").round_to(-2, Rounding::HalfEven));
+        assert_eq!(Ok(-decimal("2.6")), (-decimal("2.675")).round_to(1, Rounding::TowardZero));
+    }
+
+    #[test]
+    fn modulo() {
+        assert_eq!(Ok(decimal("0.5")), (-decimal("2.5")).modulo(decimal("1")));
+        assert_eq!(Ok(-decimal("3")), (-decimal("2.5")).quotient(decimal("1")));
+    }
+}
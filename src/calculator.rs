@@ -1,19 +1,120 @@
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, VecDeque};
 use std::str::FromStr;
 
+use crate::combinatorics;
+use crate::currency::Rates;
+use crate::error::CalculatorError;
+use crate::expression::{self, Symbolic};
+use crate::functions;
 use crate::math::Math;
+use crate::quantity::Quantity;
+use crate::rounding::Rounding;
 use crate::shunting_yard::Shunt;
+use crate::temporal::{self, DateTime};
 use crate::tokenizer::{Token, Tokenize};
+use crate::tolerance::Tolerance;
+use crate::units::{self, Dimension, Unit};
+use crate::value::Value;
 
-pub struct Calculator {}
+#[derive(Default)]
+pub struct Calculator {
+    /// Percentage mode, where `a + b%` and `a - b%` add or subtract b% of a, so `100 + 15%` is 115
+    /// rather than 100.15, as on handheld calculators
+    pub percentages: bool,
+    /// How `round` treats halves
+    pub rounding: Rounding,
+    /// Units defined with `unit rack = 42 U` or in the unit file, by name, as expressions in other units
+    pub units: BTreeMap<String, String>,
+    /// Exchange rates from the rates file, which make currency codes such as USD units
+    pub rates: Option<Rates>,
+    /// How close inexact numbers must be to compare equal, and any numbers to be `≈`
+    pub tolerance: Tolerance,
+}
 
 enum ParsedToken<T, S> {
     Operand(T),
     Operator(S),
 }
 
-struct Memory<T> {
-    stack: VecDeque<T>,
+struct Memory<'a, T> {
+    stack: VecDeque<Value<T>>,
+    calculator: &'a Calculator,
+}
+
+/// How an operator token combines its operands, resolved once from the token so that it can be
+/// applied to any number of values
+pub(crate) enum Operation<T> {
+    Unary(fn(T) -> Result<T, CalculatorError>),
+    Binary(fn(T, T) -> Result<T, CalculatorError>),
+}
+
+impl<T: Math<T>> Operation<T> {
+    pub(crate) fn of(t: &Token) -> Self {
+        match t {
+            Token::T(_) => panic!(),
+            Token::Unit(_) => panic!(),
+            Token::Temporal(_) => panic!(),
+            Token::Plus => Operation::Binary(|left, right| left.checked_plus(right)),
+            Token::Minus => Operation::Binary(|left, right| left.checked_minus(right)),
+            Token::Multiply => Operation::Binary(|left, right| left.checked_times(right)),
+            Token::Divide => Operation::Binary(|left, right| left.checked_div(right)),
+            Token::Percent => Operation::Unary(|left| Ok(left.percent())),
+            Token::PercentIncrease => Operation::Binary(|left, right| left.clone().checked_plus(functions::percent_of(right, left)?)),
+            Token::PercentDecrease => Operation::Binary(|left, right| left.clone().checked_minus(functions::percent_of(right, left)?)),
+            Token::Modulo => Operation::Binary(|left, right| left.modulo(right)),
+            Token::Quotient => Operation::Binary(|left, right| left.quotient(right)),
+            Token::Power => Operation::Binary(|left, right| {
+                if right.clone().re().compare(&T::zero()) == Some(Ordering::Less) {
+                    reciprocal(&left)?;
+                }
+                left.checked_power(right)
+            }),
+            Token::Root => Operation::Binary(|left, right| {
+                reciprocal(&left)?;
+                Ok(left.root(right))
+            }),
+            Token::Angle => Operation::Binary(|magnitude, angle| Ok(magnitude * angle.cis()?)),
+            Token::Degree => Operation::Unary(|angle| Ok(angle.degrees())),
+            Token::Factorial => Operation::Unary(combinatorics::factorial),
+            Token::BitAnd => Operation::Binary(|left, right| left.bit_and(right)),
+            Token::BitOr => Operation::Binary(|left, right| left.bit_or(right)),
+            Token::Xor => Operation::Binary(|left, right| left.bit_xor(right)),
+            Token::BitNot => Operation::Unary(|value| value.bit_not()),
+            Token::Negate => Operation::Unary(|value| T::zero().checked_minus(value)),
+            Token::ShiftLeft => Operation::Binary(|left, right| left.shift_left(right)),
+            Token::ShiftRight => Operation::Binary(|left, right| left.shift_right(right)),
+            // Comparisons and logic give booleans rather than numbers
+            Token::Equal | Token::NotEqual | Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual | Token::Approx => panic!(),
+            Token::And | Token::Or | Token::Not => panic!(),
+            // TODO: have different set of tokens for input and output?
+            //  Pain because will have to map them. This might be the cleanest solution
+            Token::OpenBrace => panic!(),
+            Token::CloseBrace => panic!(),
+            Token::OpenBracket => panic!(),
+            Token::CloseBracket => panic!(),
+            Token::Separator => panic!(),
+            Token::Function(_, _) => panic!(),
+            Token::Vector(_) => panic!(),
+            Token::Then | Token::Else => panic!(),
+        }
+    }
+
+    fn operand_count(&self) -> usize {
+        match self {
+            Operation::Unary(_) => 1,
+            Operation::Binary(_) => 2,
+        }
+    }
+}
+
+/// Negative powers and roots divide by their base, so a zero base is a division by zero for the
+/// types that report one, rather than a panic or a truncated zero
+fn reciprocal<T: Math<T>>(base: &T) -> Result<(), CalculatorError> {
+    if base.magnitude() == 0f64 {
+        T::one().checked_div(base.clone())?;
+    }
+    Ok(())
 }
 
 pub trait ParseOperand {
@@ -30,9 +131,38 @@ impl ParseOperand for &str {
     }
 }
 
+/// Splits an integer literal in another base, `0x1F`, `0b1010` or `0o17`, into its radix and digits
+pub(crate) fn radix_literal(s: &str) -> Option<(u32, &str)> {
+    let radix = match s.get(..2)? {
+        "0x" => 16,
+        "0b" => 2,
+        "0o" => 8,
+        _ => return None,
+    };
+    Some((radix, &s[2..]))
+}
+
+/// Literals in other bases are bit patterns, so `0xFFFFFFFF` is -1
 impl FromStrValue for i32 {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.parse()
+        match radix_literal(s) {
+            Some((radix, digits)) => u32::from_str_radix(digits, radix).map(|bits| bits as i32),
+            None => s.parse(),
+        }
+    }
+}
+
+impl FromStrValue for f32 {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tau" => Ok(std::f32::consts::TAU),
+            "pi" => Ok(std::f32::consts::PI),
+            "e" => Ok(std::f32::consts::E),
+            _ => match radix_literal(s) {
+                Some((radix, digits)) => u64::from_str_radix(digits, radix).map(|value| value as f32).or_else(|_| s.parse()),
+                None => s.parse(),
+            }
+        }
     }
 }
 
@@ -42,44 +172,58 @@ impl FromStrValue for f64 {
             "tau" => Ok(std::f64::consts::TAU),
             "pi" => Ok(std::f64::consts::PI),
             "e" => Ok(std::f64::consts::E),
-            _ => s.parse()
+            _ => match radix_literal(s) {
+                Some((radix, digits)) => u64::from_str_radix(digits, radix).map(|value| value as f64).or_else(|_| s.parse()),
+                None => s.parse(),
+            }
         }
     }
 }
 
-impl<T> Memory<T> {
-    fn new() -> Self {
+impl<'a, T> Memory<'a, T> {
+    fn new(calculator: &'a Calculator) -> Self {
         Self {
-            stack: VecDeque::new()
+            stack: VecDeque::new(),
+            calculator,
         }
     }
 
     fn push(&mut self, t: T) {
-        self.stack.push_back(t);
+        self.stack.push_back(Value::Scalar(t));
     }
 
-    fn push_operator(&mut self, t: Token) -> Result<(), String>
+    fn push_operator(&mut self, t: Token) -> Result<(), CalculatorError>
         where T: FromStrValue + Math<T>
     {
-        let n = Self::operand_count(&t);
-        let mut top_n = self.pop_n(n)?;
+        let value = match t {
+            Token::Function(name, arguments) => functions::call(&name, self.pop_n(arguments)?, self.calculator.rounding, &self.calculator.tolerance)?,
+            Token::Unit(name) => self.calculator.unit(&name)?,
+            Token::Temporal(literal) => Value::Temporal(DateTime::parse(&literal)?),
+            Token::Vector(elements) => Value::vector(self.pop_n(elements)?)?,
+            Token::Not => self.pop_n(1)?.remove(0).not()?,
+            // The shunting yard leaves a brace or bracket in the output when it has no partner
+            Token::OpenBrace | Token::CloseBrace | Token::OpenBracket | Token::CloseBracket =>
+                return Err(CalculatorError::UnbalancedBrackets(t.to_string())),
+            Token::Equal | Token::NotEqual | Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual | Token::Approx | Token::And | Token::Or => {
+                let mut operands = self.pop_n(2)?.into_iter();
+                let (left, right) = (operands.next().ok_or(CalculatorError::EmptyStack)?, operands.next().ok_or(CalculatorError::EmptyStack)?);
+                match t {
+                    Token::And | Token::Or => left.logic(&t, right)?,
+                    _ => left.compare(&t, right, &self.calculator.tolerance)?,
+                }
+            }
+            _ => {
+                let operation = Operation::of(&t);
+                let mut operands = self.pop_n(operation.operand_count())?.into_iter();
 
-        let left = top_n.pop().ok_or("No lhs");
-        let right = top_n.pop().ok_or("No rhs");
+                let left = operands.next().ok_or(CalculatorError::EmptyStack);
+                let right = operands.next().ok_or(CalculatorError::EmptyStack);
 
-        let value = match t {
-            Token::T(_) => panic!(),
-            Token::Plus => left? + right?,
-            Token::Minus => left? - right?,
-            Token::Multiply => left? * right?,
-            Token::Divide => left? / right?,
-            Token::Percent => left?.percent(),
-            Token::Power => left?.pow(right?),
-            Token::Root => left?.root(right?),
-            // TODO: have different set of tokens for input and output?
-            //  Pain because will have to map them. This might be the cleanest solution
-            Token::OpenBrace => panic!(),
-            Token::CloseBrace => panic!(),
+                match operation {
+                    Operation::Unary(operation) => left?.unary(&t, operation)?,
+                    Operation::Binary(operation) => left?.combine(&t, right?, operation)?,
+                }
+            }
         };
 
         self.stack.push_back(value);
@@ -87,44 +231,113 @@ impl<T> Memory<T> {
         Ok(())
     }
 
-    fn top(mut self) -> Result<T, String> {
-        self.stack.pop_back().ok_or_else(|| "Empty stack".to_string())
+    /// Takes the condition of `if` from the stack
+    fn condition(&mut self) -> Result<bool, CalculatorError> {
+        match self.pop_n(1)?.remove(0) {
+            Value::Boolean(condition) => Ok(condition),
+            condition => Err(CalculatorError::UnsupportedOperation(format!("if({})", condition.kind()))),
+        }
+    }
+
+    /// Ends `if`, whose result is the branch that was taken and is already on the stack
+    fn end_if(&mut self, arguments: usize) -> Result<(), CalculatorError>
+        where T: Math<T>
+    {
+        if arguments != 3 {
+            return Err(CalculatorError::ArgumentCount { function: "if".to_string(), expected: 3, found: arguments });
+        }
+        let value = self.pop_n(1)?.remove(0).expand();
+        self.stack.push_back(value);
+        Ok(())
     }
-    fn pop_n(&mut self, n: u8) -> Result<Vec<T>, String> {
-        (0..n).map(|_| self.stack.pop_back().ok_or_else(|| "Empty stack".to_string())).collect()
+
+    fn top(mut self) -> Result<Value<T>, CalculatorError> {
+        self.stack.pop_back().ok_or(CalculatorError::EmptyStack)
     }
 
-    fn operand_count(t: &Token) -> u8 {
-        match t {
-            Token::T(_) => { panic!() }
-            Token::Plus => 2,
-            Token::Minus => 2,
-            Token::Multiply => 2,
-            Token::Divide => 2,
-            Token::Percent => 1,
-            Token::Power => 2,
-            Token::Root => 2,
-            // TODO: have different set of tokens for input and output?
-            //  Pain because will have to map them. This might be the cleanest solution
-            Token::OpenBrace => { panic!() }
-            Token::CloseBrace => { panic!() }
+    /// Removes the top `n` values, returning them in the order they were pushed
+    fn pop_n(&mut self, n: usize) -> Result<Vec<Value<T>>, CalculatorError> {
+        if n > self.stack.len() {
+            return Err(CalculatorError::EmptyStack);
+        }
+        Ok(self.stack.split_off(self.stack.len() - n).into())
+    }
+}
+
+/// Skips a branch of `if` that is not taken, up to and including the `Else` or the `if` that ends
+/// it, which is returned. An `if` of fewer than two arguments has no `Then` of its own.
+fn skip_branch<T>(tokens: &mut impl Iterator<Item=ParsedToken<T, Token>>) -> Option<Token> {
+    let mut depth = 0;
+    for token in tokens {
+        match token {
+            ParsedToken::Operator(Token::Then) => depth += 1,
+            ParsedToken::Operator(Token::Else) if depth == 0 => return Some(Token::Else),
+            // An unbalanced brace or bracket, which must not be skipped over silently
+            ParsedToken::Operator(bracket @ (Token::OpenBrace | Token::CloseBrace | Token::OpenBracket | Token::CloseBracket)) =>
+                return Some(bracket),
+            ParsedToken::Operator(Token::Function(name, arguments)) if name == "if" && arguments >= 2 => {
+                if depth == 0 {
+                    return Some(Token::Function(name, arguments));
+                }
+                depth -= 1;
+            }
+            _ => {}
         }
     }
+    None
+}
+
+/// Replaces a percentage that is added or subtracted, `b % +` in reverse polish notation, with an
+/// increase or decrease of the left operand by that percentage
+pub(crate) fn relative_percentages(tokens: impl Iterator<Item=Token>) -> Vec<Token> {
+    let mut result: Vec<Token> = vec![];
+    for token in tokens {
+        let relative = match (&token, result.last()) {
+            (Token::Plus, Some(Token::Percent)) => Token::PercentIncrease,
+            (Token::Minus, Some(Token::Percent)) => Token::PercentDecrease,
+            _ => {
+                result.push(token);
+                continue;
+            }
+        };
+        result.pop();
+        result.push(relative);
+    }
+    result
 }
 
 impl Calculator {
-    pub(crate) fn calculate<T>(&self, expression: &str) -> Result<T, String>
+    /// Calculates an expression that must result in a single number
+    #[cfg(test)]
+    pub(crate) fn calculate<T>(&self, expression: &str) -> Result<T, CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        match self.evaluate(expression)? {
+            Value::Scalar(value) => Ok(value),
+            value => Err(CalculatorError::UnsupportedOperation(format!("{} result", value.kind()))),
+        }
+    }
+
+    pub(crate) fn evaluate<T>(&self, expression: &str) -> Result<Value<T>, CalculatorError>
         where T: FromStrValue + Math<T>
     {
+        if let Some(expression) = expression::symbolic(expression) {
+            return self.evaluate_symbolic(expression?);
+        }
+        let shunted = temporal::durations(expression.chars().tokenize()).into_iter().shunt();
+        let tokens = if self.percentages {
+            relative_percentages(shunted)
+        } else {
+            shunted.collect()
+        };
         let map =
-            expression
-                .chars()
-                .tokenize()
-                .shunt()
+            tokens
+                .into_iter()
                 .map(|t| {
                     match t {
+                        Token::T(v) if self.is_defined_unit(&v) => Ok(ParsedToken::Operator(Token::Unit(v))),
                         Token::T(v) => {
-                            let result: Result<T, String> = v.parse_operand::<T>().map_err(|_| format!("Cannot parse \"{}\"", v));
+                            let result: Result<T, CalculatorError> = v.parse_operand::<T>().map_err(|_| CalculatorError::Parse(v));
                             result.map(|f| ParsedToken::Operand(f))
                         }
                         _ => {
@@ -132,131 +345,873 @@ impl Calculator {
                         }
                     }
                 })
-                .collect::<Result<Vec<ParsedToken<T, Token>>, String>>();
-
-        let memory = map?.into_iter()
-            .fold(Ok(Memory::new()),
-                  |mut memory: Result<Memory<T>, String>, token| {
-                      if let Ok(memory) = memory.as_mut() {
-                          match token {
-                              ParsedToken::Operand(operand) => memory.push(operand),
-                              ParsedToken::Operator(o) => memory.push_operator(o)?,
-                          }
-                      }
-                      memory
-                  })?;
+                .collect::<Result<Vec<ParsedToken<T, Token>>, CalculatorError>>();
+
+        let mut memory = Memory::new(self);
+        let mut tokens = map?.into_iter();
+        while let Some(token) = tokens.next() {
+            match token {
+                ParsedToken::Operand(operand) => memory.push(operand),
+                ParsedToken::Operator(Token::Then) => {
+                    // A false condition skips to the second branch, an `if` with no second branch is an error
+                    if !memory.condition()? {
+                        match skip_branch(&mut tokens) {
+                            Some(Token::Function(_, arguments)) =>
+                                return Err(CalculatorError::ArgumentCount { function: "if".to_string(), expected: 3, found: arguments }),
+                            Some(Token::Else) | None => {}
+                            Some(bracket) => return Err(CalculatorError::UnbalancedBrackets(bracket.to_string())),
+                        }
+                    }
+                }
+                ParsedToken::Operator(Token::Else) => match skip_branch(&mut tokens) {
+                    Some(Token::Function(_, arguments)) => memory.end_if(arguments)?,
+                    Some(Token::Else) | None => {}
+                    Some(bracket) => return Err(CalculatorError::UnbalancedBrackets(bracket.to_string())),
+                },
+                ParsedToken::Operator(Token::Function(name, arguments)) if name == "if" => memory.end_if(arguments)?,
+                ParsedToken::Operator(o) => memory.push_operator(o)?,
+            }
+        }
         memory.top()
     }
+
+    /// The result of symbolic functions such as `diff` is a value when all its names are known,
+    /// as in `diff(x^2, x, 3)`, otherwise it is kept as an expression
+    fn evaluate_symbolic<T>(&self, symbolic: Symbolic) -> Result<Value<T>, CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        let variable = |name: &str| symbolic.variables.iter().any(|variable| variable == name)
+            || !units::is_unit(name) && !self.is_defined_unit(name) && name.parse_operand::<T>().is_err();
+        if symbolic.expression.symbols().into_iter().any(variable) {
+            return Ok(Value::Expression(symbolic.expression));
+        }
+        self.evaluate(&symbolic.expression.to_string())
+    }
+
+    /// Units that are not built in, and so are not known to the tokenizer
+    fn is_defined_unit(&self, name: &str) -> bool {
+        self.units.contains_key(name) || self.rates.as_ref().is_some_and(|rates| rates.is_currency(name))
+    }
+
+    /// One of the unit `name`, base units are their own dimension and others are evaluated from
+    /// their definitions
+    fn unit<T>(&self, name: &str) -> Result<Value<T>, CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        if let Some(definition) = self.units.get(name) {
+            return self.evaluate(definition);
+        }
+        // Each currency is a dimension of its own
+        if self.rates.as_ref().is_some_and(|rates| rates.is_currency(name)) {
+            return Ok(Value::Quantity(Quantity::new(T::one(), Dimension::base(name))));
+        }
+        match units::lookup(name) {
+            Some(Unit::Base(unit)) => Ok(Value::Quantity(Quantity::new(T::one(), Dimension::base(unit)))),
+            Some(Unit::Derived(definition)) => self.evaluate(&definition),
+            None => Err(CalculatorError::Parse(name.to_string())),
+        }
+    }
 }
 
 #[cfg(test)]
 mod calculator_tests {
+    use crate::complex::Complex;
+    use crate::format::FormatOptions;
+    use crate::math::Magnitude;
+    use crate::integer::Integer;
+    use crate::rational::Rational;
+    use crate::word::Word;
+
     use super::*;
 
     #[test]
     fn constant() {
-        assert_eq!(Ok(1), Calculator {}.calculate("1"));
+        assert_eq!(Ok(1), Calculator::default().calculate("1"));
     }
 
     #[test]
     pub fn add() {
-        assert_eq!(Ok(3), Calculator {}.calculate("1+2"));
+        assert_eq!(Ok(3), Calculator::default().calculate("1+2"));
     }
 
     #[test]
     pub fn subtract() {
-        assert_eq!(Ok(2), Calculator {}.calculate("5-3"));
+        assert_eq!(Ok(2), Calculator::default().calculate("5-3"));
     }
 
     #[test]
     pub fn multiply() {
-        assert_eq!(Ok(18), Calculator {}.calculate("3*6"));
+        assert_eq!(Ok(18), Calculator::default().calculate("3*6"));
     }
 
     #[test]
     pub fn subtract_into_negative() {
-        assert_eq!(Ok(-1i32), Calculator {}.calculate("2-3"));
+        assert_eq!(Ok(-1i32), Calculator::default().calculate("2-3"));
     }
 
     #[test]
     pub fn integer_divide() {
-        assert_eq!(Ok(3), Calculator {}.calculate("7/2"));
+        assert_eq!(Ok(3), Calculator::default().calculate("7/2"));
     }
 
     #[test]
     pub fn floating_point_divide() {
-        assert_eq!(Ok(3.5), Calculator {}.calculate("7/2"));
+        assert_eq!(Ok(3.5), Calculator::default().calculate("7/2"));
     }
 
     #[test]
     pub fn unnecessary_brackets() {
-        assert_eq!(Ok(7), Calculator {}.calculate("1+(3*2)"));
+        assert_eq!(Ok(7), Calculator::default().calculate("1+(3*2)"));
     }
 
     #[test]
     pub fn brackets_changing_precedence() {
-        assert_eq!(Ok(8), Calculator {}.calculate("(1+3)*2"));
+        assert_eq!(Ok(8), Calculator::default().calculate("(1+3)*2"));
     }
 
     #[test]
     pub fn two_braces() {
-        assert_eq!(Ok(12), Calculator {}.calculate("(1+3)*(5-2)"));
+        assert_eq!(Ok(12), Calculator::default().calculate("(1+3)*(5-2)"));
     }
 
     #[test]
     pub fn power() {
-        assert_eq!(Ok(16), Calculator {}.calculate("2^4"));
+        assert_eq!(Ok(16), Calculator::default().calculate("2^4"));
+    }
+
+    #[test]
+    pub fn zero_to_negative_powers() {
+        let calculator = Calculator::default();
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<Complex<Rational>>("0^-1"));
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<Integer>("0^-1"));
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<Rational>("0√4"));
+        assert_eq!(Ok(Complex::real(Rational::new(1, 1))), calculator.calculate::<Complex<Rational>>("0^0"));
+        assert_eq!(Ok(f64::INFINITY), calculator.calculate("0^-1"));
     }
 
     #[test]
     pub fn root() {
-        assert_eq!(Ok(4f64), Calculator {}.calculate("2√16"));
-        assert_eq!(Ok(3f64), Calculator {}.calculate("3√27"));
+        assert_eq!(Ok(4f64), Calculator::default().calculate("2√16"));
+        assert_eq!(Ok(3f64), Calculator::default().calculate("3√27"));
     }
 
     #[test]
     pub fn percent() {
-        assert_eq!(Ok(0.95), Calculator {}.calculate("95%"));
+        assert_eq!(Ok(0.95), Calculator::default().calculate("95%"));
     }
 
     #[test]
     pub fn power_with_right_multiplier() {
-        let calculator = Calculator {};
+        let calculator = Calculator::default();
         assert_eq!(calculator.calculate::<i32>("(2^4)*2"), calculator.calculate("2^4*2"))
     }
 
     #[test]
     pub fn power_with_left_multiplier() {
-        let calculator = Calculator {};
+        let calculator = Calculator::default();
         assert_eq!(calculator.calculate::<i32>("3*(2^4)"), calculator.calculate("3*2^4"))
     }
 
     #[test]
     pub fn tau() {
-        assert_eq!(Ok(std::f64::consts::TAU), Calculator {}.calculate("tau"))
+        assert_eq!(Ok(std::f64::consts::TAU), Calculator::default().calculate("tau"))
     }
 
     #[test]
     pub fn pi() {
-        assert_eq!(Ok(std::f64::consts::PI), Calculator {}.calculate("pi"))
+        assert_eq!(Ok(std::f64::consts::PI), Calculator::default().calculate("pi"))
     }
 
     #[test]
     pub fn e() {
-        assert_eq!(Ok(std::f64::consts::E), Calculator {}.calculate("e"))
+        assert_eq!(Ok(std::f64::consts::E), Calculator::default().calculate("e"))
     }
 
     #[test]
     pub fn automatic_multiplication() {
-        assert_eq!(Ok(12), Calculator {}.calculate("(3)(4)"));
-        assert_eq!(Ok(12), Calculator {}.calculate("(3)4"));
-        assert_eq!(Ok(12), Calculator {}.calculate("3(4)"));
-        assert_eq!(Ok(24), Calculator {}.calculate("(2)3(4)"));
-        assert_eq!(Ok(5), Calculator {}.calculate("(2)3-1"));
+        assert_eq!(Ok(12), Calculator::default().calculate("(3)(4)"));
+        assert_eq!(Ok(12), Calculator::default().calculate("(3)4"));
+        assert_eq!(Ok(12), Calculator::default().calculate("3(4)"));
+        assert_eq!(Ok(24), Calculator::default().calculate("(2)3(4)"));
+        assert_eq!(Ok(5), Calculator::default().calculate("(2)3-1"));
     }
 
     #[test]
     pub fn multiplication_by_constant() {
-        assert_eq!(Ok(std::f64::consts::TAU), Calculator {}.calculate("2pi"))
+        assert_eq!(Ok(std::f64::consts::TAU), Calculator::default().calculate("2pi"))
+    }
+
+    #[test]
+    pub fn vector() {
+        assert_eq!("[1, 2, 3]", Calculator::default().evaluate::<f64>("[1, 2, 3]").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn vector_of_expressions() {
+        assert_eq!("[3, 8]", Calculator::default().evaluate::<f64>("[1+2, 2(3+1)]").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn vector_arithmetic() {
+        let calculator = Calculator::default();
+        assert_eq!("[5, 7]", calculator.evaluate::<f64>("[1, 2] + [4, 5]").unwrap().to_string());
+        assert_eq!("[-3, -3]", calculator.evaluate::<f64>("[1, 2] - [4, 5]").unwrap().to_string());
+        assert_eq!("[2, 4]", calculator.evaluate::<f64>("2[1, 2]").unwrap().to_string());
+        assert_eq!("[2, 4]", calculator.evaluate::<f64>("[1, 2] * 2").unwrap().to_string());
+        assert_eq!("[0.5, 1]", calculator.evaluate::<f64>("[1, 2] / 2").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn unbalanced_brackets() {
+        let calculator = Calculator::default();
+        let unbalanced = |bracket: &str| Err(CalculatorError::UnbalancedBrackets(bracket.to_string()));
+        assert_eq!(unbalanced("("), calculator.evaluate::<f64>("sin(1"));
+        assert_eq!(unbalanced("("), calculator.evaluate::<f64>("max(1, 2"));
+        assert_eq!(unbalanced("("), calculator.evaluate::<f64>("if(1 < 2, 3"));
+        assert_eq!(unbalanced("("), calculator.evaluate::<f64>("if(1 > 2, 3"));
+        assert_eq!(unbalanced("("), calculator.evaluate::<f64>("if(1 < 2, 3, 4"));
+        assert_eq!(unbalanced("["), calculator.evaluate::<f64>("[1"));
+        assert_eq!(unbalanced(")"), calculator.evaluate::<f64>("1)"));
+        assert_eq!(unbalanced("]"), calculator.evaluate::<f64>("[1]]"));
+    }
+
+    #[test]
+    pub fn vector_dimension_mismatch() {
+        assert_eq!(
+            Err(CalculatorError::DimensionMismatch { expected: 2, found: 3 }),
+            Calculator::default().evaluate::<f64>("[1, 2] + [4, 5, 6]")
+        );
+    }
+
+    #[test]
+    pub fn unsupported_vector_operation() {
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("scalar + vector".to_string())),
+            Calculator::default().evaluate::<f64>("1 + [4, 5, 6]")
+        );
+    }
+
+    #[test]
+    pub fn vector_products() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(32f64), calculator.calculate("dot([1, 2, 3], [4, 5, 6])"));
+        assert_eq!("[-3, 6, -3]", calculator.evaluate::<f64>("cross([1, 2, 3], [4, 5, 6])").unwrap().to_string());
+        assert_eq!(Ok(5f64), calculator.calculate("norm([3, 4])"));
+    }
+
+    #[test]
+    pub fn element_wise_function() {
+        assert_eq!("[1, 2, 3]", Calculator::default().evaluate::<f64>("sqrt([1, 4, 9])").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn function() {
+        assert_eq!(Ok(3f64), Calculator::default().calculate("sqrt(9)"));
+        assert_eq!(Ok(7f64), Calculator::default().calculate("1 + 2sqrt(9)"));
+    }
+
+    #[test]
+    pub fn function_argument_count() {
+        assert_eq!(
+            Err(CalculatorError::ArgumentCount { function: "dot".to_string(), expected: 2, found: 1 }),
+            Calculator::default().calculate::<f64>("dot([1])")
+        );
+    }
+
+    #[test]
+    pub fn brackets_after_operand() {
+        assert_eq!(Ok(14), Calculator::default().calculate("2(3+4)"));
+    }
+
+    #[test]
+    pub fn complex_vector() {
+        assert_eq!("[i, 2]", Calculator::default().evaluate::<Complex<f64>>("[i, 2]").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn matrix() {
+        assert_eq!("[[1, 2], [3, 4]]", Calculator::default().evaluate::<f64>("[[1, 2], [3, 4]]").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn matrix_arithmetic() {
+        let calculator = Calculator::default();
+        assert_eq!("[[7, 10], [15, 22]]", calculator.evaluate::<f64>("[[1, 2], [3, 4]]^2").unwrap().to_string());
+        assert_eq!("[[1, 2], [3, 4]]", calculator.evaluate::<f64>("[[1, 2], [3, 4]] * identity(2)").unwrap().to_string());
+        assert_eq!("[5, 11]", calculator.evaluate::<f64>("[[1, 2], [3, 4]] * [1, 2]").unwrap().to_string());
+        assert_eq!("[[1, 3], [2, 4]]", calculator.evaluate::<f64>("transpose([[1, 2], [3, 4]])").unwrap().to_string());
+        assert_eq!(Ok(-2f64), calculator.calculate("det([[1, 2], [3, 4]])"));
+        assert_eq!("[[1, -1], [-1, 2]]", calculator.evaluate::<f64>("inv([[2, 1], [1, 1]])").unwrap().to_string());
+    }
+
+    #[test]
+    pub fn complex_matrix() {
+        assert_eq!(
+            "[[-1, 0], [0, -1]]",
+            Calculator::default().evaluate::<Complex<f64>>("[[i, 0], [0, i]]^2").unwrap().to_string()
+        );
+    }
+
+    #[test]
+    pub fn singular_matrix() {
+        assert_eq!(Err(CalculatorError::SingularMatrix), Calculator::default().evaluate::<f64>("inv([[1, 2], [2, 4]])"));
+    }
+
+    #[test]
+    pub fn solve() {
+        let calculator = Calculator::default();
+        assert_eq!("[1, 2]", calculator.evaluate::<f64>("solve([[2, 0], [0, 4]], [2, 8])").unwrap().to_string());
+        assert_eq!("[-i, i]", calculator.evaluate::<Complex<f64>>("solve([[i, 0], [0, 1]], [1, i])").unwrap().to_string());
+        assert_eq!(Err(CalculatorError::SingularMatrix), calculator.evaluate::<f64>("solve([[1, 2], [2, 4]], [1, 2])"));
+    }
+
+    fn assert_close(expected: Complex<f64>, expression: &str) {
+        let actual: Complex<f64> = Calculator::default().calculate(expression).unwrap();
+        assert!((expected - actual).magnitude() < 1e-12, "{} = {}, expected {}", expression, actual, expected);
+    }
+
+    #[test]
+    pub fn polar_input() {
+        assert_close(Complex::new(0f64, 2f64), "2∠90°");
+        assert_close(Complex::new(1f64, 1f64), "2^(1/2)∠45°");
+        assert_close(Complex::new(0f64, 2f64), "2 cis(pi/2)");
+        assert_close(Complex::new(1f64, 2f64), "1 + 2∠90°");
+    }
+
+    #[test]
+    pub fn polar_input_of_real_numbers() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(-2f64), calculator.calculate("2∠180°"));
+        assert_eq!(Ok(2f64), calculator.calculate("2 cis(0)"));
+        assert_eq!(Err(CalculatorError::ComplexResult), calculator.calculate::<f64>("2∠45°"));
+    }
+
+    #[test]
+    pub fn complex_parts() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(Complex::new(5f64, 0f64)), calculator.calculate("abs(3 + 4i)"));
+        assert_eq!(Ok(Complex::new(3f64, 0f64)), calculator.calculate("re(3 + 4i)"));
+        assert_eq!(Ok(Complex::new(4f64, 0f64)), calculator.calculate("im(3 + 4i)"));
+        assert_eq!(Ok(Complex::new(3f64, -4f64)), calculator.calculate("conj(3 + 4i)"));
+        assert_close(Complex::new(std::f64::consts::FRAC_PI_2, 0f64), "arg(2i)");
+        assert_eq!(Ok(std::f64::consts::PI), calculator.calculate("arg(0 - 1)"));
+    }
+
+    #[test]
+    pub fn complex_literals() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(Complex::new(3f64, 4f64)), calculator.calculate("3+4i"));
+        assert_eq!(Ok(Complex::new(0f64, 5f64)), calculator.calculate("2.5i * 2"));
+        assert_eq!(Ok(Complex::new(11f64, 2f64)), calculator.calculate("(1+2j)(3-4j)"));
+        assert_eq!(Ok(Complex::new(0f64, 1500f64)), calculator.calculate("1.5e3i"));
+        assert_eq!(Err(CalculatorError::Parse("4i".to_string())), calculator.calculate::<f64>("4i"));
+    }
+
+    #[test]
+    pub fn integer_division_by_zero() {
+        assert_eq!(Err(CalculatorError::DivisionByZero), Calculator::default().calculate::<i32>("1/0"));
+    }
+
+    #[test]
+    pub fn single_precision_complex() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(Complex::new(-5f32, 12f32)), calculator.calculate("(2+3i)^2"));
+        assert_eq!(Ok(Complex::new(0.5f32, -0.5f32)), calculator.calculate("1/(1+i)"));
+    }
+
+    #[test]
+    pub fn gaussian_rationals() {
+        let calculator = Calculator::default();
+        assert_eq!(
+            Ok(Complex::new(Rational::new(1, 4), Rational::new(1, 3))),
+            calculator.calculate("(1 + i/2)^2 / 3")
+        );
+        assert_eq!(
+            "[[1/2, -(1/2)i], [0, 1]]",
+            calculator.evaluate::<Complex<Rational>>("inv([[2, i], [0, 1]])").unwrap().to_string()
+        );
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<Complex<Rational>>("i/0"));
+    }
+
+    #[test]
+    pub fn scientific_notation() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(1000f64), calculator.calculate("1E+3"));
+        assert_eq!(Ok(0.00025f64), calculator.calculate("2.5e-4"));
+        assert_eq!(Ok(1f64 + 1e-9f64), calculator.calculate("1 + 1e-9"));
+        assert_eq!(Ok(2f64 * std::f64::consts::E), calculator.calculate("2e"));
+        assert_eq!(Ok(2f64 * std::f64::consts::E - 4f64), calculator.calculate("2e - 4"));
+    }
+
+    #[test]
+    pub fn radix_literals() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(32), calculator.calculate::<i32>("0x1F + 1"));
+        assert_eq!(Ok(25f64), calculator.calculate("0b1010 + 0o17"));
+        assert_eq!(Ok(Integer::new(1 << 40)), calculator.calculate("0x100 ^ 5"));
+        assert_eq!(Ok(-1), calculator.calculate::<i32>("0xFFFFFFFF"));
+        assert_eq!(Ok(i32::MIN), calculator.calculate::<i32>("0x80000000"));
+        assert!(calculator.calculate::<i32>("0x100000000").is_err());
+        for literal in ["0b102", "0o19", "0b1010.1", "0x1.5"] {
+            assert_eq!(Err(CalculatorError::Parse(literal.to_string())), calculator.calculate::<f64>(literal));
+            assert_eq!(Err(CalculatorError::Parse(literal.to_string())), calculator.calculate::<i32>(literal));
+        }
+    }
+
+    #[test]
+    pub fn integer_overflow() {
+        let calculator = Calculator::default();
+        for expression in ["2147483647 + 1", "-2147483647 - 2", "65536*65536", "2^31", "-(-2147483647 - 1)", "[[65536]]*[[65536]]",
+            "abs(-2147483647 - 1)", "norm(-2147483647 - 1)", "approx(2147483647, -1, 1)"] {
+            assert_eq!(Err(CalculatorError::Overflow), calculator.calculate::<i32>(expression), "{}", expression);
+        }
+        assert_eq!(Ok(i32::MIN), calculator.calculate::<i32>("-2147483647 - 1"));
+        assert_eq!(Ok(0), calculator.calculate::<i32>("2^-1"));
+        assert_eq!(Ok(Word::<8, false>::new(0)), calculator.calculate("255 + 1"));
+    }
+
+    #[test]
+    pub fn factorial() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(120f64), calculator.calculate("5!"));
+        assert_eq!(Ok(1f64), calculator.calculate("0!"));
+        assert_eq!(Ok(722f64), calculator.calculate("2 + 3!!"));
+        assert_eq!(Ok(64f64), calculator.calculate("2^3!"));
+        assert_close(Complex::real(std::f64::consts::PI.sqrt() / 2f64), "0.5!");
+        assert_eq!(Ok(479_001_600), calculator.calculate::<i32>("12!"));
+        assert_eq!(Err(CalculatorError::Overflow), calculator.calculate::<i32>("13!"));
+        assert_eq!(Err(CalculatorError::Overflow), calculator.calculate::<f64>("171!"));
+        assert!(calculator.calculate::<f64>("(0 - 1)!").is_err());
+        assert_eq!(
+            Ok(Integer::new(2_432_902_008_176_640_000) * Integer::new(21)),
+            calculator.calculate("21!")
+        );
+        assert_eq!(Ok(Complex::real(Rational::new(24, 1))), calculator.calculate("4!"));
+        assert_eq!(Ok(Word::<8, false>::new(0)), calculator.calculate("10!"));
+    }
+
+    #[test]
+    pub fn gamma_functions() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(24f64), calculator.calculate("gamma(5)"));
+        assert_close(Complex::real(std::f64::consts::PI.sqrt()), "gamma(0.5)");
+        assert_close(Complex::real(863.231_987_192_405_4), "lgamma(201)");
+        assert!(calculator.calculate::<f64>("gamma(0)").is_err());
+        assert_close(Complex::new(0.498_015_668_118_349_2, -0.154_949_828_301_809), "gamma(1 + i)");
+        assert_eq!(Ok(Integer::new(720)), calculator.calculate("gamma(7)"));
+    }
+
+    #[test]
+    pub fn combinations() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(10f64), calculator.calculate("nCr(5, 2)"));
+        assert_eq!(Ok(20f64), calculator.calculate("nPr(5, 2)"));
+        assert_eq!(Ok(0f64), calculator.calculate("nCr(2, 5)"));
+        assert_close(Complex::real(2.5f64 * 1.5f64 / 2f64), "nCr(2.5, 2)");
+        assert_eq!(
+            "100891344545564193334812497256".parse::<Integer>(),
+            calculator.calculate("nCr(100, 50)")
+        );
+        assert_eq!(Ok(Integer::new(9_900)), calculator.calculate("nPr(100, 2)"));
+        assert_eq!(Ok(0), calculator.calculate::<i32>("nPr(2, 5)"));
+    }
+
+    #[test]
+    pub fn percentages_are_fractions_by_default() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(100.15f64), calculator.calculate("100 + 15%"));
+    }
+
+    #[test]
+    pub fn percentage_mode() {
+        let calculator = Calculator { percentages: true, ..Calculator::default() };
+        // Adding or subtracting a percentage increases or decreases by that percentage
+        assert_eq!(Ok(115f64), calculator.calculate("100 + 15%"));
+        assert_eq!(Ok(85f64), calculator.calculate("100 - 15%"));
+        assert_eq!(Ok(120f64), calculator.calculate("50 + 50 + 20%"));
+        assert_eq!(Ok(132f64), calculator.calculate("100 + (10 + 20)% + 2"));
+        // Otherwise a percentage is a fraction
+        assert_eq!(Ok(15f64), calculator.calculate("100 * 15%"));
+        assert_eq!(Ok(0.15f64), calculator.calculate("15%"));
+        // Only the percentage directly added or subtracted is relative
+        assert_eq!(Ok(100.3f64), calculator.calculate("100 + 15% * 2"));
+        assert_eq!(Ok(Rational::new(115, 1)), calculator.calculate("100 + 15%"));
+        assert_eq!(Ok(115), calculator.calculate::<i32>("100 + 15%"));
+        assert_eq!(Ok(85), calculator.calculate::<i32>("100 - 15%"));
+        let metres = calculator.evaluate::<f64>("3 m + 10%").map(|value| value.to_string());
+        assert_eq!(Ok("3.3 m".to_string()), metres);
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("vector + scalar".to_string())),
+            calculator.evaluate::<f64>("[1, 2] + 10%")
+        );
+    }
+
+    #[test]
+    pub fn percentage_functions() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(30f64), calculator.calculate("percentof(15, 200)"));
+        assert_eq!(Ok(-25f64), calculator.calculate("percentchange(80, 60)"));
+        assert_eq!(Ok(25f64), calculator.calculate("aspercent(5, 20)"));
+        assert_eq!(Ok(30), calculator.calculate::<i32>("percentof(15, 200)"));
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<i32>("aspercent(1, 0)"));
+    }
+
+    #[test]
+    pub fn modulo() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(1f64), calculator.calculate("7 mod 3"));
+        assert_eq!(Ok(2f64), calculator.calculate("(0 - 7) mod 3"));
+        assert_eq!(Ok(2f64), calculator.calculate("(0 - 7) mod (0 - 3)"));
+        assert_eq!(Ok(0.5f64), calculator.calculate("2.5 mod 1"));
+        assert_eq!(Ok(2), calculator.calculate::<i32>("(0 - 7) mod 3"));
+        assert_eq!(Ok(Integer::new(2)), calculator.calculate("(0 - 7) mod 3"));
+        assert_eq!(Ok(Rational::new(1, 2)), calculator.calculate("(0 - 5/2) mod 1"));
+        assert_eq!(Ok(Complex::real(2f64)), calculator.calculate("(0 - 7) mod 3"));
+        assert_eq!(Ok(Word::<8, true>::new(2)), calculator.calculate("(0 - 7) mod 3"));
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<f64>("1 mod 0"));
+        assert!(calculator.calculate::<Complex<f64>>("i mod 2").is_err());
+    }
+
+    #[test]
+    pub fn quotient() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(2f64), calculator.calculate("7 // 3"));
+        assert_eq!(Ok(-3f64), calculator.calculate("(0 - 7) // 3"));
+        assert_eq!(Ok(3f64), calculator.calculate("(0 - 7) // (0 - 3)"));
+        assert_eq!(Ok(-3), calculator.calculate::<i32>("(0 - 7) // 3"));
+        assert_eq!(Ok(Integer::new(3)), calculator.calculate("(0 - 7) // (0 - 3)"));
+        assert_eq!(Ok(Rational::new(-3, 1)), calculator.calculate("(0 - 5/2) // 1"));
+        assert_eq!(Ok(3f64), calculator.calculate("1 + 5 // 2"));
+        assert_eq!(Ok(0.5f64), calculator.calculate("50% mod 1"));
+    }
+
+    #[test]
+    pub fn bitwise() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(0x0F), calculator.calculate::<i32>("0xFF & 0x0F"));
+        assert_eq!(Ok(0xF7), calculator.calculate::<i32>("0xF0 | 7"));
+        assert_eq!(Ok(6), calculator.calculate::<i32>("5 xor 3"));
+        assert_eq!(Ok(-6), calculator.calculate::<i32>("~5"));
+        assert_eq!(Ok(0x10 | 0x20), calculator.calculate::<i32>("1 << 4 | 0x40 >> 1"));
+        assert_eq!(Ok(1 << 4), calculator.calculate::<i32>("1 << 2 + 2"));
+        assert_eq!(Ok(Value::Boolean(true)), calculator.evaluate::<i32>("5 & 3 == 1"));
+        assert_eq!(Ok(Value::Boolean(true)), calculator.evaluate::<i32>("6 xor 3 | 1 == 5"));
+        assert_eq!(Ok(i32::MIN), calculator.calculate::<i32>("rotr(1, 1)"));
+        assert_eq!(Ok(Integer::new(1 << 40)), calculator.calculate("1 << 40"));
+        assert_eq!(Ok(Word::<8, false>::new(0x0F)), calculator.calculate("~0xF0"));
+        assert_eq!(Ok(Word::<8, false>::new(0x2D)), calculator.calculate("rotl(0xD2, 4)"));
+    }
+
+    #[test]
+    pub fn number_theory() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(6f64), calculator.calculate("gcd(12, 18)"));
+        assert_eq!(Ok(36), calculator.calculate::<i32>("lcm(12, 18)"));
+        assert_eq!(Ok(1f64), calculator.calculate("isprime(97)"));
+        assert_eq!(Ok(0f64), calculator.calculate("isprime(91)"));
+        assert_eq!(Ok(445), calculator.calculate::<i32>("modpow(4, 13, 497)"));
+        assert_eq!(Ok(Integer::new(4)), calculator.calculate("modinv(3, 11)"));
+        assert_eq!(Ok(Integer::new(1)), calculator.calculate("isprime(2^127 - 1)"));
+        assert_eq!(
+            Err(CalculatorError::NoInverse { value: "2".to_string(), modulus: "4".to_string() }),
+            calculator.calculate::<i32>("modinv(2, 4)")
+        );
+        assert_eq!(Err(CalculatorError::IntegerOnly("gcd".to_string())), calculator.calculate::<f64>("gcd(1.5, 3)"));
+    }
+
+    #[test]
+    pub fn factors() {
+        let calculator = Calculator::default();
+        assert_eq!("2^3 · 3 · 5", calculator.evaluate::<f64>("factor(120)").unwrap().to_string());
+        assert_eq!("-1 · 7", calculator.evaluate::<i32>("factor(0 - 7)").unwrap().to_string());
+        assert_eq!("1", calculator.evaluate::<Integer>("factor(1)").unwrap().to_string());
+        assert_eq!(
+            "2^64 · 3",
+            calculator.evaluate::<Integer>("factor(3 * 2^64)").unwrap().to_string()
+        );
+        assert_eq!(Ok(121f64), calculator.calculate("factor(120) + 1"));
+        assert_eq!(Ok(1f64), calculator.calculate("isprime(factor(7))"));
+    }
+
+    #[test]
+    pub fn rounding() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(3f64), calculator.calculate("round(2.5)"));
+        assert_eq!(Ok(0.667), calculator.calculate("round(2 / 3, 3)"));
+        assert_eq!(Ok(1200f64), calculator.calculate("round(1234, 0 - 2)"));
+        assert_eq!(Ok(-3f64), calculator.calculate("floor(0 - 2.5)"));
+        assert_eq!(Ok(-2f64), calculator.calculate("ceil(0 - 2.5)"));
+        assert_eq!(Ok(-2f64), calculator.calculate("trunc(0 - 2.5)"));
+        assert_eq!(Ok(-0.5), calculator.calculate("frac(0 - 2.5)"));
+        assert_eq!(Ok(-1f64), calculator.calculate("sign(0 - 2.5)"));
+        assert_eq!(Ok(0f64), calculator.calculate("sign(0)"));
+        assert_eq!(Ok(2.5), calculator.calculate("abs(0 - 2.5)"));
+        assert_eq!("[1, 2, 3]", calculator.evaluate::<f64>("round([1.2, 2.4, 2.6])").unwrap().to_string());
+        assert!(calculator.calculate::<f64>("round(1, 0.5)").is_err());
+    }
+
+    #[test]
+    pub fn rounding_modes() {
+        let even = Calculator { rounding: Rounding::HalfEven, ..Calculator::default() };
+        let toward_zero = Calculator { rounding: Rounding::TowardZero, ..Calculator::default() };
+        assert_eq!(Ok(2f64), even.calculate("round(2.5)"));
+        assert_eq!(Ok(4f64), even.calculate("round(3.5)"));
+        assert_eq!(Ok(Rational::new(31, 25)), even.calculate("round(1.245, 2)"));
+        assert_eq!(Ok(Rational::new(-31, 25)), toward_zero.calculate("round(0 - 1.249, 2)"));
+        assert_eq!(Ok(1_200), even.calculate::<i32>("round(1250, 0 - 2)"));
+        assert_eq!(Ok(Integer::new(1_300)), Calculator::default().calculate("round(1250, 0 - 2)"));
+        assert_eq!(Ok(Word::<16, true>::new(250)), Calculator::default().calculate("round(245, 0 - 1)"));
+    }
+
+    #[test]
+    pub fn rounding_complex_numbers() {
+        assert_close(Complex::new(2f64, -3f64), "round(1.6 - 2.5i)");
+        assert_close(Complex::new(0.5, -0.5), "frac(1.5 - 2.5i)");
+        assert_close(Complex::new(1f64, -1f64), "sign(3 - 4i)");
+    }
+
+    #[test]
+    pub fn statistics() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(10f64), calculator.calculate("sum(1, 2, 3, 4)"));
+        assert_eq!(Ok(2.5), calculator.calculate("mean([1, 2, 3, 4])"));
+        assert_eq!(Ok(3f64), calculator.calculate("median(5, [1, 3])"));
+        assert_eq!(Ok(2f64), calculator.calculate("mode(1, 2, 2, 3)"));
+        assert_eq!(Ok(-1f64), calculator.calculate("min(3, 0 - 1, 2)"));
+        assert_eq!(Ok(9f64), calculator.calculate("max(2, 3 * 3, 4)"));
+        assert_eq!(Ok(7f64), calculator.calculate("range(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_eq!(Ok(2f64), calculator.calculate("stdevp(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_eq!(Ok(4f64), calculator.calculate("varp([2, 4, 4, 4, 5, 5, 7, 9])"));
+        assert_eq!(Ok(29f64), calculator.calculate("percentile(40, 15, 20, 35, 40, 50)"));
+        assert_eq!(Ok(29f64), calculator.calculate("percentile([15, 20, 35, 40, 50], 40)"));
+        assert!(matches!(calculator.calculate::<f64>("percentile([1, 2, 3])"), Err(CalculatorError::UnsupportedOperation(_))));
+        assert_eq!(Ok(Rational::new(32, 7)), calculator.calculate("var(2, 4, 4, 4, 5, 5, 7, 9)"));
+        assert_eq!(Ok(Rational::new(7, 3)), calculator.calculate("mean(1, 2, 4)"));
+        assert_eq!(Ok(1f64), calculator.calculate("stdev(1, 2, 3)"));
+    }
+
+    #[test]
+    pub fn statistics_of_complex_numbers() {
+        let calculator = Calculator::default();
+        assert_close(Complex::new(2f64, 1f64), "mean(1 + 2i, 3)");
+        assert_eq!(
+            Err(CalculatorError::Unordered("median".to_string())),
+            calculator.calculate::<Complex<f64>>("median(1 + 2i, 3)")
+        );
+        assert!(calculator.calculate::<f64>("mean([[1, 2], [3, 4]])").is_err());
+    }
+
+    #[test]
+    pub fn negation() {
+        let calculator = Calculator::default();
+        assert_eq!(Ok(1f64), calculator.calculate("-2 + 3"));
+        assert_eq!(Ok(-4f64), calculator.calculate("-2^2"));
+        assert_eq!(Ok(0.5), calculator.calculate("2^-1"));
+        assert_eq!(Ok(5f64), calculator.calculate("2 - -3"));
+        assert_eq!(Ok(-6f64), calculator.calculate("2 * -3"));
+        assert_eq!(Ok(-6f64), calculator.calculate("-3!"));
+        assert_eq!("[-1, 2]", calculator.evaluate::<f64>("[-1, 2]").unwrap().to_string());
+        assert_eq!(Ok(Word::<8, false>::new(255)), calculator.calculate("-1"));
+    }
+
+    #[test]
+    pub fn units() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("6 m/s".to_string()), result("3 m * 2 s^-1"));
+        assert_eq!(Ok("3000 m".to_string()), result("3 km"));
+        assert_eq!(Ok("50 m/s".to_string()), result("100 m / 2 s"));
+        assert_eq!(Ok("2 m^2".to_string()), result("2 m^2"));
+        assert_eq!(Ok("0.5 m^-1".to_string()), result("1/2 m"));
+        assert_eq!(Ok("5 m/s^2".to_string()), result("10 N / (2 kg)"));
+        assert_eq!(Ok("3 m".to_string()), result("sqrt(9 m^2)"));
+        assert_eq!(Ok("2 m".to_string()), result("3√(8 m^3)"));
+        assert_eq!(Ok("-1.5 m".to_string()), result("-(1 m + 50 cm)"));
+        assert_eq!(Ok("300 s (PT5M, 5 minutes)".to_string()), result("5 min"));
+        assert_eq!(Ok(2f64), calculator.calculate("1 km / (500 m)"));
+        assert_eq!(Ok(2f64), calculator.calculate("min(5, 2)"));
+    }
+
+    #[test]
+    pub fn incompatible_units() {
+        let calculator = Calculator::default();
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            calculator.evaluate::<f64>("1 m + 1 s")
+        );
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "1".to_string())),
+            calculator.evaluate::<f64>("1 m + 1")
+        );
+        assert!(calculator.evaluate::<f64>("sqrt(2 m)").is_err());
+        assert!(calculator.evaluate::<f64>("sin(2 m)").is_err());
+        assert!(calculator.evaluate::<f64>("2^(1 m)").is_err());
+        assert!(calculator.evaluate::<f64>("(2 m)!").is_err());
+    }
+
+    #[test]
+    pub fn dates_and_durations() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<Rational>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("2027-01-15 (Friday 15 January 2027)".to_string()), result("2026-10-17 + 90 days"));
+        assert_eq!(Ok("2026-10-10 (Saturday 10 October 2026)".to_string()), result("2026-10-17 - 1 week"));
+        assert_eq!(Ok("69 days".to_string()), calculator.evaluate_conversion::<Rational>("2026-12-25 - 2026-10-17 in days", &FormatOptions::default())
+            .map(|(value, _)| value.to_string()));
+        assert_eq!(Ok("49200 s (PT13H40M, 13 hours 40 minutes)".to_string()), result("3h 25m * 4"));
+        assert_eq!(Ok("2026-10-17T02:30:00Z (Saturday 17 October 2026 02:30)".to_string()), result("2026-10-17 + 2h30m"));
+        assert_eq!(Ok("2026-10-17T15:30:00+02:00 (Saturday 17 October 2026 15:30)".to_string()), result("2026-10-17T14:30+02:00 + 1 h"));
+        assert_eq!(Ok(2016), calculator.calculate("2026-10"));
+        assert!(result("today").is_ok());
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("date + scalar".to_string())), result("2026-10-17 + 3"));
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            result("2026-10-17 + 3 m")
+        );
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("date + date".to_string())), result("2026-10-17 + 2026-10-17"));
+        assert_eq!(Err(CalculatorError::Overflow), result("2026-10-17 + 1e300 s"));
+        assert_eq!(Err(CalculatorError::Parse("2026-02-29".to_string())), result("2026-02-29"));
+    }
+
+    #[test]
+    pub fn comparisons() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("true".to_string()), result("1 + 2 == 3"));
+        assert_eq!(Ok("false".to_string()), result("2^10 < 1000"));
+        assert_eq!(Ok("true".to_string()), result("2 >= 2 and 3 > 2 and 1 <= 1 and 1 != 2"));
+        assert_eq!(Ok("true".to_string()), result("1 km > 999 m"));
+        assert_eq!(Ok("true".to_string()), result("2026-10-17 < 2026-10-17T00:01"));
+        assert_eq!(Ok("true".to_string()), result("(1 < 2) == (3 < 4)"));
+        assert_eq!(Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())), result("1 m < 1 s"));
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("boolean < scalar".to_string())), result("1 < 2 < 3"));
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("vector == vector".to_string())), result("[1] == [1]"));
+    }
+
+    #[test]
+    pub fn comparisons_need_real_numbers() {
+        let calculator = Calculator::default();
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            calculator.evaluate::<Complex<f64>>("(1 + 1i) * (1 - 1i) == 2")
+        );
+        assert_eq!(
+            Err(CalculatorError::Unordered("<".to_string())),
+            calculator.evaluate::<Complex<f64>>("1i < 2")
+        );
+        assert_eq!(Ok(Value::Boolean(true)), calculator.evaluate::<Complex<f64>>("1i == 1i"));
+        assert_eq!(Ok(Value::Boolean(false)), calculator.evaluate::<Complex<f64>>("1i == 2i"));
+        assert_eq!(Ok(Value::Boolean(true)), calculator.evaluate::<Complex<Rational>>("1/3 + 1i != 1/3 + 2i"));
+    }
+
+    #[test]
+    pub fn tolerance() {
+        let mut calculator = Calculator::default();
+        let result = |calculator: &Calculator, expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("true".to_string()), result(&calculator, "0.1 + 0.2 == 0.3"));
+        assert_eq!(Ok("true".to_string()), result(&calculator, "0.1 + 0.2 <= 0.3 and not (0.1 + 0.2 > 0.3)"));
+        assert_eq!(Ok("true".to_string()), result(&calculator, "sin(pi) == 0"));
+        assert_eq!(Ok("false".to_string()), result(&calculator, "1.001 ≈ 1"));
+        assert_eq!(Ok("true".to_string()), result(&calculator, "approx(1.001, 1, 0.01)"));
+        assert_eq!(Ok("false".to_string()), result(&calculator, "approx(1.1, 1, 0.01)"));
+        assert_eq!(Ok("true".to_string()), result(&calculator, "approx(1 km, 1001 m, 2 m)"));
+        assert_eq!(Ok("false".to_string()), result(&calculator, "0 / 0 == 0 / 0"));
+        calculator.tolerance.set("rel 0.01").unwrap();
+        assert_eq!(Ok("true".to_string()), result(&calculator, "1.001 == 1"));
+        assert_eq!(Ok("true".to_string()), result(&calculator, "100 m ≈ 100.5 m"));
+        calculator.tolerance.set("exact").unwrap();
+        assert_eq!(Ok("false".to_string()), result(&calculator, "0.1 + 0.2 == 0.3"));
+        assert_eq!(
+            Ok(Value::Boolean(true)),
+            Calculator::default().evaluate::<Complex<f64>>("e^(pi i) ≈ -1")
+        );
+    }
+
+    #[test]
+    pub fn derivatives() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("3*x^2 + cos(x)".to_string()), result("diff(x^3 + sin(x), x)"));
+        assert_eq!(Ok("12".to_string()), result("diff(x^3, x, 2)"));
+        assert_eq!(Ok("13".to_string()), result("diff(x^3, x, 2) + 1"));
+        assert_eq!(Ok("6*x".to_string()), result("diff(diff(x^3, x), x)"));
+        assert_eq!(Ok("2*t".to_string()), result("diff(t^2 + y, t)"));
+        // Outside symbolic functions x is only multiplication
+        assert_eq!(Ok("6".to_string()), result("2 x 3"));
+        assert_eq!(Err(CalculatorError::EmptyStack), result("3 x + 1"));
+        assert_eq!(Ok("1".to_string()), Calculator::default().evaluate::<Rational>("diff(x^2, x, 1/2)").map(|value| value.to_string()));
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("expression to km".to_string())),
+            calculator.evaluate_conversion::<f64>("diff(x^2, x) to km", &FormatOptions::default()).map(|(value, _)| value)
+        );
+    }
+
+    #[test]
+    pub fn simplification() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("2*x".to_string()), result("simplify(x + x*1 + 0)"));
+        assert_eq!(Ok("x + 2".to_string()), result("simplify((2x + 4)/2)"));
+        assert_eq!(Ok("5".to_string()), result("simplify(2 + 3)"));
+        assert_eq!(Ok("1/x".to_string()), result("diff(ln(2x), x)"));
+        assert_eq!(
+            Err(CalculatorError::ArgumentCount { function: "simplify".to_string(), expected: 1, found: 2 }),
+            result("simplify(x, 2)")
+        );
+    }
+
+    #[test]
+    pub fn exact_numbers_need_approx() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<Complex<Rational>>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("false".to_string()), result("1 + 1/10^12 == 1"));
+        assert_eq!(Ok("true".to_string()), result("1 + 1/10^12 ≈ 1"));
+        assert_eq!(Ok("true".to_string()), result("approx(1 + 1/10^12, 1)"));
+    }
+
+    #[test]
+    pub fn logic() {
+        let calculator = Calculator::default();
+        let result = |expression: &str| calculator.evaluate::<f64>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("true".to_string()), result("1 > 2 or 2 > 1"));
+        assert_eq!(Ok("false".to_string()), result("not 1 < 2 and 1 == 1"));
+        assert_eq!(Ok("true".to_string()), result("not (1 > 2)"));
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("scalar and boolean".to_string())), result("1 and 1 < 2"));
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("not scalar".to_string())), result("not 1"));
+        assert_eq!(Err(CalculatorError::UnsupportedOperation("- boolean".to_string())), result("-(1 < 2)"));
+    }
+
+    #[test]
+    pub fn conditional() {
+        let calculator = Calculator::default();
+        // Tax of 20% above 12570 and 40% above 50270
+        let tax = |income: f64| calculator.calculate::<f64>(&format!(
+            "if({0} > 50270, 7540 + ({0} - 50270) * 0.4, if({0} > 12570, ({0} - 12570) * 0.2, 0))", income
+        ));
+        assert_eq!(Ok(0f64), tax(10000f64));
+        assert_eq!(Ok(1486f64), tax(20000f64));
+        assert_eq!(Ok(11432f64), tax(60000f64));
+        assert_eq!("[1, 2]", calculator.evaluate::<f64>("if(1 == 1, [1, 2], 3)").unwrap().to_string());
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("if(scalar)".to_string())),
+            calculator.evaluate::<f64>("if(1, 2, 3)")
+        );
+        // Only the branch that is taken is evaluated
+        assert_eq!(Ok(0), calculator.calculate::<i32>("if(0 == 0, 0, 1/0)"));
+        assert_eq!(Ok(2), calculator.calculate::<i32>("if(0 == 1, 1/0, if(1 == 1, 2, 1/0))"));
+        assert_eq!(Err(CalculatorError::DivisionByZero), calculator.calculate::<i32>("if(0 == 1, 0, 1/0)"));
+        assert_eq!(
+            Err(CalculatorError::ArgumentCount { function: "if".to_string(), expected: 3, found: 2 }),
+            calculator.evaluate::<f64>("if(1 == 2, 3)")
+        );
+    }
+
+    #[test]
+    pub fn bitwise_needs_integers() {
+        let calculator = Calculator::default();
+        assert_eq!(Err(CalculatorError::IntegerOnly("&".to_string())), calculator.calculate::<f64>("3 & 1"));
+        assert_eq!(Err(CalculatorError::IntegerOnly("~".to_string())), calculator.calculate::<Complex<f64>>("~1"));
+        assert_eq!(Err(CalculatorError::IntegerOnly("rotl".to_string())), calculator.calculate::<f64>("rotl(1, 2)"));
+        assert!(calculator.calculate::<Integer>("rotl(1, 2)").is_err());
+        assert!(calculator.calculate::<i32>("1 << (0 - 1)").is_err());
     }
 }
@@ -0,0 +1,192 @@
+use std::f64::consts::PI;
+
+use num::{BigInt, One};
+use num::complex::Complex64;
+
+use crate::error::CalculatorError;
+use crate::math::Math;
+
+/// Coefficients of the Lanczos approximation with g = 7, accurate to about 15 significant figures
+const LANCZOS_G: f64 = 7f64;
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// The Lanczos sum and `t = z + g - 1/2` for `z - 1`, when the real part of `z` is at least a half
+fn lanczos(z: Complex64) -> (Complex64, Complex64) {
+    let z = z - 1f64;
+    let sum = LANCZOS.iter().enumerate().skip(1)
+        .fold(Complex64::new(LANCZOS[0], 0f64), |sum, (i, c)| sum + c / (z + i as f64));
+    (sum, z + LANCZOS_G + 0.5)
+}
+
+/// Γ(z), using the reflection formula Γ(z)Γ(1 - z) = π / sin(πz) for real parts below a half
+pub(crate) fn gamma(z: Complex64) -> Complex64 {
+    if z.re < 0.5 {
+        return PI / ((PI * z).sin() * gamma(1f64 - z));
+    }
+    let (sum, t) = lanczos(z);
+    (2f64 * PI).sqrt() * t.powc(z - 0.5) * (-t).exp() * sum
+}
+
+/// ln Γ(z), which does not overflow as Γ(z) does. For real z the real part is ln |Γ(z)|.
+pub(crate) fn ln_gamma(z: Complex64) -> Complex64 {
+    if z.re < 0.5 {
+        return PI.ln() - (PI * z).sin().ln() - ln_gamma(1f64 - z);
+    }
+    let (sum, t) = lanczos(z);
+    0.5 * (2f64 * PI).ln() + (z - 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Whether Γ has a pole at `value`, a non-positive integer
+pub(crate) fn is_pole(value: f64) -> bool {
+    value <= 0f64 && value.fract() == 0f64
+}
+
+/// A product that reports floating point results too large for the type, which would otherwise be
+/// infinite, as the fixed width integers report theirs
+fn times<T: Math<T>>(left: T, right: T) -> Result<T, CalculatorError> {
+    let product = left.checked_times(right)?;
+    if product.magnitude() > T::MAX_MAGNITUDE {
+        return Err(CalculatorError::Overflow);
+    }
+    Ok(product)
+}
+
+/// `n!`, exact by repeated multiplication for integers, otherwise Γ(n + 1)
+pub(crate) fn factorial<T: Math<T>>(n: T) -> Result<T, CalculatorError> {
+    match n.to_integer() {
+        Some(count) if count < 0 => Err(CalculatorError::UnsupportedOperation("factorial of a negative integer".to_string())),
+        Some(count) => falling(n, count),
+        None => (n + T::one()).gamma(),
+    }
+}
+
+/// The product of the `count` numbers counting down from `n`. Stops early once the product is zero,
+/// as it can become for words that wrap around.
+fn falling<T: Math<T>>(n: T, count: i64) -> Result<T, CalculatorError> {
+    let (mut product, mut factor) = (T::one(), n);
+    for _ in 0..count {
+        product = times(product, factor.clone())?;
+        if product.to_integer() == Some(0) {
+            break;
+        }
+        factor = factor - T::one();
+    }
+    Ok(product)
+}
+
+/// The counts of a choice, if they are non-negative integers
+fn integer_counts<T: Math<T>>(n: &T, r: &T) -> Option<(i64, i64)> {
+    match (n.to_integer(), r.to_integer()) {
+        (Some(n), Some(r)) if 0 <= r && 0 <= n => Some((n, r)),
+        _ => None,
+    }
+}
+
+/// `nPr`, the number of ordered arrangements of `r` of `n` items, `n! / (n - r)!`, which is zero
+/// when there are fewer than `r` items
+pub(crate) fn permutations<T: Math<T>>(n: T, r: T) -> Result<T, CalculatorError> {
+    match integer_counts(&n, &r) {
+        Some((_, r)) => falling(n, r),
+        None => factorial(n.clone())?.checked_div(factorial(n - r)?),
+    }
+}
+
+/// `C(n, r)` of integers of any size, by the same exact steps as [combinations]
+fn big_combinations(n: i64, r: i64) -> BigInt {
+    let r = r.min(n - r);
+    (0..r).fold(BigInt::one(), |result, k| result * (n - k) / (k + 1))
+}
+
+/// `nCr`, the number of ways to choose `r` of `n` items, `n! / (r! (n - r)!)`. For integers each
+/// step of `C(n, k) = C(n, k - 1) × (n - k + 1) / k` divides exactly. Integer types take those steps
+/// with big integers, as products that wrap around no longer divide exactly and products that
+/// overflow may still have a result in range.
+pub(crate) fn combinations<T: Math<T>>(n: T, r: T) -> Result<T, CalculatorError> {
+    match integer_counts(&n, &r) {
+        Some((count, chosen)) if chosen > count => Ok(T::zero()),
+        Some((count, chosen)) if T::INTEGER => T::from_big_integer(big_combinations(count, chosen)),
+        Some((count, chosen)) => {
+            // C(n, r) = C(n, n - r), whichever takes fewer steps
+            let (r, steps) = if chosen > count - chosen { (n.clone() - r, count - chosen) } else { (r, chosen) };
+            let (mut result, mut k, mut factor) = (T::one(), T::one(), n - r + T::one());
+            for _ in 0..steps {
+                result = times(result, factor.clone())?.checked_div(k.clone())?;
+                k = k + T::one();
+                factor = factor + T::one();
+            }
+            Ok(result)
+        }
+        None => {
+            let denominator = times(factorial(r.clone())?, factorial(n.clone() - r)?)?;
+            factorial(n)?.checked_div(denominator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod combinatorics_tests {
+    use crate::word::Word;
+
+    use super::*;
+
+    fn assert_close(expected: f64, actual: f64) {
+        assert!((expected - actual).abs() <= 1e-12 * expected.abs().max(1f64), "{} != {}", expected, actual);
+    }
+
+    #[test]
+    fn gamma_of_reals() {
+        assert_close(24f64, gamma(Complex64::new(5f64, 0f64)).re);
+        assert_close(PI.sqrt(), gamma(Complex64::new(0.5, 0f64)).re);
+        assert_close(-2f64 * PI.sqrt(), gamma(Complex64::new(-0.5, 0f64)).re);
+    }
+
+    #[test]
+    fn gamma_of_complex_numbers() {
+        // Γ(1 + i) = 0.4980156681 - 0.1549498283i
+        let value = gamma(Complex64::new(1f64, 1f64));
+        assert!((value - Complex64::new(0.498_015_668_118_349_2, -0.154_949_828_301_809)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn ln_gamma_of_reals() {
+        assert_close(24f64.ln(), ln_gamma(Complex64::new(5f64, 0f64)).re);
+        assert_close(863.231_987_192_405_4, ln_gamma(Complex64::new(201f64, 0f64)).re);
+        assert_close((2f64 * PI.sqrt()).ln(), ln_gamma(Complex64::new(-0.5, 0f64)).re);
+    }
+
+    #[test]
+    fn exact_factorials() {
+        assert_eq!(Ok(1), factorial(0));
+        assert_eq!(Ok(479_001_600), factorial(12));
+        assert_eq!(Err(CalculatorError::Overflow), factorial(13));
+    }
+
+    #[test]
+    fn exact_combinations() {
+        assert_eq!(Ok(10), combinations(5, 2));
+        assert_eq!(Ok(1), combinations(5, 0));
+        assert_eq!(Ok(20), permutations(5, 2));
+        assert_eq!(Ok(184_756), combinations(20, 10));
+        assert_eq!(Ok(155_117_520), combinations(30, 15));
+        assert_eq!(Err(CalculatorError::Overflow), combinations(40, 20));
+    }
+
+    #[test]
+    fn word_combinations() {
+        let word = Word::<8, false>::new;
+        assert_eq!(Ok(word(120)), combinations(word(10), word(3)));
+        assert_eq!(Ok(word(180)), combinations(word(20), word(10)));
+        assert_eq!(Ok(word(129)), combinations(word(255), word(2)));
+        assert_eq!(Ok(word(0)), combinations(word(3), word(5)));
+    }
+}
@@ -1,14 +1,22 @@
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter, Write};
 use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::str::FromStr;
 
+use num::BigInt;
 use num::pow::Pow as NumPow;
 
 use crate::FromStrValue;
-use crate::math::{Inv, Percent, Pow};
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::Rounding;
+use crate::math::{BigInteger, Bitwise, Checked, CheckedDiv, Compare, Elementary, Gamma, Inv, Magnitude, Modulo, One, Percent, Polar, Pow, Round, Real, ToInteger, Zero};
 
 #[cfg(test)]
 mod complex_number_tests {
+    use crate::bigfloat::BigFloat;
+    use crate::rational::Rational;
+
     use super::*;
 
     #[test]
@@ -34,8 +42,56 @@ mod complex_number_tests {
         assert!(!complex.has_real());
         assert!(complex.has_imaginary());
     }
+
+    #[test]
+    fn parse_imaginary_literals() {
+        assert_eq!(Ok(Complex::new(0f64, 1f64)), <Complex<f64> as FromStrValue>::from_str("j"));
+        assert_eq!(Ok(Complex::new(0f64, 4f64)), <Complex<f64> as FromStrValue>::from_str("4i"));
+        assert_eq!(Ok(Complex::new(0f64, 2.5f64)), <Complex<f64> as FromStrValue>::from_str("2.5j"));
+        assert_eq!(Ok(Complex::new(0f64, 1500f64)), <Complex<f64> as FromStrValue>::from_str("1.5e3i"));
+        assert_eq!(Ok(Complex::real(std::f64::consts::PI)), <Complex<f64> as FromStrValue>::from_str("pi"));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("3 + 4i", Complex::new(3f64, 4f64).to_string());
+        assert_eq!("1 - 2i", Complex::new(1f64, -2f64).to_string());
+        assert_eq!("-i", Complex::new(0f64, -1f64).to_string());
+        assert_eq!("0", Complex::new(0f64, 0f64).to_string());
+    }
+
+    #[test]
+    fn single_precision_arithmetic() {
+        let a = Complex::new(1f32, 2f32);
+        let b = Complex::new(3f32, -1f32);
+        assert_eq!(Complex::new(5f32, 5f32), a * b);
+        assert_eq!(Complex::new(0.1f32, 0.7f32), a / b);
+        assert_eq!(Complex::new(-3f32, 4f32), a.pow(Complex::real(2f32)));
+    }
+
+    #[test]
+    fn gaussian_rational_arithmetic() {
+        let half = Rational::new(1, 2);
+        let a = Complex::new(half.clone(), Rational::one());
+        assert_eq!(Complex::new(Rational::new(-3, 4), Rational::one()), a.clone() * a.clone());
+        assert_eq!(Complex::new(Rational::new(2, 5), Rational::new(-4, 5)), a.clone().inv());
+        assert_eq!(Complex::new(Rational::new(1, 200), Rational::new(1, 100)), a.percent());
+        assert_eq!("1/2 - (3/2)i", Complex::new(half, Rational::new(-3, 2)).to_string());
+    }
+
+    #[test]
+    fn decimal_arithmetic() {
+        let decimal = |s: &str| s.parse::<BigFloat>().unwrap();
+        let a = Complex::new(decimal("0.1"), decimal("0.2"));
+        let b = Complex::new(decimal("0.2"), -decimal("0.1"));
+        assert_eq!(Complex::new(decimal("0.3"), decimal("0.1")), a.clone() + b.clone());
+        assert_eq!(Complex::new(decimal("0.04"), decimal("0.03")), a.clone() * b.clone());
+        assert_eq!(Complex::new(BigFloat::zero(), BigFloat::one()), a.clone() / b);
+        assert_eq!("0.1 + 0.2i", a.to_string());
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Complex<T> {
     real: T,
     imaginary: T,
@@ -49,6 +105,10 @@ impl<T> Complex<T> {
         }
     }
 
+    pub fn into_parts(self) -> (T, T) {
+        (self.real, self.imaginary)
+    }
+
     pub fn real(real: T) -> Self where T: Zero {
         Self {
             real,
@@ -76,32 +136,22 @@ impl<T: Zero> Zero for Complex<T> {
     }
 }
 
+#[allow(dead_code)]
 impl<T> Complex<T> {
     fn has_complex() {}
 }
 
-pub trait Zero {
-    fn zero() -> Self;
-}
-
-pub trait One {
-    fn one() -> Self;
-}
-
-impl Zero for f64 {
-    fn zero() -> Self {
-        0f64
-    }
-}
-
-impl One for f64 {
+impl<T: Zero + One> One for Complex<T> {
     fn one() -> Self {
-        1f64
+        Self {
+            real: T::one(),
+            imaginary: T::zero(),
+        }
     }
 }
 
-impl Add for Complex<f64> {
-    type Output = Complex<f64>;
+impl<T: Real> Add for Complex<T> {
+    type Output = Complex<T>;
 
     fn add(self, rhs: Self) -> Self::Output {
         Self {
@@ -111,8 +161,8 @@ impl Add for Complex<f64> {
     }
 }
 
-impl Sub for Complex<f64> {
-    type Output = Complex<f64>;
+impl<T: Real> Sub for Complex<T> {
+    type Output = Complex<T>;
 
     fn sub(self, rhs: Self) -> Self::Output {
         Self {
@@ -122,117 +172,311 @@ impl Sub for Complex<f64> {
     }
 }
 
-impl Mul for Complex<f64> {
-    type Output = Complex<f64>;
+impl<T: Real> Mul for Complex<T> {
+    type Output = Complex<T>;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        let a = self.real;
-        let b = self.imaginary;
-        let c = rhs.real;
-        let d = rhs.imaginary;
+        let Complex { real: a, imaginary: b } = self;
+        let Complex { real: c, imaginary: d } = rhs;
         Self {
             // (a + bi) * (c + di)
             // ac - bd + (bc + ad)i
-            real: a * c - b * d,
+            real: a.clone() * c.clone() - b.clone() * d.clone(),
             imaginary: b * c + a * d,
         }
     }
 }
 
-impl Div for Complex<f64> {
-    type Output = Complex<f64>;
+impl<T: Real> Complex<T> {
+    /// (ac + bd)/(c^2 + d^2) + (bc-ad)/(c^2+d^2)i, with `divide` dividing the parts
+    fn divide(self, rhs: Self, divide: impl Fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        let Complex { real: a, imaginary: b } = self;
+        let Complex { real: c, imaginary: d } = rhs;
+        let c2d2 = c.clone() * c.clone() + d.clone() * d.clone();
+        Ok(Self {
+            real: divide(a.clone() * c.clone() + b.clone() * d.clone(), c2d2.clone())?,
+            imaginary: divide(b * c - a * d, c2d2)?,
+        })
+    }
 
-    fn div(self, rhs: Self) -> Self::Output {
-        let a = self.real;
-        let b = self.imaginary;
-        let c = rhs.real;
-        let d = rhs.imaginary;
-        // (ac + bd)/(c^2 + d^2) + (bc-ad)/(c^2+d^2)i
-        let c2d2 = c * c + d * d;
-        Self {
-            real: (a * c + b * d) / c2d2,
-            imaginary: (b * c - a * d) / c2d2,
+    /// Applies an operation only defined for real numbers to the real parts
+    fn real_parts(self, rhs: Self, name: &str, operation: fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        if self.has_imaginary() || rhs.has_imaginary() {
+            return Err(CalculatorError::UnsupportedOperation(format!("{} of complex numbers", name)));
         }
+        Ok(Complex::real(operation(self.real, rhs.real)?))
+    }
+
+    /// Raises to an integer power exactly by repeated squaring
+    fn powi(self, exponent: i64) -> Self {
+        let mut base = if exponent < 0 { self.inv() } else { self };
+        let mut exponent = exponent.unsigned_abs();
+        let mut result = Self::one();
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result * base.clone();
+            }
+            base = base.clone() * base;
+            exponent /= 2;
+        }
+        result
+    }
+}
+
+impl<T: Real> Div for Complex<T> {
+    type Output = Complex<T>;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        self.divide(rhs, |l, r| Ok(l / r)).expect("Division without checks cannot fail")
     }
 }
 
-impl Percent for Complex<f64> {
+impl<T: Real> CheckedDiv for Complex<T> {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        self.divide(rhs, T::checked_div)
+    }
+}
+
+impl<T: Real> Percent for Complex<T> {
     fn percent(self) -> Self {
-        self / Complex::real(100f64)
+        Complex::new(self.real.percent(), self.imaginary.percent())
+    }
+}
+
+impl<T: Real> From<Complex<T>> for num::Complex<f64> {
+    fn from(complex: Complex<T>) -> Self {
+        num::Complex { re: complex.real.to_f64(), im: complex.imaginary.to_f64() }
+    }
+}
+
+impl<T: Real> From<num::Complex<f64>> for Complex<T> {
+    fn from(complex: num::Complex<f64>) -> Self {
+        Complex::new(T::from_f64(complex.re), T::from_f64(complex.im))
     }
 }
 
-impl Pow for Complex<f64> {
+/// Integer powers are exact, others are calculated through `f64`
+impl<T: Real> Pow for Complex<T> {
     fn pow(self, rhs: Self) -> Self {
-        // TODO write into if we use this elsewhere
-        let lhs = num::Complex { im: self.imaginary, re: self.real };
-        let rhs = num::Complex { im: rhs.imaginary, re: rhs.real };
-        let complex = lhs.pow(rhs);
-        Complex::new(complex.re, complex.im)
+        if let Some(exponent) = rhs.to_integer() {
+            return self.powi(exponent);
+        }
+        let lhs: num::Complex<f64> = self.into();
+        let rhs: num::Complex<f64> = rhs.into();
+        lhs.pow(rhs).into()
     }
 }
 
-impl Inv for Complex<f64> {
+impl<T: Real> Elementary for Complex<T> {
+    fn sqrt(self) -> Self {
+        num::Complex::from(self).sqrt().into()
+    }
+
+    fn exp(self) -> Self {
+        num::Complex::from(self).exp().into()
+    }
+
+    fn ln(self) -> Self {
+        num::Complex::from(self).ln().into()
+    }
+
+    fn sin(self) -> Self {
+        num::Complex::from(self).sin().into()
+    }
+
+    fn cos(self) -> Self {
+        num::Complex::from(self).cos().into()
+    }
+
+    fn tan(self) -> Self {
+        num::Complex::from(self).tan().into()
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        if !self.has_imaginary() {
+            return Ok(Complex::real(self.real.abs()?));
+        }
+        Ok(Complex::real(T::from_f64(self.magnitude())))
+    }
+}
+
+impl<T: Real> Polar for Complex<T> {
+    fn re(self) -> Self {
+        Complex::real(self.real)
+    }
+
+    fn im(self) -> Self {
+        Complex::real(self.imaginary)
+    }
+
+    fn arg(self) -> Self {
+        Complex::real(T::from_f64(num::Complex::from(self).arg()))
+    }
+
+    fn conj(self) -> Self {
+        Complex::new(self.real, -self.imaginary)
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        Ok((num::Complex::<f64>::i() * num::Complex::from(self)).exp().into())
+    }
+
+    fn degrees(self) -> Self {
+        Complex::new(self.real.degrees(), self.imaginary.degrees())
+    }
+}
+
+impl<T: Real> Inv for Complex<T> {
     fn inv(self) -> Self {
-        Complex::real(1f64).div(self)
+        Complex::one().div(self)
+    }
+}
+
+impl<T: Real> Magnitude for Complex<T> {
+    const EXACT: bool = T::EXACT;
+    const EPSILON: f64 = T::EPSILON;
+    const INTEGER: bool = T::INTEGER;
+    const MAX_MAGNITUDE: f64 = T::MAX_MAGNITUDE;
+
+    fn magnitude(&self) -> f64 {
+        self.real.to_f64().hypot(self.imaginary.to_f64())
+    }
+}
+
+impl<T: Real> ToInteger for Complex<T> {
+    fn to_integer(&self) -> Option<i64> {
+        if self.has_imaginary() {
+            return None;
+        }
+        self.real.to_integer()
     }
 }
 
-impl<T: FromStrValue + Default> FromStr for Complex<T> {
+impl<T: Real> Modulo for Complex<T> {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        self.real_parts(rhs, "mod", T::modulo)
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        self.real_parts(rhs, "//", T::quotient)
+    }
+}
+
+impl<T: Real> Checked for Complex<T> {}
+
+impl<T: Real> Bitwise for Complex<T> {}
+
+impl<T: Real> BigInteger for Complex<T> {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        if self.has_imaginary() {
+            return None;
+        }
+        self.real.to_big_integer()
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        Ok(Complex::real(T::from_big_integer(value)?))
+    }
+}
+
+/// Only numbers without imaginary parts have an order
+impl<T: Real> Compare for Complex<T> {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        if self.has_imaginary() || other.has_imaginary() {
+            return None;
+        }
+        self.real.compare(&other.real)
+    }
+}
+
+/// Rounds the real and imaginary parts separately
+impl<T: Real> Round for Complex<T> {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(Complex::new(self.real.round_to(digits, rounding)?, self.imaginary.round_to(digits, rounding)?))
+    }
+
+    fn sign(self) -> Self {
+        Complex::new(self.real.sign(), self.imaginary.sign())
+    }
+}
+
+/// Real numbers use the gamma function of the part type, which is exact for rational integers
+impl<T: Real> Gamma for Complex<T> {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        if !self.has_imaginary() {
+            return Ok(Complex::real(self.real.gamma()?));
+        }
+        let value = combinatorics::gamma(self.into());
+        if !value.is_finite() {
+            return Err(CalculatorError::Overflow);
+        }
+        Ok(value.into())
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        if !self.has_imaginary() {
+            return Ok(Complex::real(self.real.ln_gamma()?));
+        }
+        Ok(combinatorics::ln_gamma(self.into()).into())
+    }
+}
+
+impl<T: FromStrValue + Zero> FromStr for Complex<T> {
     type Err = <T as FromStr>::Err;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Self {
-            real: <T as FromStrValue>::from_str(s)?,
-            imaginary: Default::default(),
-        })
+        Ok(Complex::real(<T as FromStrValue>::from_str(s)?))
     }
 }
 
-impl<T: FromStrValue + Default + One> FromStrValue for Complex<T> {
+/// The imaginary unit may be written `i` or `j`, alone or as the suffix of a number, e.g. `2.5j`
+impl<T: FromStrValue + Zero + One> FromStrValue for Complex<T> {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s == "i" {
-            Ok(Self {
-                real: Default::default(),
-                imaginary: <T as One>::one(),
-            })
-        } else {
-            s.parse()
+        if s == "i" || s == "j" {
+            return Ok(Complex::new(T::zero(), T::one()));
+        }
+        if let Some(number) = s.strip_suffix(['i', 'j']) {
+            if number.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                return Ok(Complex::new(T::zero(), <T as FromStrValue>::from_str(number)?));
+            }
         }
+        s.parse()
     }
 }
 
-impl<T: Display + PartialEq + Zero + One + PartialOrd + Neg<Output=T> + Copy> Display for Complex<T> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+impl<T: Display + PartialEq + Zero + One + PartialOrd + Neg<Output=T> + Clone> Complex<T> {
+    /// Writes as `a + bi`, using `unit` as the imaginary unit and `part` to write each number
+    pub(crate) fn write_rectangular(&self, f: &mut Formatter<'_>, unit: char, part: impl Fn(&T) -> String) -> std::fmt::Result {
         let has_real = self.has_real();
-        let has_imaginary = self.has_imaginary();
-        if has_real {
-            f.write_fmt(format_args!("{}", self.real))?;
-        }
-        if has_imaginary {
-            if self.imaginary > T::zero() {
-                if has_real {
-                    f.write_str(" + ")?;
-                }
+        if has_real || !self.has_imaginary() {
+            f.write_str(&part(&self.real))?;
+        }
+        if !self.has_imaginary() {
+            return Ok(());
+        }
+        let negative = self.imaginary < T::zero();
+        match (has_real, negative) {
+            (true, true) => f.write_str(" - ")?,
+            (true, false) => f.write_str(" + ")?,
+            (false, true) => f.write_char('-')?,
+            (false, false) => {}
+        }
+        let imaginary = if negative { -self.imaginary.clone() } else { self.imaginary.clone() };
+        if imaginary != <T as One>::one() {
+            let imaginary = part(&imaginary);
+            // A fraction is bracketed so that it reads back as one number, (1/2)i rather than 1/(2i)
+            if imaginary.contains('/') {
+                write!(f, "({})", imaginary)?;
             } else {
-                if has_real {
-                    f.write_char(' ')?;
-                }
-                f.write_char('-')?;
-            }
-            let mut img = self.imaginary;
-            if img < T::zero() {
-                img = -img;
-            }
-            if img != <T as One>::one() {
-                f.write_fmt(format_args!("{}", self.imaginary))?
-            }
-            f.write_char('i')
-        } else {
-            if !has_real {
-                f.write_fmt(format_args!("{}", self.real))?;
+                f.write_str(&imaginary)?;
             }
-            f.write_str("")
         }
+        f.write_char(unit)
+    }
+}
+
+impl<T: Display + PartialEq + Zero + One + PartialOrd + Neg<Output=T> + Clone> Display for Complex<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write_rectangular(f, 'i', T::to_string)
     }
 }
@@ -0,0 +1,160 @@
+use crate::calculator::{Calculator, FromStrValue};
+use crate::error::CalculatorError;
+use crate::format::{Base, FormatOptions};
+use crate::math::Math;
+use crate::quantity::Quantity;
+use crate::temporal;
+use crate::value::Value;
+
+/// What the result of an expression is converted to, written after `to` or `in`, e.g. `255 to hex`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conversion {
+    /// Shows integers in another base, other numbers stay decimal
+    Base(Base),
+    /// Shows a date and time at a fixed offset from UTC, e.g. `now to +02:00`, in minutes
+    Offset(i32),
+    /// Shows a quantity in units of the same dimension, e.g. `3 m/s to km/h`
+    Unit(String),
+}
+
+impl Conversion {
+    /// Targets that are neither bases nor offsets are units
+    pub fn parse(target: &str) -> Self {
+        Base::parse(target)
+            .map(Conversion::Base)
+            .or_else(|| temporal::parse_offset(target).map(Conversion::Offset))
+            .unwrap_or_else(|| Conversion::Unit(target.to_string()))
+    }
+}
+
+/// Splits an expression at its last `to` or `in` keyword into the expression and the target it is converted to
+fn split(expression: &str) -> (&str, Option<&str>) {
+    for (index, _) in expression.char_indices().rev() {
+        let rest = &expression[index..];
+        if !rest.starts_with("to") && !rest.starts_with("in") {
+            continue;
+        }
+        let starts_word = expression[..index].chars().next_back().is_none_or(|c| !c.is_alphabetic());
+        let ends_word = rest[2..].chars().next().is_some_and(char::is_whitespace);
+        let target = rest[2..].trim();
+        if starts_word && ends_word && !target.is_empty() {
+            return (expression[..index].trim_end(), Some(target));
+        }
+    }
+    (expression, None)
+}
+
+impl Calculator {
+    /// Evaluates an expression that may end with a conversion, e.g. `255 to hex`, returning the
+    /// result with the options to show it with
+    pub(crate) fn evaluate_conversion<T>(&self, expression: &str, options: &FormatOptions) -> Result<(Value<T>, FormatOptions), CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        match split(expression) {
+            (expression, Some(target)) => self.convert(self.evaluate(expression)?, Conversion::parse(target), options),
+            (expression, None) => Ok((self.evaluate(expression)?, options.clone())),
+        }
+    }
+
+    /// Converts a result, returning it with the options to show it with
+    fn convert<T>(&self, value: Value<T>, conversion: Conversion, options: &FormatOptions) -> Result<(Value<T>, FormatOptions), CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        let unit = match conversion {
+            Conversion::Base(base) => return Ok((value, FormatOptions { base, ..options.clone() })),
+            Conversion::Offset(offset) => return match value {
+                Value::Temporal(date) => Ok((Value::Temporal(date.with_offset(offset)), options.clone())),
+                value => Err(CalculatorError::UnsupportedOperation(format!("{} to a time zone", value.kind()))),
+            },
+            Conversion::Unit(unit) => unit,
+        };
+        let target = match self.evaluate::<T>(&unit) {
+            Ok(Value::Quantity(target)) => target,
+            Ok(_) | Err(CalculatorError::Parse(_)) => return Err(CalculatorError::UnknownConversion(unit)),
+            Err(error) => return Err(error),
+        };
+        let quantity = match value {
+            Value::Quantity(quantity) => quantity,
+            Value::Scalar(value) => Quantity::dimensionless(value),
+            value => return Err(CalculatorError::UnsupportedOperation(format!("{} to {}", value.kind(), unit))),
+        };
+        // Currencies are different dimensions that convert through their exchange rates
+        if let Some(rates) = self.rates.as_ref().filter(|_| quantity.dimension() != target.dimension()) {
+            if let (Some(exchanged), Some(target)) = (rates.exchange(quantity.clone())?, rates.exchange(target.clone())?) {
+                if exchanged.dimension() == target.dimension() {
+                    return Ok((Value::Quantity(exchanged.convert(target, &unit)?), options.clone()));
+                }
+            }
+        }
+        Ok((Value::Quantity(quantity.convert(target, &unit)?), options.clone()))
+    }
+}
+
+#[cfg(test)]
+mod conversion_tests {
+    use crate::format::Formatted;
+    use crate::currency::Rates;
+    use crate::integer::Integer;
+    use crate::rational::Rational;
+
+    use super::*;
+
+    fn convert<T: FromStrValue + Math<T> + crate::format::Format>(expression: &str) -> Result<String, CalculatorError> {
+        let (value, options) = Calculator::default().evaluate_conversion::<T>(expression, &FormatOptions::default())?;
+        Ok(Formatted(&value, &options).to_string())
+    }
+
+    #[test]
+    fn split_conversion() {
+        assert_eq!(("255", Some("hex")), split("255 to hex"));
+        assert_eq!(("0xFF + 1", Some("bin")), split("0xFF + 1 in  bin "));
+        assert_eq!(("sin(1)", None), split("sin(1)"));
+        assert_eq!(("2 to", None), split("2 to"));
+    }
+
+    #[test]
+    fn to_base() {
+        assert_eq!(Ok("0xFF".to_string()), convert::<i32>("255 to hex"));
+        assert_eq!(Ok("0b1010".to_string()), convert::<f64>("0xA to bin"));
+        assert_eq!(Ok("0o777".to_string()), convert::<i32>("0b111111111 in oct"));
+        assert_eq!(Ok("511".to_string()), convert::<i32>("0o777 to dec"));
+    }
+
+    #[test]
+    fn big_integer_to_base() {
+        assert_eq!(Ok("0x10000000000000000".to_string()), convert::<Integer>("2^64 to hex"));
+    }
+
+    #[test]
+    fn to_unit() {
+        assert_eq!(Ok("108/5 km/h".to_string()), convert::<Rational>("3 m * 2 s^-1 to km/h"));
+        assert_eq!(Ok("12 inch".to_string()), convert::<Rational>("1 ft in inch"));
+        assert_eq!(Ok("3.6 MJ".to_string()), convert::<f64>("1 kWh to MJ"));
+        assert_eq!(Ok("1.5 min".to_string()), convert::<f64>("90 s to min"));
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            convert::<f64>("3 m to s")
+        );
+        assert_eq!(Err(CalculatorError::UnknownConversion("furlong".to_string())), convert::<f64>("3 m to furlong"));
+    }
+
+    #[test]
+    fn to_currency() {
+        let rates = Rates::parse("date,2026-10-16\nEUR,1\nUSD,1.25\nGBP,0.8").unwrap();
+        let calculator = Calculator { rates: Some(rates), ..Calculator::default() };
+        let convert = |expression: &str| calculator.evaluate_conversion::<Rational>(expression, &FormatOptions::default())
+            .map(|(value, options)| Formatted(&value, &options).to_string());
+        assert_eq!(Ok("80 EUR (rates as of 2026-10-16)".to_string()), convert("100 USD to EUR"));
+        assert_eq!(Ok("25/16 USD (rates as of 2026-10-16)".to_string()), convert("1 GBP in USD"));
+        assert_eq!(Ok("4/3 GBP/min (rates as of 2026-10-16)".to_string()), convert("125 USD/h to GBP/min"));
+        assert_eq!(Ok("100 USD".to_string()), convert("100 USD to USD"));
+        assert_eq!(Err(CalculatorError::IncompatibleUnits("USD".to_string(), "EUR".to_string())), convert("100 USD + 5 EUR"));
+        assert_eq!(Err(CalculatorError::IncompatibleUnits("m".to_string(), "EUR".to_string())), convert("3 m to EUR"));
+        assert_eq!(Err(CalculatorError::Parse("USD".to_string())), Calculator::default().evaluate::<f64>("1 USD"));
+    }
+
+    #[test]
+    fn unknown_target() {
+        assert_eq!(Err(CalculatorError::UnknownConversion("base7".to_string())), convert::<i32>("255 to base7"));
+    }
+}
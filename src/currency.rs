@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+
+use crate::calculator::{FromStrValue, ParseOperand};
+use crate::error::CalculatorError;
+use crate::math::Math;
+use crate::quantity::Quantity;
+
+/// The base unit currencies are exchanged through, it never appears in results
+const REFERENCE: &str = "¤";
+
+/// Exchange rates read from a local rates file, so that currencies can be converted offline.
+/// Each currency is a dimension of its own, so `100 USD + 5 EUR` is an error while
+/// `100 USD to EUR` converts.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rates {
+    /// The date the rates are from, shown with results converted with them
+    date: String,
+    /// How much of each currency one unit of a common reference currency buys, as written
+    rates: BTreeMap<String, String>,
+}
+
+/// Three letter codes, such as USD
+fn is_code(name: &str) -> bool {
+    name.len() == 3 && name.chars().all(|c| c.is_ascii_uppercase())
+}
+
+impl Rates {
+    /// Reads a rates file, comma separated lines of a currency code and its rate against a common
+    /// reference currency, and a `date` line for the date they are from, e.g.
+    ///
+    /// ```text
+    /// # Rates per 1 EUR
+    /// date,2026-10-16
+    /// EUR,1
+    /// USD,1.0842
+    /// ```
+    pub fn parse(text: &str) -> Result<Self, CalculatorError> {
+        let mut date = None;
+        let mut rates = BTreeMap::new();
+        let lines = text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'));
+        for line in lines {
+            let parse = || CalculatorError::Parse(line.to_string());
+            let (name, value) = line.split_once(',').ok_or_else(parse)?;
+            let (name, value) = (name.trim(), value.trim());
+            match name {
+                "date" => date = Some(value.to_string()),
+                "currency" => {}
+                name if is_code(name) && value.parse::<f64>().is_ok_and(|rate| rate > 0f64) => {
+                    rates.insert(name.to_string(), value.to_string());
+                }
+                _ => return Err(parse()),
+            }
+        }
+        let date = date.ok_or_else(|| CalculatorError::Parse("rates without a date line".to_string()))?;
+        Ok(Rates { date, rates })
+    }
+
+    pub fn is_currency(&self, name: &str) -> bool {
+        self.rates.contains_key(name)
+    }
+
+    /// Measures the currencies of a quantity in the reference currency, so that quantities in
+    /// different currencies can be compared. Returns None when it has no currencies.
+    pub(crate) fn exchange<T>(&self, quantity: Quantity<T>) -> Result<Option<Quantity<T>>, CalculatorError>
+        where T: FromStrValue + Math<T>
+    {
+        let currencies: Vec<String> = quantity.dimension().units()
+            .filter(|unit| self.is_currency(unit))
+            .map(str::to_string)
+            .collect();
+        if currencies.is_empty() {
+            return Ok(None);
+        }
+        let mut quantity = quantity;
+        for currency in currencies {
+            let rate = &self.rates[&currency];
+            let rate: T = rate.as_str().parse_operand().map_err(|_| CalculatorError::Parse(rate.clone()))?;
+            quantity = quantity.replace_base(&currency, REFERENCE, T::one().checked_div(rate)?)?;
+        }
+        Ok(Some(quantity.with_as_of(&self.date)))
+    }
+}
+
+#[cfg(test)]
+mod currency_tests {
+    use crate::units::Dimension;
+
+    use super::*;
+
+    const RATES: &str = "# Rates per 1 EUR\ncurrency,rate\ndate,2026-10-16\nEUR,1\nUSD,1.25\nGBP,0.8\n";
+
+    #[test]
+    fn rates_file() {
+        let rates = Rates::parse(RATES).unwrap();
+        assert_eq!("2026-10-16", rates.date);
+        assert!(rates.is_currency("USD"));
+        assert!(!rates.is_currency("JPY"));
+        assert_eq!(Err(CalculatorError::Parse("usd,1.25".to_string())), Rates::parse("date,2026-10-16\nusd,1.25"));
+        assert_eq!(Err(CalculatorError::Parse("USD,-1".to_string())), Rates::parse("date,2026-10-16\nUSD,-1"));
+        assert!(Rates::parse("EUR,1").is_err());
+    }
+
+    #[test]
+    fn exchange() {
+        let rates = Rates::parse(RATES).unwrap();
+        let dollars = Quantity::new(100f64, Dimension::base("USD"));
+        let exchanged = rates.exchange(dollars).unwrap().unwrap();
+        assert_eq!(Quantity::new(80f64, Dimension::base(REFERENCE)).with_as_of("2026-10-16"), exchanged);
+        assert_eq!(Ok(None), rates.exchange(Quantity::new(1f64, Dimension::base("m"))));
+    }
+}
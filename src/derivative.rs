@@ -0,0 +1,148 @@
+use crate::error::CalculatorError;
+use crate::expression::Expression;
+use crate::tokenizer::Token;
+
+/// The derivative of `base^exponent`, given the derivatives of both. Constant exponents and
+/// constant bases have the simpler forms, otherwise it is `base^exponent * (exponent' * ln(base) + exponent * base' / base)`.
+fn power_rule(base: Expression, exponent: Expression, base_derivative: Expression, exponent_derivative: Expression) -> Expression {
+    if exponent_derivative.is_number(0) {
+        let lowered = exponent.clone().minus(Expression::number(1));
+        return exponent.times(base.power(lowered)).times(base_derivative);
+    }
+    let power = base.clone().power(exponent.clone());
+    let logarithm = Expression::function("ln", base.clone());
+    if base_derivative.is_number(0) {
+        return power.times(logarithm).times(exponent_derivative);
+    }
+    power.times(exponent_derivative.times(logarithm).plus(exponent.times(base_derivative).over(base)))
+}
+
+impl Expression {
+    /// The derivative with respect to `variable`, simplified as it is built. Other names are constants.
+    pub fn derivative(&self, variable: &str) -> Result<Expression, CalculatorError> {
+        Ok(match self {
+            Expression::Number(_) => Expression::number(0),
+            Expression::Symbol(name) => Expression::number(if name == variable { 1 } else { 0 }),
+            Expression::Negate(operand) => operand.derivative(variable)?.negate(),
+            Expression::Binary(t, left, right) => {
+                let (left, right) = (left.as_ref().clone(), right.as_ref().clone());
+                let (left_derivative, right_derivative) = (left.derivative(variable)?, right.derivative(variable)?);
+                match t {
+                    Token::Plus => left_derivative.plus(right_derivative),
+                    Token::Minus => left_derivative.minus(right_derivative),
+                    Token::Multiply => left_derivative.times(right.clone()).plus(left.times(right_derivative)),
+                    Token::Divide => left_derivative.times(right.clone())
+                        .minus(left.times(right_derivative))
+                        .over(right.power(Expression::number(2))),
+                    Token::Power => power_rule(left, right, left_derivative, right_derivative),
+                    // `n√x` is `x^(1/n)`
+                    Token::Root => {
+                        let exponent = Expression::number(1).over(left);
+                        let exponent_derivative = exponent.derivative(variable)?;
+                        power_rule(right, exponent, right_derivative, exponent_derivative)
+                    }
+                    t => return Err(CalculatorError::UnsupportedOperation(format!("diff of {}", t))),
+                }
+            }
+            Expression::Function(name, arguments) => match arguments.as_slice() {
+                [argument] => {
+                    let inner = argument.derivative(variable)?;
+                    let outer = match name.as_str() {
+                        "sqrt" => Expression::number(1).over(Expression::number(2).times(self.clone())),
+                        "exp" => self.clone(),
+                        "ln" => Expression::number(1).over(argument.clone()),
+                        "sin" => Expression::function("cos", argument.clone()),
+                        "cos" => Expression::function("sin", argument.clone()).negate(),
+                        "tan" => Expression::number(1).over(Expression::function("cos", argument.clone()).power(Expression::number(2))),
+                        "abs" => Expression::function("sign", argument.clone()),
+                        name => return Err(CalculatorError::UnsupportedOperation(format!("diff of {}", name))),
+                    };
+                    // By the chain rule, written `u'/u` rather than `1/u*u'`
+                    match outer {
+                        Expression::Binary(Token::Divide, numerator, denominator) if numerator.is_number(1) => inner.over(*denominator),
+                        outer => inner.times(outer),
+                    }
+                }
+                _ => return Err(CalculatorError::UnsupportedOperation(format!("diff of {}", name))),
+            },
+        })
+    }
+
+    /// `diff(expression, x)`, the simplified derivative, and `diff(expression, x, a)`, the derivative at x = a
+    pub(crate) fn differentiate(arguments: Vec<Expression>) -> Result<Expression, CalculatorError> {
+        let found = arguments.len();
+        let mut arguments = arguments.into_iter();
+        match (arguments.next(), arguments.next(), arguments.next(), arguments.next()) {
+            (Some(expression), Some(Expression::Symbol(variable)), point, None) => {
+                let derivative = expression.derivative(&variable)?.simplify();
+                Ok(match point {
+                    Some(point) => derivative.substitute(&variable, &point),
+                    None => derivative,
+                })
+            }
+            (Some(_), Some(_), _, None) =>
+                Err(CalculatorError::UnsupportedOperation("diff with respect to an expression".to_string())),
+            _ => Err(CalculatorError::ArgumentCount { function: "diff".to_string(), expected: 2, found }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod derivative_tests {
+    use crate::calculator::Calculator;
+
+    use super::*;
+
+    fn derivative(expression: &str) -> String {
+        Expression::parse(expression).unwrap().derivative("x").unwrap().to_string()
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!("0", derivative("pi^2 + y"));
+        assert_eq!("3*x^2 + 2*x - 1", derivative("x^3 + x^2 - x + 7"));
+        assert_eq!("-1/x^2", derivative("1/x"));
+        assert_eq!("cos(x)*x^2 + 2*sin(x)*x", derivative("sin(x) * x^2"));
+        assert_eq!("-3*sin(3*x)", derivative("cos(3x)"));
+        assert_eq!("e^x", derivative("e^x"));
+        assert_eq!("2^x*ln(2)", derivative("2^x"));
+        assert_eq!("2/(2*x)", derivative("ln(2x)"));
+        assert_eq!("1/(2*sqrt(x))", derivative("sqrt(x)"));
+        assert_eq!("1/2*x^(-1/2)", derivative("2√x"));
+        assert_eq!("x^x*(ln(x) + x/x)", derivative("x^x"));
+        assert_eq!("-2*exp(-2*x)", derivative("exp(-2x)"));
+    }
+
+    #[test]
+    fn unsupported() {
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("diff of re".to_string())),
+            Expression::parse("re(x)").unwrap().derivative("x")
+        );
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("diff with respect to an expression".to_string())),
+            Expression::differentiate(vec![Expression::number(1), Expression::number(2)])
+        );
+        assert_eq!(
+            Err(CalculatorError::ArgumentCount { function: "diff".to_string(), expected: 2, found: 1 }),
+            Expression::differentiate(vec![Expression::number(1)])
+        );
+    }
+
+    /// The derivative evaluates to the slope of the expression, by central differences
+    #[test]
+    fn evaluates_numerically() {
+        let calculator = Calculator::default();
+        for expression in ["x^3 - 2x", "sin(x) * x^2", "x^x", "ln(x)/x", "tan(x) + sqrt(x)", "3√x", "exp(x^2)"] {
+            let parsed = Expression::parse(expression).unwrap();
+            let at = |x: f64| {
+                let value = parsed.clone().substitute("x", &Expression::Symbol(x.to_string())).to_string();
+                calculator.calculate::<f64>(&value).unwrap()
+            };
+            let slope = (at(1.3 + 1e-6) - at(1.3 - 1e-6)) / 2e-6;
+            let derivative = parsed.derivative("x").unwrap().substitute("x", &Expression::Symbol("1.3".to_string()));
+            let exact = calculator.calculate::<f64>(&derivative.to_string()).unwrap();
+            assert!((slope - exact).abs() < 1e-6, "{}: {} against {}", expression, exact, slope);
+        }
+    }
+}
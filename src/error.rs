@@ -0,0 +1,112 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum CalculatorError {
+    /// An operand that is neither a number nor a known name
+    Parse(String),
+    /// An operator or function did not have enough operands
+    EmptyStack,
+    /// A brace or bracket without its partner, e.g. "sin(1" or "[1]]"
+    UnbalancedBrackets(String),
+    /// A column in a batch calculation does not have the same number of rows as the first
+    ColumnLength {
+        column: String,
+        rows: usize,
+        expected: usize,
+    },
+    /// Vectors of different lengths were combined
+    DimensionMismatch {
+        expected: usize,
+        found: usize,
+    },
+    EmptyVector,
+    /// Matrices of different shapes, as (rows, columns), were combined
+    ShapeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    /// A square matrix was required
+    NotSquare(usize, usize),
+    SingularMatrix,
+    /// A system of equations whose solution would be dominated by rounding errors, with its condition number
+    IllConditioned(f64),
+    DivisionByZero,
+    /// A result too large for the number type
+    Overflow,
+    /// A result that can only be represented by a complex number, when calculating with real numbers
+    ComplexResult,
+    UnknownFunction(String),
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    /// An operator or function that is not defined for the kinds of value given, e.g. "vector ^ scalar"
+    UnsupportedOperation(String),
+    /// A bitwise operator or function applied to a number type that is not an integer
+    IntegerOnly(String),
+    /// A function that divides, such as `inv`, applied to an integer number type whose division truncates
+    FractionsOnly(String),
+    /// A function that orders numbers, such as `median`, given complex numbers or NaN
+    Unordered(String),
+    /// `modinv(value, modulus)` or a negative `modpow` exponent when the two have a common factor
+    NoInverse {
+        value: String,
+        modulus: String,
+    },
+    /// Quantities of different dimensions were added, subtracted or converted, e.g. "1 m + 1 s"
+    IncompatibleUnits(String, String),
+    /// A user-defined unit whose name is not letters or is already a function, constant or unit
+    UnitName(String),
+    /// A user-defined unit that depends on itself, directly or through other units
+    UnitCycle(String),
+    /// A unit file that defines the same unit twice, with the line of the second definition
+    DuplicateUnit {
+        name: String,
+        line: usize,
+    },
+    /// The target of a `to` or `in` conversion is not known, e.g. "255 to base7"
+    UnknownConversion(String),
+}
+
+impl Display for CalculatorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CalculatorError::Parse(operand) => write!(f, "Cannot parse \"{}\"", operand),
+            CalculatorError::EmptyStack => f.write_str("Empty stack"),
+            CalculatorError::UnbalancedBrackets(bracket) => write!(f, "Unbalanced \"{}\"", bracket),
+            CalculatorError::ColumnLength { column, rows, expected } =>
+                write!(f, "Column \"{}\" has {} rows, expected {}", column, rows, expected),
+            CalculatorError::DimensionMismatch { expected, found } =>
+                write!(f, "Dimension mismatch, expected {} but found {}", expected, found),
+            CalculatorError::EmptyVector => f.write_str("Vectors need at least one element"),
+            CalculatorError::ShapeMismatch { expected, found } =>
+                write!(f, "Shape mismatch, expected {}x{} but found {}x{}", expected.0, expected.1, found.0, found.1),
+            CalculatorError::NotSquare(rows, columns) => write!(f, "Matrix is {}x{} but must be square", rows, columns),
+            CalculatorError::SingularMatrix => f.write_str("Matrix is singular"),
+            CalculatorError::IllConditioned(condition) =>
+                write!(f, "Matrix is ill-conditioned, condition number {:e}", condition),
+            CalculatorError::DivisionByZero => f.write_str("Division by zero"),
+            CalculatorError::Overflow => f.write_str("Overflow"),
+            CalculatorError::ComplexResult => f.write_str("Result is complex"),
+            CalculatorError::UnknownFunction(name) => write!(f, "Unknown function \"{}\"", name),
+            CalculatorError::ArgumentCount { function, expected, found } =>
+                write!(f, "{} takes {} argument(s) but was given {}", function, expected, found),
+            CalculatorError::UnsupportedOperation(operation) => write!(f, "Unsupported operation: {}", operation),
+            CalculatorError::IntegerOnly(operation) =>
+                write!(f, "{} needs integers, e.g. calculator --i32, --bigint or --word u8", operation),
+            CalculatorError::FractionsOnly(operation) =>
+                write!(f, "{} needs fractions, e.g. calculator --rational or the default floating point", operation),
+            CalculatorError::Unordered(operation) =>
+                write!(f, "{} needs real numbers, complex numbers and NaN cannot be ordered", operation),
+            CalculatorError::NoInverse { value, modulus } =>
+                write!(f, "{} has no inverse modulo {}, they have a common factor", value, modulus),
+            CalculatorError::IncompatibleUnits(left, right) => write!(f, "Incompatible units {} and {}", left, right),
+            CalculatorError::UnitName(name) =>
+                write!(f, "Cannot define unit \"{}\", names are letters that are not already functions, constants or units", name),
+            CalculatorError::UnitCycle(name) => write!(f, "Unit \"{}\" is defined in terms of itself", name),
+            CalculatorError::DuplicateUnit { name, line } => write!(f, "Unit \"{}\" is defined again on line {}", name, line),
+            CalculatorError::UnknownConversion(target) => write!(f, "Cannot convert to \"{}\"", target),
+        }
+    }
+}
@@ -0,0 +1,347 @@
+use std::fmt::{Display, Formatter};
+
+use crate::error::CalculatorError;
+use crate::math::{Pow, ToInteger, Zero};
+use crate::rational::Rational;
+use crate::shunting_yard::{Shunt, ShuntType, ShuntingYardToken};
+use crate::tokenizer::{Token, Tokenize};
+
+/// Functions that are worked out on the expressions they are given rather than on their values
+pub const SYMBOLIC: &[&str] = &["diff", "simplify"];
+
+/// Binds more tightly than any operator, never needs brackets
+const ATOM: u8 = u8::MAX;
+
+/// An expression as a tree, built from the RPN the shunting yard gives, so that it can be worked
+/// on symbolically and printed back as infix
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expression {
+    /// A number, kept exact
+    Number(Rational),
+    /// A variable, a constant such as pi or a unit, by name
+    Symbol(String),
+    Negate(Box<Expression>),
+    /// One of `+ - * / ^ √`, with the index of a root first as in `3√x`
+    Binary(Token, Box<Expression>, Box<Expression>),
+    Function(String, Vec<Expression>),
+}
+
+/// How tightly the operator `t` binds, as the shunting yard reads it
+fn precedence(t: &Token) -> u8 {
+    match t.shunt_type() {
+        ShuntType::Operator { precedence, .. } | ShuntType::Prefix { precedence } => precedence,
+        _ => ATOM,
+    }
+}
+
+impl Expression {
+    /// Reads an expression without evaluating it, names that are not numbers are kept as symbols
+    pub fn parse(expression: &str) -> Result<Self, CalculatorError> {
+        let mut stack = vec![];
+        for token in expression.chars().tokenize_symbolic().shunt() {
+            let node = match token {
+                // The branches of `if` are kept as its arguments
+                Token::Then | Token::Else => continue,
+                Token::T(name) | Token::Unit(name) => match name.parse::<Rational>() {
+                    Ok(number) => Expression::Number(number),
+                    Err(_) => Expression::Symbol(name),
+                },
+                Token::Negate => Expression::Negate(Box::new(stack.pop().ok_or(CalculatorError::EmptyStack)?)),
+                t @ (Token::Plus | Token::Minus | Token::Multiply | Token::Divide | Token::Power | Token::Root) => {
+                    let right = stack.pop().ok_or(CalculatorError::EmptyStack)?;
+                    let left = stack.pop().ok_or(CalculatorError::EmptyStack)?;
+                    Expression::Binary(t, Box::new(left), Box::new(right))
+                }
+                Token::Function(name, arguments) => {
+                    if arguments > stack.len() {
+                        return Err(CalculatorError::EmptyStack);
+                    }
+                    Expression::Function(name, stack.split_off(stack.len() - arguments))
+                }
+                t => return Err(CalculatorError::UnsupportedOperation(format!("{} in an expression", t))),
+            };
+            stack.push(node);
+        }
+        stack.pop().ok_or(CalculatorError::EmptyStack)
+    }
+
+    pub fn number(value: i64) -> Self {
+        Expression::Number(Rational::new(value, 1))
+    }
+
+    pub fn function(name: &str, argument: Expression) -> Self {
+        match (name, &argument) {
+            ("ln", Expression::Symbol(e)) if e == "e" => Expression::number(1),
+            ("ln", one) if one.is_number(1) => Expression::number(0),
+            _ => Expression::Function(name.to_string(), vec![argument]),
+        }
+    }
+
+    pub(crate) fn binary(t: Token, left: Expression, right: Expression) -> Self {
+        Expression::Binary(t, Box::new(left), Box::new(right))
+    }
+
+    pub(crate) fn is_number(&self, value: i64) -> bool {
+        matches!(self, Expression::Number(number) if *number == Rational::new(value, 1))
+    }
+
+    /// This expression without its sign, when it is written with one, e.g. `2*x` for `-2*x`
+    pub(crate) fn negated(&self) -> Option<Expression> {
+        match self {
+            Expression::Negate(operand) => Some(operand.as_ref().clone()),
+            Expression::Number(number) if *number < Rational::zero() => Some(Expression::Number(-number.clone())),
+            Expression::Binary(t @ (Token::Multiply | Token::Divide), left, right) =>
+                left.negated().map(|left| Expression::binary(t.clone(), left, right.as_ref().clone())),
+            _ => None,
+        }
+    }
+
+    /// `-self`, with the sign folded into a number or a leading factor
+    pub fn negate(self) -> Self {
+        if let Some(negated) = self.negated() {
+            return negated;
+        }
+        match self {
+            Expression::Number(number) => Expression::Number(-number),
+            Expression::Binary(t @ (Token::Multiply | Token::Divide), left, right) if matches!(*left, Expression::Number(_)) =>
+                Expression::Binary(t, Box::new(left.negate()), right),
+            operand => Expression::Negate(Box::new(operand)),
+        }
+    }
+
+    /// `self + rhs`, folding numbers and zeros and keeping sums flat
+    pub fn plus(self, rhs: Self) -> Self {
+        if let Some(negated) = rhs.negated() {
+            return self.minus(negated);
+        }
+        match (self, rhs) {
+            (Expression::Number(left), Expression::Number(right)) => Expression::Number(left + right),
+            (left, right) if left.is_number(0) => right,
+            (left, right) if right.is_number(0) => left,
+            (left, Expression::Binary(Token::Plus, middle, right)) => left.plus(*middle).plus(*right),
+            (left, Expression::Binary(Token::Minus, middle, right)) => left.plus(*middle).minus(*right),
+            (left, right) => Expression::binary(Token::Plus, left, right),
+        }
+    }
+
+    /// `self - rhs`, folding numbers and zeros and keeping sums flat
+    pub fn minus(self, rhs: Self) -> Self {
+        if let Some(negated) = rhs.negated() {
+            return self.plus(negated);
+        }
+        match (self, rhs) {
+            (Expression::Number(left), Expression::Number(right)) => Expression::Number(left - right),
+            (left, right) if right.is_number(0) => left,
+            (left, right) if left.is_number(0) => right.negate(),
+            (left, right) if left == right => Expression::number(0),
+            (left, Expression::Binary(Token::Plus, middle, right)) => left.minus(*middle).minus(*right),
+            (left, Expression::Binary(Token::Minus, middle, right)) => left.minus(*middle).plus(*right),
+            (left, right) => Expression::binary(Token::Minus, left, right),
+        }
+    }
+
+    /// `self * rhs`, folding numbers, zeros, ones and signs, with numbers first and products flat
+    pub fn times(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (Expression::Number(left), Expression::Number(right)) => Expression::Number(left * right),
+            (left, right) if left.is_number(0) || right.is_number(0) => Expression::number(0),
+            (left, right) if left.is_number(1) => right,
+            (left, right) if right.is_number(1) => left,
+            (left, right) if left.is_number(-1) => right.negate(),
+            (Expression::Negate(left), right) => left.times(right).negate(),
+            (left, Expression::Negate(right)) => left.times(*right).negate(),
+            (left, right @ Expression::Number(_)) => right.times(left),
+            (left, Expression::Binary(Token::Multiply, middle, right)) => left.times(*middle).times(*right),
+            (Expression::Number(left), Expression::Binary(Token::Divide, numerator, denominator))
+                if matches!(*numerator, Expression::Number(_)) =>
+                Expression::Number(left).times(*numerator).over(*denominator),
+            (left, right) => Expression::binary(Token::Multiply, left, right),
+        }
+    }
+
+    /// `self / rhs`, folding numbers, ones and signs. Division by zero is left to evaluation.
+    pub fn over(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (left, right) if right.is_number(1) => left,
+            (Expression::Number(left), Expression::Number(right)) if right != Rational::zero() => Expression::Number(left / right),
+            (left, right) if left.is_number(0) && !right.is_number(0) => Expression::number(0),
+            (Expression::Negate(left), right) => left.over(right).negate(),
+            (left, Expression::Negate(right)) => left.over(*right).negate(),
+            (left, right) => Expression::binary(Token::Divide, left, right),
+        }
+    }
+
+    /// `self ^ rhs`, folding zero and one exponents and small integer powers of numbers
+    pub fn power(self, rhs: Self) -> Self {
+        match (self, rhs) {
+            (_, right) if right.is_number(0) => Expression::number(1),
+            (left, right) if right.is_number(1) => left,
+            (left, _) if left.is_number(1) => Expression::number(1),
+            (Expression::Number(left), Expression::Number(right))
+                if right.to_integer().is_some_and(|exponent| exponent.abs() <= 64 && (exponent > 0 || left != Rational::zero())) =>
+                Expression::Number(left.pow(right)),
+            (left, right) => Expression::binary(Token::Power, left, right),
+        }
+    }
+
+    /// The names of the variables, constants and units in this expression
+    pub fn symbols(&self) -> Vec<&str> {
+        match self {
+            Expression::Number(_) => vec![],
+            Expression::Symbol(name) => vec![name.as_str()],
+            Expression::Negate(operand) => operand.symbols(),
+            Expression::Binary(_, left, right) => [left.symbols(), right.symbols()].concat(),
+            Expression::Function(_, arguments) => arguments.iter().flat_map(Expression::symbols).collect(),
+        }
+    }
+
+    /// Replaces the variable `name` with `value`
+    pub fn substitute(self, name: &str, value: &Expression) -> Self {
+        match self {
+            Expression::Symbol(symbol) if symbol == name => value.clone(),
+            Expression::Negate(operand) => Expression::Negate(Box::new(operand.substitute(name, value))),
+            Expression::Binary(t, left, right) =>
+                Expression::binary(t, left.substitute(name, value), right.substitute(name, value)),
+            Expression::Function(function, arguments) =>
+                Expression::Function(function, arguments.into_iter().map(|argument| argument.substitute(name, value)).collect()),
+            expression => expression,
+        }
+    }
+
+    /// Works out the symbolic functions in this expression, innermost first, adding the names
+    /// they treat as variables to `variables`
+    fn expand(self, variables: &mut Vec<String>) -> Result<Self, CalculatorError> {
+        Ok(match self {
+            Expression::Negate(operand) => Expression::Negate(Box::new(operand.expand(variables)?)),
+            Expression::Binary(t, left, right) => Expression::binary(t, left.expand(variables)?, right.expand(variables)?),
+            Expression::Function(name, arguments) => {
+                let arguments = arguments.into_iter()
+                    .map(|argument| argument.expand(variables))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match name.as_str() {
+                    "diff" => {
+                        if let [_, Expression::Symbol(variable)] = arguments.as_slice() {
+                            variables.push(variable.clone());
+                        }
+                        Expression::differentiate(arguments)?
+                    }
+                    "simplify" => match <[Expression; 1]>::try_from(arguments) {
+                        Ok([argument]) => argument.simplify(),
+                        Err(arguments) =>
+                            return Err(CalculatorError::ArgumentCount { function: name, expected: 1, found: arguments.len() }),
+                    },
+                    _ => Expression::Function(name, arguments),
+                }
+            }
+            expression => expression,
+        })
+    }
+
+    /// How tightly this expression binds when printed, so whether it needs brackets as an operand
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Number(number) if number.as_integer().is_some() && *number >= Rational::zero() => ATOM,
+            Expression::Number(number) if *number < Rational::zero() => precedence(&Token::Negate),
+            Expression::Number(_) => precedence(&Token::Divide),
+            Expression::Negate(_) => precedence(&Token::Negate),
+            Expression::Binary(t, _, _) => precedence(t),
+            Expression::Symbol(_) | Expression::Function(..) => ATOM,
+        }
+    }
+
+    fn operand(f: &mut Formatter<'_>, operand: &Expression, brackets: bool) -> std::fmt::Result {
+        match brackets {
+            true => write!(f, "({})", operand),
+            false => write!(f, "{}", operand),
+        }
+    }
+}
+
+/// An expression with its symbolic functions worked out, and the names they treat as variables,
+/// such as the t of `diff(t^2, t)`, even where they are also units or constants
+#[derive(Debug, PartialEq)]
+pub struct Symbolic {
+    pub expression: Expression,
+    pub variables: Vec<String>,
+}
+
+/// Parses `expression` and works out its symbolic functions, such as `diff`, or None when it has none
+pub fn symbolic(expression: &str) -> Option<Result<Symbolic, CalculatorError>> {
+    let symbolic = expression.chars().tokenize()
+        .any(|token| matches!(&token, Token::Function(name, _) if SYMBOLIC.contains(&name.as_str())));
+    symbolic.then(|| {
+        let mut variables = vec![];
+        let expression = Expression::parse(expression)?.expand(&mut variables)?;
+        Ok(Symbolic { expression, variables })
+    })
+}
+
+/// Infix, with only the brackets the operators need, e.g. `3*x^2 - 2/(x + 1)`
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Number(number) => write!(f, "{}", number),
+            Expression::Symbol(name) => f.write_str(name),
+            Expression::Negate(operand) => {
+                // `-(x*y)` is `-x*y`, but sums and other signs need brackets
+                f.write_str("-")?;
+                Expression::operand(f, operand, operand.precedence() < self.precedence() || operand.negated().is_some())
+            }
+            Expression::Binary(t, left, right) => {
+                Expression::operand(f, left, left.precedence() < self.precedence())?;
+                match t {
+                    Token::Plus | Token::Minus => write!(f, " {} ", t)?,
+                    t => write!(f, "{}", t)?,
+                }
+                Expression::operand(f, right, right.precedence() <= self.precedence())
+            }
+            Expression::Function(name, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(Expression::to_string).collect();
+                write!(f, "{}({})", name, arguments.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod expression_tests {
+    use super::*;
+
+    fn round_trip(expression: &str) -> String {
+        Expression::parse(expression).unwrap().to_string()
+    }
+
+    #[test]
+    fn infix() {
+        assert_eq!("3*x^2 - 2/(x + 1)", round_trip("3 * x^2 - 2 / (x + 1)"));
+        assert_eq!("(a + b)*(a - b)", round_trip("(a+b)(a-b)"));
+        assert_eq!("a - (b - c)", round_trip("a - (b - c)"));
+        assert_eq!("-x^2 + (-x)^2", round_trip("-x^2 + (-x)^2"));
+        assert_eq!("sin(2*x) + 3√(x + 1)", round_trip("sin(2x) + 3√(x + 1)"));
+        assert_eq!("5/2*y", round_trip("2.5 y"));
+        assert_eq!(
+            Err(CalculatorError::UnsupportedOperation("! in an expression".to_string())),
+            Expression::parse("x!")
+        );
+    }
+
+    #[test]
+    fn folding() {
+        let x = || Expression::Symbol("x".to_string());
+        assert_eq!("x", x().plus(Expression::number(0)).times(Expression::number(1)).to_string());
+        assert_eq!("6*x", Expression::number(2).times(x().times(Expression::number(3))).to_string());
+        assert_eq!("x - 2*x", x().plus(Expression::number(-2).times(x())).to_string());
+        assert_eq!("0", x().minus(x()).to_string());
+        assert_eq!("1/8", Expression::number(2).power(Expression::number(-3)).to_string());
+        assert_eq!("-x/2", x().negate().over(Expression::number(2)).to_string());
+        assert_eq!("1", x().power(Expression::number(0)).to_string());
+    }
+
+    #[test]
+    fn symbolic_functions() {
+        assert!(symbolic("sin(x) + 1").is_none());
+        let expanded = symbolic("diff(x^2 + x, x)").unwrap().unwrap();
+        assert_eq!("2*x + 1", expanded.expression.to_string());
+        assert_eq!(vec!["x".to_string()], expanded.variables);
+    }
+}
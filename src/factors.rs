@@ -0,0 +1,72 @@
+use std::fmt::{Display, Formatter};
+
+use crate::format::{Format, FormatOptions};
+use crate::math::Math;
+
+/// A number as a product of prime powers, shown as `2^3 · 3 · 5`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Factors<T> {
+    /// Each prime with its power, in increasing order
+    factors: Vec<(T, u32)>,
+}
+
+impl<T> Factors<T> {
+    pub fn new(factors: Vec<(T, u32)>) -> Self {
+        Self { factors }
+    }
+
+    #[cfg(test)]
+    pub fn into_factors(self) -> Vec<(T, u32)> {
+        self.factors
+    }
+}
+
+impl<T: Math<T>> Factors<T> {
+    /// The number that was factored
+    pub fn product(self) -> T {
+        self.factors.into_iter()
+            .flat_map(|(prime, power)| std::iter::repeat_n(prime, power as usize))
+            .fold(T::one(), |product, prime| product * prime)
+    }
+}
+
+/// One has no prime factors and is shown as 1
+impl<T: Format> Format for Factors<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        if self.factors.is_empty() {
+            return f.write_str("1");
+        }
+        for (index, (prime, power)) in self.factors.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" · ")?;
+            }
+            prime.format(f, options)?;
+            if *power > 1 {
+                write!(f, "^{}", power)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Format> Display for Factors<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f, &FormatOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod factors_tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        assert_eq!("2^3 · 3 · 5", Factors::new(vec![(2, 3), (3, 1), (5, 1)]).to_string());
+        assert_eq!("1", Factors::<i32>::new(vec![]).to_string());
+    }
+
+    #[test]
+    fn product() {
+        assert_eq!(120, Factors::new(vec![(2, 3), (3, 1), (5, 1)]).product());
+    }
+}
@@ -0,0 +1,505 @@
+use std::fmt::{Display, Formatter, LowerExp};
+
+use num::{BigInt, FromPrimitive, Signed};
+
+use crate::bigfloat::BigFloat;
+use crate::complex::Complex;
+use crate::integer::Integer;
+use crate::math::{Real, Zero};
+use crate::rational::Rational;
+use crate::tolerance::Tolerance;
+use crate::word::Word;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AngleUnit {
+    Degrees,
+    Radians,
+}
+
+/// How complex numbers are written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ComplexForm {
+    /// `a + bi`
+    #[default]
+    Rectangular,
+    /// `r∠θ`
+    Polar(AngleUnit),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Notation {
+    #[default]
+    Standard,
+    /// One digit before the point, e.g. `1.5e4`
+    Scientific,
+    /// Exponents that are multiples of three, e.g. `15e3`
+    Engineering,
+}
+
+/// The base integers are shown in, with the prefix of their literals
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Base {
+    Binary,
+    Octal,
+    #[default]
+    Decimal,
+    Hexadecimal,
+}
+
+impl Base {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bin" | "binary" => Some(Base::Binary),
+            "oct" | "octal" => Some(Base::Octal),
+            "dec" | "decimal" => Some(Base::Decimal),
+            "hex" | "hexadecimal" => Some(Base::Hexadecimal),
+            _ => None,
+        }
+    }
+
+    fn radix(self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            Base::Binary => "0b",
+            Base::Octal => "0o",
+            Base::Decimal => "",
+            Base::Hexadecimal => "0x",
+        }
+    }
+
+    /// Writes an integer as a literal in this base, e.g. `-0xFF`
+    pub fn format(self, integer: &BigInt) -> String {
+        let sign = if integer.is_negative() { "-" } else { "" };
+        format!("{}{}{}", sign, self.prefix(), integer.magnitude().to_str_radix(self.radix()).to_uppercase())
+    }
+}
+
+/// How many digits of a number are shown
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Precision {
+    /// As many as are needed to read back the same number
+    #[default]
+    Shortest,
+    Decimals(usize),
+    Significant(usize),
+}
+
+/// The session's choices for how results are displayed
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOptions {
+    pub complex: ComplexForm,
+    /// `i`, or `j` as electrical engineers write it
+    pub imaginary_unit: char,
+    pub notation: Notation,
+    pub precision: Precision,
+    /// Separates thousands with commas, e.g. `1,234,567`
+    pub grouping: bool,
+    /// Numbers smaller than this are shown as zero, hiding rounding errors such as `cos(pi/2)`
+    pub snap: Option<f64>,
+    /// The base integers are shown in, other numbers are always decimal
+    pub base: Base,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            complex: ComplexForm::default(),
+            imaginary_unit: 'i',
+            notation: Notation::default(),
+            precision: Precision::default(),
+            grouping: false,
+            snap: None,
+            base: Base::default(),
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Applies the argument of a `:format` command, e.g. `polar radians`, `j`, `sig 6`, `eng`, `snap 1e-12`, `snap on` or `base hex`
+    pub fn set(&mut self, setting: &str) -> Result<(), String> {
+        let words: Vec<&str> = setting.split_whitespace().collect();
+        let count = |digits: &str| digits.parse::<usize>().map_err(|_| format!("Expected a number of digits, found \"{}\"", digits));
+        match words.as_slice() {
+            ["default"] => *self = FormatOptions::default(),
+            ["rectangular"] => self.complex = ComplexForm::Rectangular,
+            ["polar"] | ["polar", "degrees"] => self.complex = ComplexForm::Polar(AngleUnit::Degrees),
+            ["polar", "radians"] => self.complex = ComplexForm::Polar(AngleUnit::Radians),
+            ["i"] => self.imaginary_unit = 'i',
+            ["j"] => self.imaginary_unit = 'j',
+            ["standard"] => self.notation = Notation::Standard,
+            ["sci"] => self.notation = Notation::Scientific,
+            ["eng"] => self.notation = Notation::Engineering,
+            ["shortest"] => self.precision = Precision::Shortest,
+            ["fixed", decimals] => self.precision = Precision::Decimals(count(decimals)?),
+            ["sig", digits] => match count(digits)? {
+                0 => return Err("Need at least one significant figure".to_string()),
+                digits => self.precision = Precision::Significant(digits),
+            },
+            ["group", "on"] => self.grouping = true,
+            ["group", "off"] => self.grouping = false,
+            ["snap", "off"] => self.snap = None,
+            ["snap", "on"] => self.snap = Some(Tolerance::default().absolute),
+            ["snap", threshold] => match threshold.parse::<f64>() {
+                Ok(threshold) if threshold > 0f64 => self.snap = Some(threshold),
+                _ => return Err(format!("Expected a positive threshold, found \"{}\"", threshold)),
+            },
+            ["base", name] => match Base::parse(name) {
+                Some(base) => self.base = base,
+                None => return Err(format!("Unknown base \"{}\", expected bin, oct, dec or hex", name)),
+            },
+            _ => return Err(format!("Unknown format \"{}\"", setting.trim())),
+        };
+        Ok(())
+    }
+
+    /// Whether numbers are shown exactly as they are, so exact types need not be converted to decimals
+    fn is_exact(&self) -> bool {
+        self.notation == Notation::Standard && self.precision == Precision::Shortest
+    }
+
+    fn snaps(&self, magnitude: f64) -> bool {
+        matches!(self.snap, Some(threshold) if magnitude < threshold)
+    }
+
+    /// Writes an integer in the chosen base, with digit grouping in decimal
+    fn integer(&self, integer: &BigInt) -> String {
+        match self.base {
+            Base::Decimal if self.grouping => group(&integer.to_string()),
+            base => base.format(integer),
+        }
+    }
+}
+
+/// Display that depends on the session's [FormatOptions]
+pub trait Format {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result;
+}
+
+/// Displays a value with the given options, flags such as `{:#}` are passed through
+pub struct Formatted<'a, T>(pub &'a T, pub &'a FormatOptions);
+
+impl<T: Format> Display for Formatted<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.0.format(f, self.1)
+    }
+}
+
+/// Formats a floating point number with the notation, precision, grouping and snapping options
+fn format_float<F>(value: F, options: &FormatOptions) -> String
+    where F: Display + LowerExp + Copy + Into<f64> + Zero
+{
+    let value = if options.snaps(value.into().abs()) { F::zero() } else { value };
+    if !value.into().is_finite() {
+        return value.to_string();
+    }
+    if options.base != Base::Decimal && value.into().fract() == 0f64 {
+        if let Some(integer) = BigInt::from_f64(value.into()) {
+            return options.base.format(&integer);
+        }
+    }
+    let scientific = match options.precision {
+        Precision::Shortest => format!("{:e}", value),
+        Precision::Decimals(decimals) => format!("{:.*e}", decimals, value),
+        Precision::Significant(digits) => format!("{:.*e}", digits - 1, value),
+    };
+    let text = match (options.notation, options.precision) {
+        (Notation::Standard, Precision::Shortest) => value.to_string(),
+        (Notation::Standard, Precision::Decimals(decimals)) => format!("{:.*}", decimals, value),
+        (Notation::Standard, Precision::Significant(_)) => shift_point(&scientific, false),
+        (Notation::Scientific, _) => scientific,
+        (Notation::Engineering, _) => shift_point(&scientific, true),
+    };
+    if options.grouping { group(&text) } else { text }
+}
+
+/// Rewrites scientific notation, e.g. `1.50e4`, without an exponent as `15000` or, for
+/// `engineering`, with an exponent that is a multiple of three as `15.0e3`
+fn shift_point(scientific: &str, engineering: bool) -> String {
+    let (mantissa, exponent) = scientific.split_once('e').expect("Scientific notation has an exponent");
+    let exponent: i32 = exponent.parse().expect("Exponents are integers");
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits = mantissa.replace('.', "");
+    let (shift, exponent) = if engineering {
+        (exponent.rem_euclid(3), Some(exponent - exponent.rem_euclid(3)))
+    } else {
+        (exponent, None)
+    };
+    // The number of digits before the point
+    let point = shift + 1;
+    let plain = if point <= 0 {
+        format!("0.{}{}", "0".repeat(point.unsigned_abs() as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    };
+    match exponent {
+        Some(exponent) => format!("{}{}e{}", sign, plain, exponent),
+        None => format!("{}{}", sign, plain),
+    }
+}
+
+/// Separates the thousands of the whole part of a number with commas
+fn group(number: &str) -> String {
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", number),
+    };
+    let whole = number.find(|c: char| !c.is_ascii_digit()).unwrap_or(number.len());
+    let (digits, rest) = number.split_at(whole);
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}{}", sign, grouped, rest)
+}
+
+impl Format for f64 {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        f.write_str(&format_float(*self, options))
+    }
+}
+
+impl Format for f32 {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        f.write_str(&format_float(*self, options))
+    }
+}
+
+/// Fractions are shown exactly unless a notation or precision is chosen
+impl Format for Rational {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        if let Some(integer) = self.as_integer() {
+            return f.write_str(&options.integer(&integer));
+        }
+        if options.is_exact() {
+            let fraction = self.to_string();
+            return f.write_str(&if options.grouping { group(&fraction) } else { fraction });
+        }
+        f.write_str(&format_float(self.to_f64(), options))
+    }
+}
+
+/// Decimals are shown in full unless a notation or precision is chosen
+impl Format for BigFloat {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        if let Some(integer) = self.as_integer() {
+            return f.write_str(&options.integer(&integer));
+        }
+        if options.is_exact() {
+            let decimal = self.to_string();
+            return f.write_str(&if options.grouping { group(&decimal) } else { decimal });
+        }
+        f.write_str(&format_float(self.to_f64(), options))
+    }
+}
+
+/// Other bases show the bit pattern, as literals in them are read, so -1 is `0xFFFFFFFF`
+impl Format for i32 {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        let value = match options.base {
+            Base::Decimal => BigInt::from(*self),
+            _ => BigInt::from(*self as u32),
+        };
+        f.write_str(&options.integer(&value))
+    }
+}
+
+impl Format for Integer {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        f.write_str(&options.integer(self.as_big_int()))
+    }
+}
+
+/// In bases other than decimal, signed words show their bits, so -1 in 8 bits is `0xFF`
+impl<const BITS: u32, const SIGNED: bool> Format for Word<BITS, SIGNED> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        let value = match options.base {
+            Base::Decimal => self.value(),
+            _ => self.bits(),
+        };
+        f.write_str(&options.integer(&BigInt::from(value)))
+    }
+}
+
+impl<T: Real + Format + Display> Format for Complex<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        match options.complex {
+            ComplexForm::Rectangular => {
+                let snap = |part: T| if options.snaps(part.magnitude()) { T::zero() } else { part };
+                let (real, imaginary) = self.clone().into_parts();
+                Complex::new(snap(real), snap(imaginary))
+                    .write_rectangular(f, options.imaginary_unit, |part| Formatted(part, options).to_string())
+            }
+            ComplexForm::Polar(unit) => {
+                let polar = num::Complex::from(self.clone());
+                let (magnitude, angle) = polar.to_polar();
+                if magnitude == 0f64 || options.snaps(magnitude) {
+                    return f.write_str("0");
+                }
+                let magnitude = format_float(magnitude, options);
+                match unit {
+                    AngleUnit::Degrees => write!(f, "{}∠{}°", magnitude, format_float(angle.to_degrees(), options)),
+                    AngleUnit::Radians => write!(f, "{}∠{}", magnitude, format_float(angle, options)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod format_tests {
+    use super::*;
+
+    fn polar(unit: AngleUnit) -> FormatOptions {
+        FormatOptions { complex: ComplexForm::Polar(unit), ..FormatOptions::default() }
+    }
+
+    #[test]
+    fn set() {
+        let mut options = FormatOptions::default();
+        assert_eq!(Ok(()), options.set("polar"));
+        assert_eq!(polar(AngleUnit::Degrees), options);
+        assert_eq!(Ok(()), options.set(" polar  radians "));
+        assert_eq!(polar(AngleUnit::Radians), options);
+        assert_eq!(Ok(()), options.set("rectangular"));
+        assert_eq!(FormatOptions::default(), options);
+        assert!(options.set("sideways").is_err());
+    }
+
+    #[test]
+    fn rectangular() {
+        let value = Complex::new(1f64, 1f64);
+        assert_eq!("1 + i", Formatted(&value, &FormatOptions::default()).to_string());
+    }
+
+    #[test]
+    fn rectangular_with_j() {
+        let mut options = FormatOptions::default();
+        assert_eq!(Ok(()), options.set("j"));
+        let value = Complex::new(3f64, -4f64);
+        assert_eq!("3 - 4j", Formatted(&value, &options).to_string());
+    }
+
+    #[test]
+    fn polar_degrees() {
+        let value = Complex::new(0f64, 2f64);
+        assert_eq!("2∠90°", Formatted(&value, &polar(AngleUnit::Degrees)).to_string());
+    }
+
+    #[test]
+    fn polar_radians() {
+        let value = Complex::new(-3f64, 0f64);
+        assert_eq!("3∠3.141592653589793", Formatted(&value, &polar(AngleUnit::Radians)).to_string());
+    }
+
+    #[test]
+    fn polar_zero() {
+        let value = Complex::new(0f64, 0f64);
+        assert_eq!("0", Formatted(&value, &polar(AngleUnit::Degrees)).to_string());
+    }
+
+    fn formatted(value: f64, settings: &[&str]) -> String {
+        let mut options = FormatOptions::default();
+        for setting in settings {
+            options.set(setting).unwrap();
+        }
+        Formatted(&value, &options).to_string()
+    }
+
+    #[test]
+    fn shortest() {
+        assert_eq!("0.30000000000000004", formatted(0.1f64 + 0.2f64, &[]));
+    }
+
+    #[test]
+    fn fixed_decimals() {
+        assert_eq!("0.30", formatted(0.1f64 + 0.2f64, &["fixed 2"]));
+        assert_eq!("-1235", formatted(-1234.5678f64, &["fixed 0"]));
+    }
+
+    #[test]
+    fn significant_figures() {
+        assert_eq!("0.3", formatted(0.1f64 + 0.2f64, &["sig 12"]).trim_end_matches('0'));
+        assert_eq!("123000", formatted(123456f64, &["sig 3"]));
+        assert_eq!("0.00123", formatted(0.0012345f64, &["sig 3"]));
+        assert_eq!("10.0", formatted(9.996f64, &["sig 3"]));
+    }
+
+    #[test]
+    fn scientific() {
+        assert_eq!("1.2345e4", formatted(12345f64, &["sci"]));
+        assert_eq!("1.23e4", formatted(12345f64, &["sci", "sig 3"]));
+        assert_eq!("-2.5e-4", formatted(-0.00025f64, &["sci"]));
+    }
+
+    #[test]
+    fn engineering() {
+        assert_eq!("12.345e3", formatted(12345f64, &["eng"]));
+        assert_eq!("250e-6", formatted(0.00025f64, &["eng"]));
+        assert_eq!("1.50e0", formatted(1.5f64, &["eng", "sig 3"]));
+        assert_eq!("123e3", formatted(123456f64, &["eng", "sig 3"]));
+    }
+
+    #[test]
+    fn grouping() {
+        assert_eq!("1,234,567", formatted(1234567f64, &["group on"]));
+        assert_eq!("-123,456.75", formatted(-123456.75f64, &["group on"]));
+        assert_eq!("999", formatted(999f64, &["group on"]));
+    }
+
+    #[test]
+    fn snapping() {
+        assert_eq!("0", formatted(6.123233995736766e-17f64, &["snap 1e-12"]));
+        assert_eq!("0.001", formatted(0.001f64, &["snap 1e-12"]));
+        assert_eq!("0", formatted(1.2246467991473532e-16f64, &["snap on"]));
+    }
+
+    #[test]
+    fn complex_parts() {
+        let mut options = FormatOptions::default();
+        options.set("fixed 2").unwrap();
+        options.set("snap 1e-12").unwrap();
+        let value = Complex::new(1.2345e-16f64, 2f64 / 3f64);
+        assert_eq!("0.67i", Formatted(&value, &options).to_string());
+        let value = Complex::new(1f64 / 3f64, -1f64);
+        assert_eq!("0.33 - i", Formatted(&value, &options).to_string());
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let mut options = FormatOptions::default();
+        assert!(options.set("sig 0").is_err());
+        assert!(options.set("fixed two").is_err());
+        assert!(options.set("snap -1").is_err());
+        assert_eq!(FormatOptions::default(), options);
+    }
+
+    #[test]
+    fn bases() {
+        assert_eq!("0xFF", formatted(255f64, &["base hex"]));
+        assert_eq!("-0b1010", formatted(-10f64, &["base bin"]));
+        assert_eq!("0o17", Formatted(&15i32, &FormatOptions { base: Base::Octal, ..FormatOptions::default() }).to_string());
+        assert_eq!("0xFFFFFFFF", Formatted(&-1i32, &FormatOptions { base: Base::Hexadecimal, ..FormatOptions::default() }).to_string());
+        assert_eq!("2.5", formatted(2.5f64, &["base hex"]));
+        let big = Integer::new(1 << 40) * Integer::new(1 << 40);
+        assert_eq!("0x100000000000000000000", Formatted(&big, &FormatOptions { base: Base::Hexadecimal, ..FormatOptions::default() }).to_string());
+        let word = Word::<8, true>::new(-1);
+        assert_eq!("0xFF", Formatted(&word, &FormatOptions { base: Base::Hexadecimal, ..FormatOptions::default() }).to_string());
+        assert_eq!("-1", Formatted(&word, &FormatOptions::default()).to_string());
+    }
+}
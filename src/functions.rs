@@ -0,0 +1,249 @@
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::number_theory;
+use crate::rounding::Rounding;
+use crate::statistics;
+use crate::tokenizer::Token;
+use crate::tolerance::Tolerance;
+use crate::math::Math;
+use crate::matrix::Matrix;
+use crate::quantity::Quantity;
+use crate::value::Value;
+
+/// Names the tokenizer recognises as functions rather than operands
+pub const FUNCTIONS: &[&str] = &[
+    "sqrt", "exp", "ln", "sin", "cos", "tan", "abs",
+    "round", "floor", "ceil", "trunc", "frac", "sign",
+    "arg", "conj", "re", "im", "cis",
+    "rotl", "rotr",
+    "gamma", "lgamma", "nCr", "nPr",
+    "gcd", "lcm", "isprime", "factor", "modpow", "modinv",
+    "percentof", "percentchange", "aspercent",
+    "sum", "mean", "median", "mode", "min", "max", "range", "var", "varp", "stdev", "stdevp", "percentile",
+    "if", "approx", "diff", "simplify",
+    "dot", "cross", "norm",
+    "det", "inv", "transpose", "identity", "solve",
+];
+
+/// The functions of a single number, which apply element-wise to vectors
+pub(crate) fn elementary<T: Math<T>>(name: &str) -> Option<fn(T) -> Result<T, CalculatorError>> {
+    match name {
+        "sqrt" => Some(|value| Ok(value.sqrt())),
+        "exp" => Some(|value| Ok(value.exp())),
+        "ln" => Some(|value| Ok(value.ln())),
+        "sin" => Some(|value| Ok(value.sin())),
+        "cos" => Some(|value| Ok(value.cos())),
+        "tan" => Some(|value| Ok(value.tan())),
+        "abs" => Some(T::abs),
+        "arg" => Some(|value| Ok(value.arg())),
+        "conj" => Some(|value| Ok(value.conj())),
+        "re" => Some(|value| Ok(value.re())),
+        "im" => Some(|value| Ok(value.im())),
+        _ => None,
+    }
+}
+
+/// Calls the function `name` with `arguments`, in the order they were written. `round` uses the
+/// `rounding` mode of the session and `approx` its `tolerance`.
+pub(crate) fn call<T: Math<T>>(name: &str, arguments: Vec<Value<T>>, rounding: Rounding, tolerance: &Tolerance) -> Result<Value<T>, CalculatorError> {
+    let arguments: Vec<Value<T>> = arguments.into_iter().map(Value::expand).collect();
+    if name == "approx" {
+        return approx(arguments, tolerance);
+    }
+    if arguments.iter().any(|argument| matches!(argument, Value::Quantity(_))) {
+        return quantities(name, arguments);
+    }
+    if let Some(operation) = elementary(name) {
+        return one(name, arguments)?.map(operation);
+    }
+    match name {
+        "cis" => one(name, arguments)?.map(T::cis),
+        "round" => match arguments.len() {
+            1 => one(name, arguments)?.map(|value| value.round_to(0, rounding)),
+            _ => match two(name, arguments)? {
+                (value, Value::Scalar(digits)) => {
+                    let digits = decimal_places(digits)?;
+                    value.map(|value| value.round_to(digits, rounding))
+                }
+                (value, digits) => Err(unsupported(name, &[value, digits])),
+            },
+        },
+        "floor" => one(name, arguments)?.map(|value| value.round_to(0, Rounding::Floor)),
+        "ceil" => one(name, arguments)?.map(|value| value.round_to(0, Rounding::Ceiling)),
+        "trunc" => one(name, arguments)?.map(|value| value.round_to(0, Rounding::TowardZero)),
+        "frac" => one(name, arguments)?.map(|value| Ok(value.clone() - value.round_to(0, Rounding::TowardZero)?)),
+        "sign" => one(name, arguments)?.map(|value| Ok(value.sign())),
+        "gamma" => one(name, arguments)?.map(T::gamma),
+        "lgamma" => one(name, arguments)?.map(T::ln_gamma),
+        "nCr" | "nPr" => match two(name, arguments)? {
+            (Value::Scalar(n), Value::Scalar(r)) if name == "nCr" => Ok(Value::Scalar(combinatorics::combinations(n, r)?)),
+            (Value::Scalar(n), Value::Scalar(r)) => Ok(Value::Scalar(combinatorics::permutations(n, r)?)),
+            (n, r) => Err(unsupported(name, &[n, r])),
+        },
+        "percentof" | "percentchange" | "aspercent" => match two(name, arguments)? {
+            (Value::Scalar(first), Value::Scalar(second)) => Ok(Value::Scalar(percentage(name, first, second)?)),
+            (first, second) => Err(unsupported(name, &[first, second])),
+        },
+        "sum" | "mean" | "median" | "mode" | "min" | "max" | "range" | "var" | "varp" | "stdev" | "stdevp" =>
+            Ok(Value::Scalar(statistics::calculate(name, statistics::values(name, arguments)?)?)),
+        "percentile" => {
+            let mut arguments = arguments;
+            // percentile([values], p) as spreadsheets write it, otherwise percentile(p, values...)
+            let p = match arguments.as_slice() {
+                [] => None,
+                [Value::Vector(_), _] => arguments.pop(),
+                _ => Some(arguments.remove(0)),
+            };
+            match p {
+                Some(Value::Scalar(p)) => Ok(Value::Scalar(statistics::percentile(p, statistics::values(name, arguments)?)?)),
+                Some(_) => Err(CalculatorError::UnsupportedOperation(
+                    "percentile without a number p, write percentile(p, values...) or percentile([values], p)".to_string())),
+                None => Err(CalculatorError::ArgumentCount { function: name.to_string(), expected: 2, found: 0 }),
+            }
+        }
+        "isprime" => one(name, arguments)?.map(number_theory::is_prime),
+        "factor" => match one(name, arguments)? {
+            Value::Scalar(n) => Ok(Value::Factors(number_theory::factor(n)?)),
+            n => Err(unsupported(name, &[n])),
+        },
+        "gcd" | "lcm" | "modinv" => match two(name, arguments)? {
+            (Value::Scalar(a), Value::Scalar(b)) => Ok(Value::Scalar(match name {
+                "gcd" => number_theory::gcd(a, b)?,
+                "lcm" => number_theory::lcm(a, b)?,
+                _ => number_theory::modinv(a, b)?,
+            })),
+            (a, b) => Err(unsupported(name, &[a, b])),
+        },
+        "modpow" => match exactly(name, arguments)? {
+            [Value::Scalar(base), Value::Scalar(exponent), Value::Scalar(modulus)] =>
+                Ok(Value::Scalar(number_theory::modpow(base, exponent, modulus)?)),
+            arguments => Err(unsupported(name, &arguments)),
+        },
+        "rotl" | "rotr" => match two(name, arguments)? {
+            (Value::Scalar(value), Value::Scalar(amount)) if name == "rotl" => Ok(Value::Scalar(value.rotate_left(amount)?)),
+            (Value::Scalar(value), Value::Scalar(amount)) => Ok(Value::Scalar(value.rotate_right(amount)?)),
+            (value, amount) => Err(unsupported(name, &[value, amount])),
+        },
+        "dot" => match two(name, arguments)? {
+            (Value::Vector(left), Value::Vector(right)) => Ok(Value::Scalar(left.dot(right)?)),
+            (left, right) => Err(unsupported(name, &[left, right])),
+        },
+        "cross" => match two(name, arguments)? {
+            (Value::Vector(left), Value::Vector(right)) => Ok(Value::Vector(left.cross(right)?)),
+            (left, right) => Err(unsupported(name, &[left, right])),
+        },
+        "norm" => match one(name, arguments)? {
+            Value::Scalar(value) => Ok(Value::Scalar(value.abs()?)),
+            Value::Vector(vector) => Ok(Value::Scalar(vector.norm()?)),
+            argument => Err(unsupported(name, &[argument])),
+        },
+        "det" => match one(name, arguments)? {
+            Value::Matrix(matrix) => Ok(Value::Scalar(matrix.determinant()?)),
+            argument => Err(unsupported(name, &[argument])),
+        },
+        "inv" => match one(name, arguments)? {
+            Value::Scalar(value) => Ok(Value::Scalar(T::one().checked_div(value)?)),
+            Value::Matrix(matrix) => Ok(Value::Matrix(matrix.inverse()?)),
+            argument => Err(unsupported(name, &[argument])),
+        },
+        "transpose" => match one(name, arguments)? {
+            Value::Matrix(matrix) => Ok(Value::Matrix(matrix.transpose())),
+            argument => Err(unsupported(name, &[argument])),
+        },
+        "solve" => match two(name, arguments)? {
+            (Value::Matrix(a), Value::Vector(b)) => Ok(Value::Vector(a.solve_vector(b)?)),
+            (Value::Matrix(a), Value::Matrix(b)) => Ok(Value::Matrix(a.solve(b)?)),
+            (a, b) => Err(unsupported(name, &[a, b])),
+        },
+        "identity" => match one(name, arguments)? {
+            Value::Scalar(size) => match size.to_integer() {
+                Some(size) if size > 0 => Ok(Value::Matrix(Matrix::identity(size as usize)?)),
+                _ => Err(CalculatorError::UnsupportedOperation("identity of a non-positive integer size".to_string())),
+            },
+            argument => Err(unsupported(name, &[argument])),
+        },
+        _ => Err(CalculatorError::UnknownFunction(name.to_string())),
+    }
+}
+
+/// The functions defined for a quantity, `abs` which keeps its unit and `sqrt` which needs a unit
+/// that is a square, such as m^2
+fn quantities<T: Math<T>>(name: &str, mut arguments: Vec<Value<T>>) -> Result<Value<T>, CalculatorError> {
+    match (name, arguments.pop()) {
+        ("abs", Some(Value::Quantity(quantity))) if arguments.is_empty() => Ok(Value::Quantity(quantity.map(T::abs)?)),
+        ("sqrt", Some(Value::Quantity(quantity))) if arguments.is_empty() => {
+            let two = T::one() + T::one();
+            Quantity::dimensionless(two).combine(&Token::Root, quantity, |index, value| Ok(index.root(value)))
+        }
+        (_, last) => {
+            arguments.extend(last);
+            Err(unsupported(name, &arguments))
+        }
+    }
+}
+
+/// `percentof(p, x)` is p% of x, `percentchange(from, to)` is the change as a percentage of `from`
+/// and `aspercent(part, whole)` is `part` as a percentage of `whole`. Multiplying before dividing
+/// keeps integer results exact where they can be.
+fn percentage<T: Math<T>>(name: &str, first: T, second: T) -> Result<T, CalculatorError> {
+    match name {
+        "percentof" => percent_of(first, second),
+        "percentchange" => ((second - first.clone()) * hundred()).checked_div(first),
+        _ => (first * hundred()).checked_div(second),
+    }
+}
+
+fn hundred<T: Math<T>>() -> T {
+    let ten = (0..10).fold(T::zero(), |sum, _| sum + T::one());
+    ten.clone() * ten
+}
+
+/// `percent`% of `value`, as for `percentof` and the relative percentages of percentage mode
+pub(crate) fn percent_of<T: Math<T>>(percent: T, value: T) -> Result<T, CalculatorError> {
+    value.checked_times(percent)?.checked_div(hundred())
+}
+
+/// `approx(a, b)` is `a ≈ b`, `approx(a, b, tol)` whether a and b are at most tol apart
+fn approx<T: Math<T>>(arguments: Vec<Value<T>>, tolerance: &Tolerance) -> Result<Value<T>, CalculatorError> {
+    match arguments.len() {
+        3 => {
+            let [left, right, within]: [Value<T>; 3] = exactly("approx", arguments)?;
+            let distance = match left.combine(&Token::Minus, right, T::checked_minus)? {
+                Value::Quantity(quantity) => Value::Quantity(quantity.map(T::abs)?),
+                difference => difference.map(T::abs)?,
+            };
+            distance.compare(&Token::LessEqual, within, tolerance)
+        }
+        _ => {
+            let (left, right) = two("approx", arguments)?;
+            left.compare(&Token::Approx, right, tolerance)
+        }
+    }
+}
+
+/// The number of decimal places to round to, from the second argument of `round`
+fn decimal_places<T: Math<T>>(digits: T) -> Result<i32, CalculatorError> {
+    let digits = digits.to_integer()
+        .ok_or_else(|| CalculatorError::UnsupportedOperation("round to a fractional number of places".to_string()))?;
+    i32::try_from(digits).map_err(|_| CalculatorError::Overflow)
+}
+
+fn one<T>(name: &str, arguments: Vec<Value<T>>) -> Result<Value<T>, CalculatorError> {
+    let [argument]: [Value<T>; 1] = exactly(name, arguments)?;
+    Ok(argument)
+}
+
+fn two<T>(name: &str, arguments: Vec<Value<T>>) -> Result<(Value<T>, Value<T>), CalculatorError> {
+    let [first, second]: [Value<T>; 2] = exactly(name, arguments)?;
+    Ok((first, second))
+}
+
+fn exactly<T, const N: usize>(name: &str, arguments: Vec<Value<T>>) -> Result<[Value<T>; N], CalculatorError> {
+    let found = arguments.len();
+    arguments.try_into().map_err(|_| CalculatorError::ArgumentCount { function: name.to_string(), expected: N, found })
+}
+
+fn unsupported<T>(name: &str, arguments: &[Value<T>]) -> CalculatorError {
+    let kinds: Vec<&str> = arguments.iter().map(|argument| argument.kind()).collect();
+    CalculatorError::UnsupportedOperation(format!("{}({})", name, kinds.join(", ")))
+}
@@ -0,0 +1,356 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use num::{BigInt, FromPrimitive, Integer as NumInteger, Signed, ToPrimitive};
+
+use crate::calculator::{FromStrValue, radix_literal};
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::{self, Rounding};
+use crate::math::{shift_amount, BigInteger, Bitwise, Checked, CheckedDiv, Compare, Elementary, Gamma, Inv, Magnitude, Modulo, One, Percent, Polar, Pow, Round, ToInteger, Zero};
+
+/// An integer of arbitrary size. As for `i32`, division truncates and functions without integer
+/// results are truncated.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Integer(BigInt);
+
+impl Integer {
+    pub fn new(value: i64) -> Self {
+        Integer(BigInt::from(value))
+    }
+
+    pub fn as_big_int(&self) -> &BigInt {
+        &self.0
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// Calculates a function without an integer form through `f64`, truncating the result
+    fn through_f64(self, function: fn(f64) -> f64) -> Self {
+        Integer(BigInt::from_f64(function(self.to_f64()).trunc()).unwrap_or_default())
+    }
+}
+
+impl Add for Integer {
+    type Output = Integer;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Integer(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Integer {
+    type Output = Integer;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Integer(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Integer {
+    type Output = Integer;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Integer(self.0 * rhs.0)
+    }
+}
+
+/// Panics when dividing by zero, [CheckedDiv] reports it instead
+impl Div for Integer {
+    type Output = Integer;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Integer(self.0 / rhs.0)
+    }
+}
+
+impl Neg for Integer {
+    type Output = Integer;
+
+    fn neg(self) -> Self::Output {
+        Integer(-self.0)
+    }
+}
+
+impl CheckedDiv for Integer {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if num::Zero::is_zero(&rhs.0) {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+}
+
+impl Zero for Integer {
+    fn zero() -> Self {
+        Integer(num::Zero::zero())
+    }
+}
+
+impl One for Integer {
+    fn one() -> Self {
+        Integer(num::One::one())
+    }
+}
+
+impl Inv for Integer {
+    fn inv(self) -> Self {
+        Integer::one() / self
+    }
+}
+
+/// Negative powers truncate to zero, except of one and minus one
+impl Pow for Integer {
+    fn pow(self, rhs: Self) -> Self {
+        match rhs.0.to_biguint() {
+            Some(exponent) => Integer(num::pow::Pow::pow(self.0, exponent)),
+            None => match self.0.to_i8() {
+                Some(-1) if rhs.0.is_odd() => self,
+                Some(1) | Some(-1) => Integer::one(),
+                _ => Integer::zero(),
+            },
+        }
+    }
+}
+
+impl Percent for Integer {
+    fn percent(self) -> Self {
+        self / Integer::new(100)
+    }
+}
+
+impl Elementary for Integer {
+    fn sqrt(self) -> Self {
+        if self.0.is_negative() {
+            return Integer::zero();
+        }
+        Integer(self.0.sqrt())
+    }
+
+    fn exp(self) -> Self {
+        self.through_f64(f64::exp)
+    }
+
+    fn ln(self) -> Self {
+        self.through_f64(f64::ln)
+    }
+
+    fn sin(self) -> Self {
+        self.through_f64(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.through_f64(f64::cos)
+    }
+
+    fn tan(self) -> Self {
+        self.through_f64(f64::tan)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(Integer(self.0.abs()))
+    }
+}
+
+impl Polar for Integer {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        Integer::zero()
+    }
+
+    fn arg(self) -> Self {
+        self.through_f64(|value| 0f64.atan2(value))
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        if !num::Zero::is_zero(&self.0) {
+            return Err(CalculatorError::ComplexResult);
+        }
+        Ok(Integer::one())
+    }
+
+    fn degrees(self) -> Self {
+        self.through_f64(f64::to_radians)
+    }
+}
+
+impl Magnitude for Integer {
+    const INTEGER: bool = true;
+
+    fn magnitude(&self) -> f64 {
+        self.to_f64().abs()
+    }
+}
+
+impl BigInteger for Integer {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        Some(self.0.clone())
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        Ok(Integer(value))
+    }
+}
+
+impl Compare for Integer {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+}
+
+impl Round for Integer {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(Integer(rounding::round_integer(self.0, digits, rounding)))
+    }
+
+    fn sign(self) -> Self {
+        Integer(self.0.signum())
+    }
+}
+
+/// Integer results are truncated, as for integer division
+impl Gamma for Integer {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        combinatorics::factorial(self - Integer::one())
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        let value = Gamma::ln_gamma(self.to_f64())?;
+        Ok(Integer(BigInt::from_f64(value.trunc()).unwrap_or_default()))
+    }
+}
+
+impl Modulo for Integer {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if num::Zero::is_zero(&rhs.0) {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(Integer(self.0.mod_floor(&rhs.0.abs())))
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let remainder = self.clone().modulo(rhs.clone())?;
+        Ok(Integer((self.0 - remainder.0) / rhs.0))
+    }
+}
+
+/// Negative numbers behave as if they had infinitely many leading ones, so there is no width to
+/// rotate within
+impl Checked for Integer {}
+
+impl Bitwise for Integer {
+    fn bit_and(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Integer(self.0 & rhs.0))
+    }
+
+    fn bit_or(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Integer(self.0 | rhs.0))
+    }
+
+    fn bit_xor(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Integer(self.0 ^ rhs.0))
+    }
+
+    fn bit_not(self) -> Result<Self, CalculatorError> {
+        Ok(Integer(!self.0))
+    }
+
+    fn shift_left(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let amount = shift_amount(rhs.to_integer().ok_or(CalculatorError::Overflow)?)?;
+        Ok(Integer(self.0 << amount))
+    }
+
+    fn shift_right(self, rhs: Self) -> Result<Self, CalculatorError> {
+        match rhs.to_integer() {
+            Some(amount) => Ok(Integer(self.0 >> shift_amount(amount)?)),
+            None if rhs.0.is_negative() => Err(CalculatorError::UnsupportedOperation("shift by a negative amount".to_string())),
+            None if self.0.is_negative() => Ok(Integer::new(-1)),
+            None => Ok(Integer::zero()),
+        }
+    }
+
+    fn rotate_left(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::UnsupportedOperation("rotl of an integer without a width, e.g. use --word u32".to_string()))
+    }
+
+    fn rotate_right(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::UnsupportedOperation("rotr of an integer without a width, e.g. use --word u32".to_string()))
+    }
+}
+
+impl ToInteger for Integer {
+    fn to_integer(&self) -> Option<i64> {
+        self.0.to_i64()
+    }
+}
+
+/// Parses decimal integers, or integers in other bases such as `0x1F`
+impl FromStr for Integer {
+    type Err = CalculatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (radix, digits) = radix_literal(s).unwrap_or((10, s));
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(CalculatorError::Parse(s.to_string()));
+        }
+        BigInt::parse_bytes(digits.as_bytes(), radix)
+            .map(Integer)
+            .ok_or_else(|| CalculatorError::Parse(s.to_string()))
+    }
+}
+
+impl FromStrValue for Integer {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+    }
+}
+
+impl Display for Integer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod integer_tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Ok(Integer::new(255)), "255".parse());
+        assert_eq!(Ok(Integer::new(31)), "0x1F".parse());
+        assert_eq!(Ok(Integer::new(10)), "0b1010".parse());
+        assert_eq!(Ok(Integer::new(15)), "0o17".parse());
+        assert_eq!(Err(CalculatorError::Parse("2.5".to_string())), "2.5".parse::<Integer>());
+    }
+
+    #[test]
+    fn beyond_64_bits() {
+        let big = Integer::new(2).pow(Integer::new(100));
+        assert_eq!("1267650600228229401496703205376", big.to_string());
+        assert_eq!(None, big.to_integer());
+    }
+
+    #[test]
+    fn truncating_division() {
+        assert_eq!(Ok(Integer::new(-3)), Integer::new(-7).checked_div(Integer::new(2)));
+        assert_eq!(Err(CalculatorError::DivisionByZero), Integer::new(1).checked_div(Integer::zero()));
+    }
+
+    #[test]
+    fn negative_powers() {
+        assert_eq!(Integer::zero(), Integer::new(2).pow(Integer::new(-1)));
+        assert_eq!(Integer::new(-1), Integer::new(-1).pow(Integer::new(-3)));
+    }
+}
@@ -1,45 +1,278 @@
 extern crate core;
 
 use std::env;
-use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::io::BufRead;
+use std::path::PathBuf;
 
-use rustyline::config::Configurer;
 use rustyline::Editor;
 use rustyline::error::ReadlineError;
 
+use crate::bigfloat::BigFloat;
 use crate::calculator::{Calculator, FromStrValue};
 use crate::complex::Complex;
+use crate::currency::Rates;
+use crate::format::{Format, FormatOptions, Formatted};
+use crate::integer::Integer;
 use crate::math::Math;
+use crate::rational::Rational;
+use crate::rounding::Rounding;
+use crate::word::Word;
 
 mod shunting_yard;
 mod tokenizer;
 mod calculator;
 mod math;
 mod complex;
+mod batch;
+mod error;
+mod functions;
+mod value;
+mod vector;
+mod matrix;
+mod format;
+mod rational;
+mod bigfloat;
+mod integer;
+mod conversion;
+mod expression;
+mod derivative;
+mod simplify;
+mod units;
+mod currency;
+mod word;
+mod combinatorics;
+mod quantity;
+mod rounding;
+mod statistics;
+mod temporal;
+mod tolerance;
+mod number_theory;
+mod factors;
 
 const BACKSPACE: char = 8u8 as char;
 
+/// The kind of number calculated with, chosen by a command line flag
+#[derive(Clone, Copy, PartialEq)]
+enum Numbers {
+    Float,
+    /// Exact arithmetic on Gaussian rationals, fractions with rational real and imaginary parts
+    Rational,
+    /// Complex numbers with decimal parts of 100 significant digits
+    BigFloat,
+    I32,
+    /// Integers of arbitrary size
+    BigInteger,
+    /// Integers of a fixed number of bits that wrap around, from `--word u8`, `i16`, ... `u64`
+    Word {
+        bits: u32,
+        signed: bool,
+    },
+}
+
+impl Numbers {
+    fn from_flag(flag: &str) -> Option<Self> {
+        match flag {
+            "--rational" => Some(Numbers::Rational),
+            "--bigfloat" => Some(Numbers::BigFloat),
+            "--i32" => Some(Numbers::I32),
+            "--bigint" => Some(Numbers::BigInteger),
+            _ => None,
+        }
+    }
+
+    fn word(name: &str) -> Option<Self> {
+        let signed = match name.get(..1)? {
+            "i" => true,
+            "u" => false,
+            _ => return None,
+        };
+        match name[1..].parse() {
+            Ok(bits @ (8 | 16 | 32 | 64)) => Some(Numbers::Word { bits, signed }),
+            _ => None,
+        }
+    }
+}
+
+fn set_rounding(calculator: &mut Calculator, name: &str) -> Result<(), String> {
+    calculator.rounding = Rounding::parse(name)
+        .ok_or(format!("Unknown rounding \"{}\", expected half-even, half-up or toward-zero", name))?;
+    Ok(())
+}
+
+/// `snap on` snaps to zero within the absolute tolerance of the session
+fn set_format(calculator: &Calculator, options: &mut FormatOptions, setting: &str) -> Result<(), String> {
+    options.set(setting)?;
+    if setting.split_whitespace().eq(["snap", "on"]) {
+        options.snap = Some(calculator.tolerance.absolute).filter(|threshold| *threshold > 0f64);
+    }
+    Ok(())
+}
+
+/// Changes the tolerance of comparisons, and the snapping threshold along with it when snapping is on
+fn set_tolerance(calculator: &mut Calculator, options: &mut FormatOptions, setting: &str) -> Result<(), String> {
+    calculator.tolerance.set(setting)?;
+    if options.snap.is_some() {
+        options.snap = Some(calculator.tolerance.absolute).filter(|threshold| *threshold > 0f64);
+    }
+    Ok(())
+}
+
+/// `:diff EXPRESSION` as a call to `diff`, with respect to x unless a variable follows a comma
+fn diff_call(arguments: &str) -> String {
+    let arguments = arguments.trim();
+    let variable = arguments.rsplit_once(',')
+        .is_some_and(|(_, variable)| !variable.trim().is_empty() && variable.trim().chars().all(char::is_alphabetic));
+    match variable {
+        true => format!("diff({})", arguments),
+        false => format!("diff({}, x)", arguments),
+    }
+}
+
+/// A file in the `calculator` config directory, e.g. `~/.config/calculator/units.txt`
+fn config_file(name: &str) -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("calculator").join(name))
+}
+
+/// Reads the exchange rates from `path`, set with `--rates FILE`, otherwise from `rates.csv` in
+/// the config directory if there is one
+fn load_rates(calculator: &mut Calculator, path: Option<PathBuf>) {
+    let text = match path {
+        Some(path) => fs::read_to_string(&path).map_err(|error| format!("{}: {}", path.display(), error)),
+        None => match config_file("rates.csv") {
+            Some(path) if path.exists() => fs::read_to_string(&path).map_err(|error| format!("{}: {}", path.display(), error)),
+            _ => return,
+        },
+    };
+    match text.and_then(|text| Rates::parse(&text).map_err(|error| error.to_string())) {
+        Ok(rates) => calculator.rates = Some(rates),
+        Err(error) => println!("Error: {}", error),
+    }
+}
+
+/// Defines the units in the unit file, if there is one, all of them or none if any is invalid
+fn load_units(calculator: &mut Calculator) {
+    let Some(path) = config_file("units.txt") else { return };
+    let Ok(text) = fs::read_to_string(&path) else { return };
+    let result = units::parse_definitions(&text).and_then(|definitions| calculator.define_units(definitions));
+    if let Err(error) = result {
+        println!("Error: {}: {}", path.display(), error);
+    }
+}
+
+/// Runs the terminal when there is no expression, otherwise calculates it
+fn run<T: Math<T> + Format + FromStrValue>(calculator: Calculator, expression: Option<String>, options: FormatOptions) {
+    match expression {
+        Some(expression) => command_line_mode::<T>(&calculator, &expression, &options),
+        None => terminal_mode::<T>(calculator, options),
+    }
+}
+
 fn main() {
-    if env::args().count() == 1 {
-        //terminal_mode::<f64>();
-        terminal_mode::<Complex<f64>>();
-    } else {
-        let string = env::args().skip(1).collect::<Vec<String>>().join(" ");
-        let calculator = Calculator {};
-        print!("{} = ", string);
-        let result = calculator.calculate::<f64>(&string);
-        match result {
-            Ok(value) => {
-                println!("{}", value);
-            }
-            Err(error) => {
-                println!("Error: {}", error);
+    let mut args = env::args().skip(1).peekable();
+    let mut numbers = Numbers::Float;
+    let mut options = FormatOptions::default();
+    let mut calculator = Calculator::default();
+    let mut rates = None;
+    while let Some(flag) = args.next_if(|arg| {
+        Numbers::from_flag(arg).is_some() || ["--format", "--word", "--rounding", "--tolerance", "--rates", "--percentages"].contains(&arg.as_str())
+    }) {
+        if let Some(flag) = Numbers::from_flag(&flag) {
+            numbers = flag;
+            continue;
+        }
+        if flag == "--percentages" {
+            calculator.percentages = true;
+            continue;
+        }
+        let result = match (flag.as_str(), args.next()) {
+            ("--word", Some(name)) => Numbers::word(&name)
+                .map(|word| numbers = word)
+                .ok_or(format!("Unknown word \"{}\", expected i8, u8, i16, u16, i32, u32, i64 or u64", name)),
+            ("--word", None) => Err("--word needs a width, e.g. --word u8".to_string()),
+            ("--rounding", Some(name)) => set_rounding(&mut calculator, &name),
+            ("--rounding", None) => Err("--rounding needs a mode, e.g. --rounding half-even".to_string()),
+            ("--tolerance", Some(setting)) => set_tolerance(&mut calculator, &mut options, &setting),
+            ("--tolerance", None) => Err("--tolerance needs a setting, e.g. --tolerance \"rel 1e-6\"".to_string()),
+            ("--rates", Some(path)) => {
+                rates = Some(PathBuf::from(path));
+                Ok(())
             }
+            ("--rates", None) => Err("--rates needs a file, e.g. --rates rates.csv".to_string()),
+            (_, Some(setting)) => set_format(&calculator, &mut options, &setting),
+            (_, None) => Err("--format needs a setting, e.g. --format \"sig 6\"".to_string()),
+        };
+        if let Err(error) = result {
+            println!("Error: {}", error);
+            return;
         }
     }
+    // Before the units, which may be defined in currencies
+    load_rates(&mut calculator, rates);
+    load_units(&mut calculator);
+    let args: Vec<String> = args.collect();
+    if args.first().is_some_and(|arg| arg == "--csv") {
+        csv_mode(&calculator, &args[1..].join(" "), &options);
+        return;
+    }
+    let expression = (!args.is_empty()).then(|| args.join(" "));
+    match numbers {
+        Numbers::Float if expression.is_some() => run::<f64>(calculator, expression, options),
+        Numbers::Float => run::<Complex<f64>>(calculator, expression, options),
+        Numbers::Rational => run::<Complex<Rational>>(calculator, expression, options),
+        Numbers::BigFloat => run::<Complex<BigFloat>>(calculator, expression, options),
+        Numbers::I32 => run::<i32>(calculator, expression, options),
+        Numbers::BigInteger => run::<Integer>(calculator, expression, options),
+        Numbers::Word { bits: 8, signed: true } => run::<Word<8, true>>(calculator, expression, options),
+        Numbers::Word { bits: 8, signed: false } => run::<Word<8, false>>(calculator, expression, options),
+        Numbers::Word { bits: 16, signed: true } => run::<Word<16, true>>(calculator, expression, options),
+        Numbers::Word { bits: 16, signed: false } => run::<Word<16, false>>(calculator, expression, options),
+        Numbers::Word { bits: 32, signed: true } => run::<Word<32, true>>(calculator, expression, options),
+        Numbers::Word { bits: 32, signed: false } => run::<Word<32, false>>(calculator, expression, options),
+        Numbers::Word { signed: true, .. } => run::<Word<64, true>>(calculator, expression, options),
+        Numbers::Word { signed: false, .. } => run::<Word<64, false>>(calculator, expression, options),
+    }
 }
 
-fn terminal_mode<T: Math<T> + Display + FromStrValue>() {
+fn command_line_mode<T: Math<T> + Format + FromStrValue>(calculator: &Calculator, string: &str, options: &FormatOptions) {
+    print!("{} =", string);
+    let result = calculator.evaluate_conversion::<T>(string, options);
+    match result {
+        Ok((value, options)) => {
+            let pretty = format!("{:#}", Formatted(&value, &options));
+            let separator = if pretty.contains('\n') { '\n' } else { ' ' };
+            println!("{}{}", separator, pretty);
+        }
+        Err(error) => {
+            println!(" Error: {}", error);
+        }
+    }
+}
+
+/// Reads comma separated columns from stdin, the first line naming them, and prints `expression`
+/// evaluated for each row
+fn csv_mode(calculator: &Calculator, expression: &str, options: &FormatOptions) {
+    let columns = match batch::read_columns(io::stdin().lock().lines().map_while(Result::ok)) {
+        Ok(columns) => columns,
+        Err(error) => {
+            println!("Error: {}", error);
+            return;
+        }
+    };
+    let named: Vec<(&str, &[f64])> = columns.iter()
+        .map(|(name, values)| (name.as_str(), values.as_slice()))
+        .collect();
+    match calculator.calculate_columns::<f64>(expression, &named) {
+        Ok(values) => values.iter().for_each(|value| println!("{}", Formatted(value, options))),
+        Err(error) => println!("Error: {}", error),
+    }
+}
+
+fn terminal_mode<T: Math<T> + Format + FromStrValue>(mut calculator: Calculator, mut options: FormatOptions) {
     // `()` can be used when no completer is required
     let mut editor = Editor::<()>::new();
     if editor.load_history("history.txt").is_err() {
@@ -69,6 +302,37 @@ fn terminal_mode<T: Math<T> + Display + FromStrValue>() {
                     println!("    up    - Previous entries");
                     println!("    clear - Clear expression history");
                     println!("    help  - this message");
+                    println!("    :format polar [degrees|radians] - Show complex numbers as magnitude∠angle");
+                    println!("    :format rectangular             - Show complex numbers as a + bi");
+                    println!("    :format i|j                     - Letter for the imaginary unit");
+                    println!("    :format fixed N | sig N | shortest - Decimal places or significant figures");
+                    println!("    :format standard | sci | eng    - Notation");
+                    println!("    :format group on|off            - Thousands separators");
+                    println!("    :format snap T | snap on | snap off - Show numbers smaller than T, or the tolerance, as 0");
+                    println!("    :format default                 - Reset formatting");
+                    println!("    :base bin|oct|dec|hex           - Base for integer results");
+                    println!("    EXPRESSION to hex               - Convert one result, also bin, oct, dec");
+                    println!("    3 m * 2 s^-1, 10 N / (2 kg)     - Units, with SI prefixes e.g. km, mg, MPa");
+                    println!("    EXPRESSION to km/h              - Convert to other units of the same dimension");
+                    println!("    2026-10-17 + 90 days, (2026-12-25 - today) in weeks, 3h 25m * 4");
+                    println!("    2026-10-17T14:30+02:00 to UTC   - Dates and times, at fixed offsets from UTC");
+                    println!("    100 USD to EUR                  - Currencies, from --rates FILE or ~/.config/calculator/rates.csv");
+                    println!("    unit rack = 42 U                - Define a unit, also read from ~/.config/calculator/units.txt");
+                    println!("    :percentages on|off             - Percentage mode, where 100 + 15% = 115");
+                    println!("    percentof(p, x), percentchange(from, to), aspercent(part, whole)");
+                    println!("    n!, nCr(n, r), nPr(n, r), gamma(x), lgamma(x) - Exact for integer modes");
+                    println!("    sum, mean, median, mode, min, max, range of numbers or vectors, e.g. mean(1, 2, [3, 4])");
+                    println!("    var, stdev (sample), varp, stdevp (population), percentile(p, ...), percentile([...], p)");
+                    println!("    gcd(a, b), lcm(a, b), isprime(n), factor(n), modpow(b, e, m), modinv(a, m)");
+                    println!("    round(x), round(x, digits), floor, ceil, trunc, frac, sign");
+                    println!("    :rounding half-even|half-up|toward-zero - How round treats halves");
+                    println!("    == != < <= > >=, and, or, not, if(condition, a, b) - Comparisons and logic");
+                    println!("    a ≈ b, approx(a, b), approx(a, b, tol) - Equal within the tolerance, or within tol");
+                    println!("    diff(expr, x), diff(expr, x, a) - Derivative, and its value at x = a, also :diff expr[, x]");
+                    println!("    simplify(expr) - Folds constants, combines like terms and cancels common factors");
+                    println!("    :tolerance rel R | abs A | exact | default - Tolerance of comparisons, default rel 1e-9 abs 1e-12");
+                    println!("    a mod b, a // b                 - Remainder and division, Euclidean for negatives");
+                    println!("    & | xor ~ << >> rotl(x, n) rotr(x, n) - Bitwise, with --i32, --bigint or --word u8");
                     println!("    enter - Exit terminal mode");
                     continue;
                 }
@@ -77,17 +341,64 @@ fn terminal_mode<T: Math<T> + Display + FromStrValue>() {
                     println!("History cleared");
                     continue;
                 }
+                if let Some(setting) = line.strip_prefix(":format") {
+                    if let Err(error) = set_format(&calculator, &mut options, setting) {
+                        println!("Error: {}", error);
+                    }
+                    continue;
+                }
+                if let Some(setting) = line.strip_prefix(":tolerance") {
+                    if let Err(error) = set_tolerance(&mut calculator, &mut options, setting) {
+                        println!("Error: {}", error);
+                    }
+                    continue;
+                }
+                if let Some(setting) = line.strip_prefix(":percentages") {
+                    match setting.trim() {
+                        "on" => calculator.percentages = true,
+                        "off" => calculator.percentages = false,
+                        setting => println!("Error: Unknown setting \"{}\", expected on or off", setting),
+                    }
+                    continue;
+                }
+                if let Some(name) = line.strip_prefix(":rounding") {
+                    if let Err(error) = set_rounding(&mut calculator, name.trim()) {
+                        println!("Error: {}", error);
+                    }
+                    continue;
+                }
+                if line.starts_with("unit ") {
+                    let result = units::parse_definition(&line).and_then(|definition| calculator.define_units(vec![definition]));
+                    if let Err(error) = result {
+                        println!("Error: {}", error);
+                    }
+                    continue;
+                }
+                if let Some(base) = line.strip_prefix(":base") {
+                    if let Err(error) = options.set(&format!("base {}", base)) {
+                        println!("Error: {}", error);
+                    }
+                    continue;
+                }
+                let line = match line.strip_prefix(":diff") {
+                    Some(arguments) => diff_call(arguments),
+                    None => line,
+                };
                 editor.add_history_entry(line.as_str());
-                let calculator = Calculator {};
-                let result = calculator.calculate::<T>(&line);
+                let result = calculator.evaluate_conversion::<T>(&line, &options);
                 match result {
-                    Ok(value) => {
+                    Ok((value, options)) => {
                         if Some(&line) == last_value.as_ref() {
                             println!("{}\r{}", BACKSPACE, BACKSPACE);
-                        }else {
-                            println!("{}\r{}{} = {}", BACKSPACE, prompt, line, value);
+                        } else {
+                            let pretty = format!("{:#}", Formatted(&value, &options));
+                            if pretty.contains('\n') {
+                                println!("{}\r{}{} =\n{}", BACKSPACE, prompt, line, pretty);
+                            } else {
+                                println!("{}\r{}{} = {}", BACKSPACE, prompt, line, pretty);
+                            }
                         }
-                        let new_last_value = Some(format!("{}", value));
+                        let new_last_value = Some(Formatted(&value, &options).to_string());
                         last_value = if new_last_value == last_value {
                             None
                         } else {
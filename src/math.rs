@@ -1,6 +1,22 @@
-use std::ops::{Add, Div, Mul, Sub};
+use std::cmp::Ordering;
+use std::f64::consts::PI;
+use std::ops::{Add, Div, Mul, Neg, Sub};
 
-pub trait CommonMath<T>: Sized + Add<Output=T> + Sub<Output=T> + Mul<Output=T> + Div<Output=T> {}
+use num::{BigInt, FromPrimitive, ToPrimitive};
+
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::{self, Rounding};
+
+pub trait CommonMath<T>: Sized + Clone + Add<Output=T> + Sub<Output=T> + Mul<Output=T> + Div<Output=T> {}
+
+pub trait Zero {
+    fn zero() -> Self;
+}
+
+pub trait One {
+    fn one() -> Self;
+}
 
 pub trait Inv {
     fn inv(self) -> Self;
@@ -10,6 +26,60 @@ pub trait Pow {
     fn pow(self, rhs: Self) -> Self;
 }
 
+/// `+`, `-`, `*` and `^` that report results too large for fixed width integers, which would
+/// otherwise panic or wrap. Other types keep the defaults, where floating point numbers overflow
+/// to infinity and words wrap as in C.
+pub trait Checked: Sized {
+    fn checked_plus(self, rhs: Self) -> Result<Self, CalculatorError> where Self: Add<Output=Self> {
+        Ok(self + rhs)
+    }
+
+    fn checked_minus(self, rhs: Self) -> Result<Self, CalculatorError> where Self: Sub<Output=Self> {
+        Ok(self - rhs)
+    }
+
+    fn checked_times(self, rhs: Self) -> Result<Self, CalculatorError> where Self: Mul<Output=Self> {
+        Ok(self * rhs)
+    }
+
+    fn checked_power(self, rhs: Self) -> Result<Self, CalculatorError> where Self: Pow {
+        Ok(self.pow(rhs))
+    }
+}
+
+/// The gamma function, Γ(n) = (n - 1)!, exact for integers of the integer types
+pub trait Gamma: Sized {
+    fn gamma(self) -> Result<Self, CalculatorError>;
+    /// ln |Γ(x)| for real numbers, which stays finite long after Γ(x) overflows
+    fn ln_gamma(self) -> Result<Self, CalculatorError>;
+}
+
+/// Conversion to and from integers of any size, for number theory
+pub trait BigInteger: Sized {
+    /// The value if it is an integer
+    fn to_big_integer(&self) -> Option<BigInt>;
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError>;
+}
+
+/// Rounding to decimal places, component-wise for complex numbers
+pub trait Round: Sized {
+    /// Rounds to `digits` decimal places, or to tens, hundreds and so on for negative `digits`
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError>;
+    /// -1, 0 or 1
+    fn sign(self) -> Self;
+}
+
+/// The order of real numbers
+pub trait Compare {
+    /// None for NaN and for complex numbers with imaginary parts
+    fn compare(&self, other: &Self) -> Option<Ordering>;
+}
+
+/// Division that reports division by zero for types that cannot represent the result
+pub trait CheckedDiv: Sized {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError>;
+}
+
 pub trait Percent {
     fn percent(self) -> Self;
 }
@@ -19,21 +89,192 @@ pub trait Root {
     fn root(self, rhs: Self) -> Self;
 }
 
+/// Functions of a single value, these apply element-wise to vectors
+pub trait Elementary: Sized {
+    fn sqrt(self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    /// Fails for the most negative fixed width integer, whose absolute value is too large for it
+    fn abs(self) -> Result<Self, CalculatorError>;
+}
+
+/// Parts of a complex number, real numbers having no imaginary part
+pub trait Polar: Sized {
+    fn re(self) -> Self;
+    fn im(self) -> Self;
+    /// The angle from the positive real axis in radians
+    fn arg(self) -> Self;
+    fn conj(self) -> Self;
+    /// The point on the unit circle at angle `self`, `cos(self) + i sin(self)`
+    fn cis(self) -> Result<Self, CalculatorError>;
+    /// Converts an angle in degrees to radians
+    fn degrees(self) -> Self;
+}
+
+/// Division with a remainder that is never negative, so that `a = b × (a // b) + a mod b` for
+/// negative numbers too, e.g. `-7 mod 3` is 2 and `-7 // 3` is -3
+pub trait Modulo: Sized {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError>;
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError>;
+}
+
+/// Operations on the bits of integers, in two's complement for negative numbers. Other number
+/// types keep the defaults, which report that an integer is needed.
+pub trait Bitwise: Sized {
+    fn bit_and(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("&".to_string()))
+    }
+
+    fn bit_or(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("|".to_string()))
+    }
+
+    fn bit_xor(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("xor".to_string()))
+    }
+
+    fn bit_not(self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("~".to_string()))
+    }
+
+    /// Shifts that move every bit out give zero, or minus one for right shifts of negative numbers
+    fn shift_left(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("<<".to_string()))
+    }
+
+    fn shift_right(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly(">>".to_string()))
+    }
+
+    /// Rotates within the width of the integer type, negative amounts rotating the other way
+    fn rotate_left(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("rotl".to_string()))
+    }
+
+    fn rotate_right(self, _rhs: Self) -> Result<Self, CalculatorError> {
+        Err(CalculatorError::IntegerOnly("rotr".to_string()))
+    }
+}
+
+/// The amount of a shift, which cannot be negative
+pub(crate) fn shift_amount(amount: i64) -> Result<u32, CalculatorError> {
+    u32::try_from(amount).map_err(|_| CalculatorError::UnsupportedOperation("shift by a negative amount".to_string()))
+}
+
+/// A real number type that complex numbers can be built from.
+///
+/// Functions that have no exact form for the type, such as `exp`, are calculated through `f64`.
+pub trait Real: Math<Self> + PartialOrd + Neg<Output=Self> {
+    fn to_f64(&self) -> f64;
+    /// The nearest value to `value`, types that cannot represent infinities and NaN give zero
+    fn from_f64(value: f64) -> Self;
+}
+
+/// The size of a value as a real number, used to choose pivots and to spot singular matrices
+pub trait Magnitude {
+    /// Whether values are exact, so that `==` and ordering need no tolerance
+    const EXACT: bool = true;
+    /// The relative rounding error of arithmetic, zero for exact types
+    const EPSILON: f64 = 0f64;
+    /// Whether values are only integers, so that `/` truncates and elimination cannot divide
+    const INTEGER: bool = false;
+    /// Whether integer arithmetic wraps around instead of overflowing, so that no division is exact
+    const WRAPPING: bool = false;
+    /// The largest magnitude of a finite value, larger results overflow to infinity
+    const MAX_MAGNITUDE: f64 = f64::INFINITY;
+
+    fn magnitude(&self) -> f64;
+}
+
+/// The value as an integer, if it is one, for counts and integer powers
+pub trait ToInteger {
+    fn to_integer(&self) -> Option<i64>;
+}
+
+impl Zero for f64 {
+    fn zero() -> Self {
+        0f64
+    }
+}
+
+impl One for f64 {
+    fn one() -> Self {
+        1f64
+    }
+}
+
+impl Zero for i32 {
+    fn zero() -> Self {
+        0i32
+    }
+}
+
+impl One for i32 {
+    fn one() -> Self {
+        1i32
+    }
+}
+
+impl Magnitude for f64 {
+    const EXACT: bool = false;
+    const EPSILON: f64 = f64::EPSILON;
+    const MAX_MAGNITUDE: f64 = f64::MAX;
+
+    fn magnitude(&self) -> f64 {
+        f64::abs(*self)
+    }
+}
+
+impl Magnitude for i32 {
+    const INTEGER: bool = true;
+
+    fn magnitude(&self) -> f64 {
+        f64::from(*self).abs()
+    }
+}
+
+impl ToInteger for f64 {
+    fn to_integer(&self) -> Option<i64> {
+        if self.fract() == 0f64 && self.abs() <= i64::MAX as f64 {
+            Some(*self as i64)
+        } else {
+            None
+        }
+    }
+}
+
+impl ToInteger for i32 {
+    fn to_integer(&self) -> Option<i64> {
+        Some(i64::from(*self))
+    }
+}
+
 impl Pow for f64 {
     fn pow(self, rhs: Self) -> Self {
         self.powf(rhs)
     }
 }
 
+/// Negative powers truncate to zero, except of one and minus one, and overflows wrap
 impl Pow for i32 {
     fn pow(self, rhs: Self) -> Self {
-        self.pow(rhs.try_into::<>().expect(""))
+        match u32::try_from(rhs) {
+            Ok(exponent) => self.wrapping_pow(exponent),
+            Err(_) => match self {
+                -1 if rhs % 2 != 0 => -1,
+                1 | -1 => 1,
+                _ => 0,
+            },
+        }
     }
 }
 
 impl Inv for i32 {
     fn inv(self) -> Self {
-        panic!()
+        1 / self
     }
 }
 
@@ -43,6 +284,22 @@ impl Inv for f64 {
     }
 }
 
+impl CheckedDiv for i32 {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        match rhs {
+            0 => Err(CalculatorError::DivisionByZero),
+            _ => i32::checked_div(self, rhs).ok_or(CalculatorError::Overflow),
+        }
+    }
+}
+
+/// Follows IEEE 754, dividing by zero gives an infinity or NaN
+impl CheckedDiv for f64 {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self / rhs)
+    }
+}
+
 impl Percent for i32 {
     fn percent(self) -> Self {
         self / 100i32
@@ -55,14 +312,496 @@ impl Percent for f64 {
     }
 }
 
+impl Elementary for f64 {
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+
+    fn exp(self) -> Self {
+        f64::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        f64::ln(self)
+    }
+
+    fn sin(self) -> Self {
+        f64::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        f64::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        f64::tan(self)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(f64::abs(self))
+    }
+}
+
+/// Integer results are truncated, as for integer division
+impl Elementary for i32 {
+    fn sqrt(self) -> Self {
+        f64::from(self).sqrt() as i32
+    }
+
+    fn exp(self) -> Self {
+        f64::from(self).exp() as i32
+    }
+
+    fn ln(self) -> Self {
+        f64::from(self).ln() as i32
+    }
+
+    fn sin(self) -> Self {
+        f64::from(self).sin() as i32
+    }
+
+    fn cos(self) -> Self {
+        f64::from(self).cos() as i32
+    }
+
+    fn tan(self) -> Self {
+        f64::from(self).tan() as i32
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        i32::checked_abs(self).ok_or(CalculatorError::Overflow)
+    }
+}
+
+impl Polar for f64 {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        0f64
+    }
+
+    fn arg(self) -> Self {
+        0f64.atan2(self)
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    /// Only whole half turns have real results
+    fn cis(self) -> Result<Self, CalculatorError> {
+        let half_turns = self / PI;
+        if (half_turns - half_turns.round()).abs() > 1e-12 {
+            return Err(CalculatorError::ComplexResult);
+        }
+        Ok(if half_turns.round() % 2f64 == 0f64 { 1f64 } else { -1f64 })
+    }
+
+    fn degrees(self) -> Self {
+        self.to_radians()
+    }
+}
+
+impl Polar for i32 {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        0i32
+    }
+
+    fn arg(self) -> Self {
+        f64::from(self).arg() as i32
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        if self != 0 {
+            return Err(CalculatorError::ComplexResult);
+        }
+        Ok(1i32)
+    }
+
+    fn degrees(self) -> Self {
+        f64::from(self).to_radians() as i32
+    }
+}
+
+impl Zero for f32 {
+    fn zero() -> Self {
+        0f32
+    }
+}
+
+impl One for f32 {
+    fn one() -> Self {
+        1f32
+    }
+}
+
+impl Magnitude for f32 {
+    const EXACT: bool = false;
+    const EPSILON: f64 = f32::EPSILON as f64;
+    const MAX_MAGNITUDE: f64 = f32::MAX as f64;
+
+    fn magnitude(&self) -> f64 {
+        f64::from(self.abs())
+    }
+}
+
+impl ToInteger for f32 {
+    fn to_integer(&self) -> Option<i64> {
+        f64::from(*self).to_integer()
+    }
+}
+
+impl Pow for f32 {
+    fn pow(self, rhs: Self) -> Self {
+        self.powf(rhs)
+    }
+}
+
+impl Inv for f32 {
+    fn inv(self) -> Self {
+        1f32 / self
+    }
+}
+
+impl CheckedDiv for f32 {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self / rhs)
+    }
+}
+
+impl Percent for f32 {
+    fn percent(self) -> Self {
+        self / 100f32
+    }
+}
+
+impl Elementary for f32 {
+    fn sqrt(self) -> Self {
+        f32::sqrt(self)
+    }
+
+    fn exp(self) -> Self {
+        f32::exp(self)
+    }
+
+    fn ln(self) -> Self {
+        f32::ln(self)
+    }
+
+    fn sin(self) -> Self {
+        f32::sin(self)
+    }
+
+    fn cos(self) -> Self {
+        f32::cos(self)
+    }
+
+    fn tan(self) -> Self {
+        f32::tan(self)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(f32::abs(self))
+    }
+}
+
+impl Polar for f32 {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        0f32
+    }
+
+    fn arg(self) -> Self {
+        0f32.atan2(self)
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        Ok(f64::from(self).cis()? as f32)
+    }
+
+    fn degrees(self) -> Self {
+        self.to_radians()
+    }
+}
+
+impl Checked for f64 {}
+
+impl Checked for f32 {}
+
+impl Checked for i32 {
+    fn checked_plus(self, rhs: Self) -> Result<Self, CalculatorError> {
+        i32::checked_add(self, rhs).ok_or(CalculatorError::Overflow)
+    }
+
+    fn checked_minus(self, rhs: Self) -> Result<Self, CalculatorError> {
+        i32::checked_sub(self, rhs).ok_or(CalculatorError::Overflow)
+    }
+
+    fn checked_times(self, rhs: Self) -> Result<Self, CalculatorError> {
+        i32::checked_mul(self, rhs).ok_or(CalculatorError::Overflow)
+    }
+
+    fn checked_power(self, rhs: Self) -> Result<Self, CalculatorError> {
+        match u32::try_from(rhs) {
+            Ok(exponent) => i32::checked_pow(self, exponent).ok_or(CalculatorError::Overflow),
+            Err(_) => Ok(Pow::pow(self, rhs)),
+        }
+    }
+}
+
+impl Gamma for f64 {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        if combinatorics::is_pole(self) {
+            return Err(CalculatorError::UnsupportedOperation("gamma of a non-positive integer".to_string()));
+        }
+        let value = combinatorics::gamma(self.into()).re;
+        if !value.is_finite() && self.is_finite() {
+            return Err(CalculatorError::Overflow);
+        }
+        // Γ of a positive integer is a factorial, which should not show rounding errors
+        Ok(if self.fract() == 0f64 { value.round() } else { value })
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        if combinatorics::is_pole(self) {
+            return Err(CalculatorError::UnsupportedOperation("lgamma of a non-positive integer".to_string()));
+        }
+        Ok(combinatorics::ln_gamma(self.into()).re)
+    }
+}
+
+impl Gamma for f32 {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        let value = Gamma::gamma(f64::from(self))? as f32;
+        if value.is_infinite() {
+            return Err(CalculatorError::Overflow);
+        }
+        Ok(value)
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        Ok(Gamma::ln_gamma(f64::from(self))? as f32)
+    }
+}
+
+/// Integer results are truncated, as for integer division
+impl Gamma for i32 {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        combinatorics::factorial(self - 1)
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        Ok(Gamma::ln_gamma(f64::from(self))? as i32)
+    }
+}
+
+/// Integers beyond 2^53 are rounded
+impl BigInteger for f64 {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        if self.fract() != 0f64 {
+            return None;
+        }
+        BigInt::from_f64(*self)
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        value.to_f64().filter(|value| value.is_finite()).ok_or(CalculatorError::Overflow)
+    }
+}
+
+impl BigInteger for f32 {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        f64::from(*self).to_big_integer()
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        value.to_f32().filter(|value| value.is_finite()).ok_or(CalculatorError::Overflow)
+    }
+}
+
+impl BigInteger for i32 {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        Some(BigInt::from(*self))
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        value.to_i32().ok_or(CalculatorError::Overflow)
+    }
+}
+
+impl Compare for f64 {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        self.partial_cmp(other)
+    }
+}
+
+impl Compare for f32 {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        self.partial_cmp(other)
+    }
+}
+
+impl Compare for i32 {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Round for f64 {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(rounding::round_f64(self, digits, rounding))
+    }
+
+    fn sign(self) -> Self {
+        if self == 0f64 { 0f64 } else { self.signum() }
+    }
+}
+
+impl Round for f32 {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(rounding::round_f64(f64::from(self), digits, rounding) as f32)
+    }
+
+    fn sign(self) -> Self {
+        if self == 0f32 { 0f32 } else { self.signum() }
+    }
+}
+
+impl Round for i32 {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Self::from_big_integer(rounding::round_integer(BigInt::from(self), digits, rounding))
+    }
+
+    fn sign(self) -> Self {
+        self.signum()
+    }
+}
+
+impl Modulo for f64 {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs == 0f64 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self.rem_euclid(rhs))
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs == 0f64 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self.div_euclid(rhs))
+    }
+}
+
+impl Modulo for f32 {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(f64::from(self).modulo(f64::from(rhs))? as f32)
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(f64::from(self).quotient(f64::from(rhs))? as f32)
+    }
+}
+
+impl Modulo for i32 {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        match rhs {
+            0 => Err(CalculatorError::DivisionByZero),
+            _ => self.checked_rem_euclid(rhs).ok_or(CalculatorError::Overflow),
+        }
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        match rhs {
+            0 => Err(CalculatorError::DivisionByZero),
+            _ => self.checked_div_euclid(rhs).ok_or(CalculatorError::Overflow),
+        }
+    }
+}
+
+impl Bitwise for f64 {}
+
+impl Bitwise for f32 {}
+
+impl Bitwise for i32 {
+    fn bit_and(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self & rhs)
+    }
+
+    fn bit_or(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self | rhs)
+    }
+
+    fn bit_xor(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self ^ rhs)
+    }
+
+    fn bit_not(self) -> Result<Self, CalculatorError> {
+        Ok(!self)
+    }
+
+    fn shift_left(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self.checked_shl(shift_amount(rhs.into())?).unwrap_or(0))
+    }
+
+    fn shift_right(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(self >> shift_amount(rhs.into())?.min(i32::BITS - 1))
+    }
+
+    fn rotate_left(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(i32::rotate_left(self, rhs.rem_euclid(i32::BITS as i32) as u32))
+    }
+
+    fn rotate_right(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(i32::rotate_right(self, rhs.rem_euclid(i32::BITS as i32) as u32))
+    }
+}
+
+impl Real for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl Real for f32 {
+    fn to_f64(&self) -> f64 {
+        f64::from(*self)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
 impl<T: Pow + Inv> Root for T {
     fn root(self, rhs: Self) -> Self {
         rhs.pow(self.inv())
     }
 }
 
-pub trait Math<T>: CommonMath<T> + Pow + Root + Percent {}
+pub trait Math<T>: CommonMath<T> + Pow + Inv + Root + CheckedDiv + Percent + Elementary + Polar + Modulo + Bitwise + Checked + Gamma + BigInteger + Round + Compare + Zero + One + Magnitude + ToInteger {}
 
-impl<T: Sized + Add<Output=T> + Sub<Output=T> + Mul<Output=T> + Div<Output=T> + Pow + Root> CommonMath<T> for T {}
+impl<T: Sized + Clone + Add<Output=T> + Sub<Output=T> + Mul<Output=T> + Div<Output=T> + Pow + Root> CommonMath<T> for T {}
 
-impl<T: CommonMath<T> + Pow + Root + Percent> Math<T> for T {}
\ No newline at end of file
+impl<T: CommonMath<T> + Pow + Inv + Root + CheckedDiv + Percent + Elementary + Polar + Modulo + Bitwise + Checked + Gamma + BigInteger + Round + Compare + Zero + One + Magnitude + ToInteger> Math<T> for T {}
\ No newline at end of file
@@ -0,0 +1,680 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::error::CalculatorError;
+use crate::format::{Format, FormatOptions, Formatted};
+use crate::integer::Integer;
+use crate::math::{BigInteger, Math, Percent};
+use crate::vector::Vector;
+
+/// Condition numbers above this leave fewer than about four trustworthy significant digits in a solution
+const ILL_CONDITIONED: f64 = 1e12;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matrix<T> {
+    rows: Vec<Vec<T>>,
+}
+
+/// The LU decomposition of a square matrix with partial pivoting, `PA = LU`.
+///
+/// `L` has a unit diagonal so is stored below the diagonal of `U`.
+struct Decomposition<T> {
+    lu: Vec<Vec<T>>,
+    /// The row of the original matrix that was moved to each row
+    permutation: Vec<usize>,
+    swaps: usize,
+    /// Pivots no larger than this are zero but for rounding errors, relative to the size of the matrix
+    tolerance: f64,
+}
+
+impl<T> Matrix<T> {
+    /// Builds a matrix from rows that all have the same number of columns
+    pub fn new(rows: Vec<Vec<T>>) -> Result<Self, CalculatorError> {
+        let columns = rows.first().map_or(0, Vec::len);
+        if columns == 0 {
+            return Err(CalculatorError::EmptyVector);
+        }
+        if let Some(row) = rows.iter().find(|row| row.len() != columns) {
+            return Err(CalculatorError::DimensionMismatch { expected: columns, found: row.len() });
+        }
+        Ok(Self { rows })
+    }
+
+    /// (rows, columns)
+    pub fn shape(&self) -> (usize, usize) {
+        (self.rows.len(), self.rows[0].len())
+    }
+
+    fn check_shape(&self, rhs: &Self) -> Result<(), CalculatorError> {
+        if self.shape() != rhs.shape() {
+            return Err(CalculatorError::ShapeMismatch { expected: self.shape(), found: rhs.shape() });
+        }
+        Ok(())
+    }
+
+    /// The size of a square matrix
+    fn check_square(&self) -> Result<usize, CalculatorError> {
+        match self.shape() {
+            (rows, columns) if rows == columns => Ok(rows),
+            (rows, columns) => Err(CalculatorError::NotSquare(rows, columns)),
+        }
+    }
+
+    pub fn transpose(self) -> Self {
+        let (_, columns) = self.shape();
+        let mut transposed: Vec<Vec<T>> = (0..columns).map(|_| vec![]).collect();
+        for row in self.rows {
+            for (column, element) in transposed.iter_mut().zip(row) {
+                column.push(element);
+            }
+        }
+        Self { rows: transposed }
+    }
+}
+
+impl<T: Math<T>> Matrix<T> {
+    pub fn identity(size: usize) -> Result<Self, CalculatorError> {
+        Self::new((0..size)
+            .map(|row| (0..size).map(|column| if row == column { T::one() } else { T::zero() }).collect())
+            .collect())
+    }
+
+    /// Applies `operation` to each element
+    pub fn map(self, operation: impl Fn(T) -> T) -> Self {
+        Self {
+            rows: self.rows.into_iter().map(|row| row.into_iter().map(&operation).collect()).collect()
+        }
+    }
+
+    /// Applies `operation` to each element, stopping at the first error
+    pub fn try_map(self, operation: impl Fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        Ok(Self {
+            rows: self.rows.into_iter()
+                .map(|row| row.into_iter().map(&operation).collect())
+                .collect::<Result<Vec<Vec<T>>, CalculatorError>>()?
+        })
+    }
+
+    /// Combines corresponding elements of two matrices of the same shape, stopping at the first error
+    fn zip(self, rhs: Self, operation: impl Fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        self.check_shape(&rhs)?;
+        Ok(Self {
+            rows: self.rows.into_iter()
+                .zip(rhs.rows)
+                .map(|(left, right)| left.into_iter().zip(right).map(|(l, r)| operation(l, r)).collect())
+                .collect::<Result<Vec<Vec<T>>, CalculatorError>>()?
+        })
+    }
+
+    fn into_row_vectors(self) -> Vec<Vector<T>> {
+        self.rows.into_iter().map(|row| Vector::new(row).expect("Rows are never empty")).collect()
+    }
+
+    /// Treats `rhs` as a column vector
+    pub fn multiply_vector(self, rhs: Vector<T>) -> Result<Vector<T>, CalculatorError> {
+        let (_, columns) = self.shape();
+        if columns != rhs.dimension() {
+            return Err(CalculatorError::DimensionMismatch { expected: columns, found: rhs.dimension() });
+        }
+        Vector::new(self.into_row_vectors()
+            .into_iter()
+            .map(|row| row.dot(rhs.clone()))
+            .collect::<Result<Vec<T>, CalculatorError>>()?)
+    }
+
+    pub fn determinant(self) -> Result<T, CalculatorError> {
+        if T::WRAPPING {
+            return self.wrapped_determinant();
+        }
+        if T::INTEGER {
+            return self.bareiss();
+        }
+        let decomposition = self.decompose()?;
+        if decomposition.is_singular() {
+            return Ok(T::zero());
+        }
+        let determinant = decomposition.lu.into_iter()
+            .enumerate()
+            .map(|(index, mut row)| row.swap_remove(index))
+            .reduce(|l, r| l * r)
+            .expect("Matrices are never empty");
+        if decomposition.swaps % 2 == 0 {
+            Ok(determinant)
+        } else {
+            Ok(T::zero() - determinant)
+        }
+    }
+
+    /// The inverse, which needs fractions, so is an error for the integer types
+    pub fn inverse(self) -> Result<Self, CalculatorError> {
+        if T::INTEGER {
+            return Err(CalculatorError::FractionsOnly("inv".to_string()));
+        }
+        let decomposition = self.decompose()?;
+        if decomposition.is_singular() {
+            return Err(CalculatorError::SingularMatrix);
+        }
+        decomposition.inverse()
+    }
+
+    /// Solves `Ax = b` for `x`, where `b` is a vector or a matrix of right hand side columns.
+    ///
+    /// Systems whose solution would be dominated by rounding errors are rejected rather than solved,
+    /// as are systems of the integer types, whose elimination would truncate.
+    pub fn solve(self, b: Self) -> Result<Self, CalculatorError> {
+        if T::INTEGER {
+            return Err(CalculatorError::FractionsOnly("solve".to_string()));
+        }
+        let (size, _) = self.shape();
+        let (rows, _) = b.shape();
+        if rows != size {
+            return Err(CalculatorError::DimensionMismatch { expected: size, found: rows });
+        }
+        let norm = self.norm();
+        let decomposition = self.decompose()?;
+        if decomposition.is_singular() {
+            return Err(CalculatorError::SingularMatrix);
+        }
+        if !T::EXACT {
+            let condition = norm * decomposition.inverse()?.norm();
+            if condition > ILL_CONDITIONED {
+                return Err(CalculatorError::IllConditioned(condition));
+            }
+        }
+        let columns = b.transpose().rows
+            .into_iter()
+            .map(|column| decomposition.solve(column))
+            .collect();
+        Ok(Self { rows: columns }.transpose())
+    }
+
+    /// Treats `b` as a column vector, see [Matrix::solve]
+    pub fn solve_vector(self, b: Vector<T>) -> Result<Vector<T>, CalculatorError> {
+        let column = Self::new(b.into_elements().into_iter().map(|element| vec![element]).collect())?;
+        let mut solution = self.solve(column)?.transpose();
+        Vector::new(solution.rows.remove(0))
+    }
+
+    /// The 1-norm, the largest sum of the magnitudes in a column
+    fn norm(&self) -> f64 {
+        let (_, columns) = self.shape();
+        (0..columns)
+            .map(|column| self.rows.iter().map(|row| row[column].magnitude()).sum::<f64>())
+            .fold(0f64, f64::max)
+    }
+
+    /// Whether no element is infinite or NaN
+    fn is_finite(&self) -> bool {
+        self.rows.iter().flatten().all(|element| element.magnitude().is_finite())
+    }
+
+    /// Raises to an integer power, negative powers are powers of the inverse
+    pub fn power(self, exponent: i64) -> Result<Self, CalculatorError> {
+        let size = self.check_square()?;
+        let finite = self.is_finite();
+        let mut base = if exponent < 0 { self.inverse()? } else { self };
+        let mut exponent = exponent.unsigned_abs();
+        let mut result = Self::identity(size)?;
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = (result * base.clone())?;
+            }
+            base = (base.clone() * base)?;
+            exponent /= 2;
+        }
+        if finite && !result.is_finite() {
+            return Err(CalculatorError::Overflow);
+        }
+        Ok(result)
+    }
+
+    /// The determinant by fraction-free Bareiss elimination, for integer types. Every division is
+    /// exact, by the pivot of the previous step, so no fractions are truncated.
+    fn bareiss(self) -> Result<T, CalculatorError> {
+        let size = self.check_square()?;
+        let mut rows = self.rows;
+        let mut previous = T::one();
+        let mut negative = false;
+        for k in 0..size - 1 {
+            if rows[k][k].magnitude() == 0f64 {
+                match (k + 1..size).find(|&row| rows[row][k].magnitude() != 0f64) {
+                    Some(row) => {
+                        rows.swap(row, k);
+                        negative = !negative;
+                    }
+                    None => return Ok(T::zero()),
+                }
+            }
+            let (pivot_rows, rows_below) = rows.split_at_mut(k + 1);
+            let pivot_row = &pivot_rows[k];
+            for row in rows_below {
+                for j in k + 1..size {
+                    let product = row[j].clone().checked_times(pivot_row[k].clone())?;
+                    let other = row[k].clone().checked_times(pivot_row[j].clone())?;
+                    row[j] = product.checked_minus(other)?.checked_div(previous.clone())?;
+                }
+            }
+            previous = pivot_row[k].clone();
+        }
+        let determinant = rows.swap_remove(size - 1).swap_remove(size - 1);
+        Ok(if negative { T::zero() - determinant } else { determinant })
+    }
+
+    /// The determinant of words, found exactly with big integers and then wrapped as their arithmetic wraps
+    fn wrapped_determinant(self) -> Result<T, CalculatorError> {
+        let integers = Matrix {
+            rows: self.rows.iter()
+                .map(|row| row.iter()
+                    .map(|element| Integer::from_big_integer(element.to_big_integer().ok_or(CalculatorError::Overflow)?))
+                    .collect())
+                .collect::<Result<Vec<Vec<Integer>>, CalculatorError>>()?
+        };
+        let determinant = integers.bareiss()?;
+        T::from_big_integer(determinant.as_big_int().clone())
+    }
+
+    fn decompose(self) -> Result<Decomposition<T>, CalculatorError> {
+        let size = self.check_square()?;
+        let tolerance = size as f64 * T::EPSILON * self.norm();
+        let mut lu = self.rows;
+        let mut permutation: Vec<usize> = (0..size).collect();
+        let mut swaps = 0;
+        for k in 0..size {
+            let pivot = (k..size)
+                .max_by(|&a, &b| lu[a][k].magnitude().total_cmp(&lu[b][k].magnitude()))
+                .expect("Pivot range is never empty");
+            if pivot != k {
+                lu.swap(pivot, k);
+                permutation.swap(pivot, k);
+                swaps += 1;
+            }
+            if lu[k][k].magnitude() == 0f64 {
+                continue;
+            }
+            let (pivot_rows, rows_below) = lu.split_at_mut(k + 1);
+            let pivot_row = &pivot_rows[k];
+            for row in rows_below {
+                let factor = row[k].clone() / pivot_row[k].clone();
+                for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(k + 1) {
+                    *value = value.clone() - factor.clone() * pivot_value.clone();
+                }
+                row[k] = factor;
+            }
+        }
+        Ok(Decomposition { lu, permutation, swaps, tolerance })
+    }
+}
+
+impl<T: Math<T>> Decomposition<T> {
+    fn inverse(&self) -> Result<Matrix<T>, CalculatorError> {
+        let columns = Matrix::identity(self.lu.len())?.rows
+            .into_iter()
+            .map(|column| self.solve(column))
+            .collect();
+        Ok(Matrix { rows: columns }.transpose())
+    }
+
+    fn is_singular(&self) -> bool {
+        self.lu.iter().enumerate().any(|(index, row)| row[index].magnitude() <= self.tolerance)
+    }
+
+    /// Solves `Ax = b` for `x` by forward then back substitution
+    fn solve(&self, b: Vec<T>) -> Vec<T> {
+        let size = self.lu.len();
+        let mut y: Vec<T> = Vec::with_capacity(size);
+        for i in 0..size {
+            let mut value = b[self.permutation[i]].clone();
+            for (j, y_j) in y.iter().enumerate() {
+                value = value - self.lu[i][j].clone() * y_j.clone();
+            }
+            y.push(value);
+        }
+        let mut x = y;
+        for i in (0..size).rev() {
+            let mut value = x[i].clone();
+            for (u_ij, x_j) in self.lu[i].iter().zip(&x).skip(i + 1) {
+                value = value - u_ij.clone() * x_j.clone();
+            }
+            x[i] = value / self.lu[i][i].clone();
+        }
+        x
+    }
+}
+
+impl<T: Math<T>> Add for Matrix<T> {
+    type Output = Result<Matrix<T>, CalculatorError>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, T::checked_plus)
+    }
+}
+
+impl<T: Math<T>> Sub for Matrix<T> {
+    type Output = Result<Matrix<T>, CalculatorError>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, T::checked_minus)
+    }
+}
+
+/// The matrix product
+impl<T: Math<T>> Mul for Matrix<T> {
+    type Output = Result<Matrix<T>, CalculatorError>;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (rows, _) = rhs.shape();
+        let (_, columns) = self.shape();
+        if columns != rows {
+            return Err(CalculatorError::DimensionMismatch { expected: columns, found: rows });
+        }
+        let rhs_columns = rhs.transpose().into_row_vectors();
+        Self::new(self.into_row_vectors()
+            .into_iter()
+            .map(|row| rhs_columns.iter().map(|column| row.clone().dot(column.clone())).collect())
+            .collect::<Result<Vec<Vec<T>>, CalculatorError>>()?)
+    }
+}
+
+impl<T: Math<T>> Mul<T> for Matrix<T> {
+    type Output = Result<Matrix<T>, CalculatorError>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.try_map(|element| element.checked_times(rhs.clone()))
+    }
+}
+
+impl<T: Math<T>> Div<T> for Matrix<T> {
+    type Output = Result<Matrix<T>, CalculatorError>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.try_map(|element| element.checked_div(rhs.clone()))
+    }
+}
+
+impl<T: Math<T>> Percent for Matrix<T> {
+    fn percent(self) -> Self {
+        self.map(T::percent)
+    }
+}
+
+/// Formats as a literal, e.g. `[[1, 2], [3, 4]]`, or with `{:#}` as a row per line with aligned columns
+impl<T: Format> Format for Matrix<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        let cells: Vec<Vec<String>> = self.rows.iter()
+            .map(|row| row.iter().map(|element| Formatted(element, options).to_string()).collect())
+            .collect();
+        if f.alternate() {
+            let (_, columns) = self.shape();
+            let widths: Vec<usize> = (0..columns)
+                .map(|column| cells.iter().map(|row| row[column].chars().count()).max().unwrap_or_default())
+                .collect();
+            for (index, row) in cells.iter().enumerate() {
+                if index > 0 {
+                    f.write_str("\n")?;
+                }
+                let padded: Vec<String> = row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+                    .collect();
+                write!(f, "[{}]", padded.join("  "))?;
+            }
+            Ok(())
+        } else {
+            let rows: Vec<String> = cells.iter().map(|row| format!("[{}]", row.join(", "))).collect();
+            write!(f, "[{}]", rows.join(", "))
+        }
+    }
+}
+
+impl<T: Format> Display for Matrix<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f, &FormatOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod matrix_tests {
+    use crate::complex::Complex;
+    use crate::rational::Rational;
+    use crate::word::Word;
+
+    use super::*;
+
+    fn matrix(rows: &[&[f64]]) -> Matrix<f64> {
+        Matrix::new(rows.iter().map(|row| row.to_vec()).collect()).unwrap()
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert_eq!(
+            Err(CalculatorError::DimensionMismatch { expected: 2, found: 1 }),
+            Matrix::new(vec![vec![1f64, 2f64], vec![3f64]])
+        );
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(
+            Ok(matrix(&[&[2f64, 2f64], &[3f64, 5f64]])),
+            matrix(&[&[1f64, 2f64], &[3f64, 4f64]]) + matrix(&[&[1f64, 0f64], &[0f64, 1f64]])
+        );
+    }
+
+    #[test]
+    fn add_mismatched_shapes() {
+        assert_eq!(
+            Err(CalculatorError::ShapeMismatch { expected: (2, 2), found: (1, 2) }),
+            matrix(&[&[1f64, 2f64], &[3f64, 4f64]]) + matrix(&[&[1f64, 0f64]])
+        );
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let integers = |rows: &[&[i32]]| Matrix::new(rows.iter().map(|row| row.to_vec()).collect()).unwrap();
+        assert_eq!(Err(CalculatorError::Overflow), integers(&[&[1]]) + integers(&[&[i32::MAX]]));
+        assert_eq!(Err(CalculatorError::Overflow), integers(&[&[i32::MIN]]) - integers(&[&[1]]));
+        assert_eq!(Err(CalculatorError::Overflow), integers(&[&[i32::MAX]]) * 2);
+        assert_eq!(Err(CalculatorError::DivisionByZero), integers(&[&[1, 2], &[3, 4]]) / 0);
+    }
+
+    #[test]
+    fn product() {
+        assert_eq!(
+            Ok(matrix(&[&[19f64, 22f64], &[43f64, 50f64]])),
+            matrix(&[&[1f64, 2f64], &[3f64, 4f64]]) * matrix(&[&[5f64, 6f64], &[7f64, 8f64]])
+        );
+    }
+
+    #[test]
+    fn product_of_different_shapes() {
+        assert_eq!(
+            Ok(matrix(&[&[11f64], &[25f64]])),
+            matrix(&[&[1f64, 2f64], &[3f64, 4f64]]) * matrix(&[&[3f64], &[4f64]])
+        );
+        assert_eq!(
+            Err(CalculatorError::DimensionMismatch { expected: 1, found: 2 }),
+            matrix(&[&[3f64], &[4f64]]) * matrix(&[&[1f64, 2f64], &[3f64, 4f64]])
+        );
+    }
+
+    #[test]
+    fn multiply_vector() {
+        assert_eq!(
+            Vector::new(vec![11f64, 25f64]),
+            matrix(&[&[1f64, 2f64], &[3f64, 4f64]]).multiply_vector(Vector::new(vec![3f64, 4f64]).unwrap())
+        );
+    }
+
+    #[test]
+    fn transpose() {
+        assert_eq!(
+            matrix(&[&[1f64, 4f64], &[2f64, 5f64], &[3f64, 6f64]]),
+            matrix(&[&[1f64, 2f64, 3f64], &[4f64, 5f64, 6f64]]).transpose()
+        );
+    }
+
+    #[test]
+    fn identity() {
+        assert_eq!(Ok(matrix(&[&[1f64, 0f64], &[0f64, 1f64]])), Matrix::identity(2));
+    }
+
+    #[test]
+    fn determinant() {
+        assert_eq!(Ok(-2f64), matrix(&[&[1f64, 2f64], &[3f64, 4f64]]).determinant());
+        assert_eq!(Ok(-306f64), matrix(&[&[6f64, 1f64, 1f64], &[4f64, -2f64, 5f64], &[2f64, 8f64, 7f64]]).determinant());
+        assert_eq!(Ok(0f64), matrix(&[&[1f64, 2f64], &[2f64, 4f64]]).determinant());
+    }
+
+    #[test]
+    fn integer_determinant() {
+        let integers = |rows: &[&[i32]]| Matrix::new(rows.iter().map(|row| row.to_vec()).collect()).unwrap();
+        assert_eq!(Ok(1), integers(&[&[2, 1], &[1, 1]]).determinant());
+        assert_eq!(Ok(-306), integers(&[&[6, 1, 1], &[4, -2, 5], &[2, 8, 7]]).determinant());
+        assert_eq!(Ok(-1), integers(&[&[0, 1], &[1, 0]]).determinant());
+        assert_eq!(Ok(0), integers(&[&[1, 2, 3], &[4, 5, 6], &[7, 8, 9]]).determinant());
+        let big = Matrix::new(vec![vec![Integer::new(2), Integer::new(1)], vec![Integer::new(1), Integer::new(1)]]).unwrap();
+        assert_eq!(Ok(Integer::new(1)), big.determinant());
+    }
+
+    #[test]
+    fn word_determinant() {
+        let words = |rows: &[&[i128]]| Matrix::new(rows.iter().map(|row| row.iter().map(|&value| Word::<8, false>::new(value)).collect()).collect()).unwrap();
+        assert_eq!(Ok(Word::new(178)), words(&[&[2, 3, 5], &[7, 11, 13], &[17, 19, 23]]).determinant());
+        assert_eq!(Ok(Word::new(255)), words(&[&[0, 1], &[1, 0]]).determinant());
+    }
+
+    #[test]
+    fn determinant_of_non_square() {
+        assert_eq!(Err(CalculatorError::NotSquare(1, 2)), matrix(&[&[1f64, 2f64]]).determinant());
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(
+            Ok(matrix(&[&[1f64, -1f64], &[-1f64, 2f64]])),
+            matrix(&[&[2f64, 1f64], &[1f64, 1f64]]).inverse()
+        );
+    }
+
+    #[test]
+    fn inverse_of_singular() {
+        assert_eq!(Err(CalculatorError::SingularMatrix), matrix(&[&[1f64, 2f64], &[2f64, 4f64]]).inverse());
+        let rounded = matrix(&[&[1f64, 2f64, 3f64], &[4f64, 5f64, 6f64], &[7f64, 8f64, 9f64]]);
+        assert_eq!(Err(CalculatorError::SingularMatrix), rounded.clone().inverse());
+        assert_eq!(Ok(0f64), rounded.determinant());
+    }
+
+    #[test]
+    fn integer_inverse() {
+        let integers = Matrix::new(vec![vec![2, 1], vec![1, 1]]).unwrap();
+        assert_eq!(Err(CalculatorError::FractionsOnly("inv".to_string())), integers.inverse());
+    }
+
+    #[test]
+    fn complex_inverse() {
+        let i = Complex::new(0f64, 1f64);
+        let one = Complex::new(1f64, 0f64);
+        let zero = Complex::new(0f64, 0f64);
+        let minus_i = Complex::new(0f64, -1f64);
+        assert_eq!(
+            Ok(Matrix::new(vec![vec![minus_i, zero], vec![zero, one]]).unwrap()),
+            Matrix::new(vec![vec![i, zero], vec![zero, one]]).unwrap().inverse()
+        );
+    }
+
+    #[test]
+    fn solve() {
+        let a = matrix(&[&[2f64, 1f64, -1f64], &[-3f64, -1f64, 2f64], &[-2f64, 1f64, 2f64]]);
+        let b = Vector::new(vec![8f64, -11f64, -3f64]).unwrap();
+        let x = a.solve_vector(b).unwrap().into_elements();
+        for (expected, actual) in [2f64, 3f64, -1f64].iter().zip(x) {
+            assert!((expected - actual).abs() < 1e-12, "{} != {}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn solve_needs_pivoting() {
+        let a = matrix(&[&[0f64, 1f64], &[1f64, 0f64]]);
+        let b = Vector::new(vec![2f64, 3f64]).unwrap();
+        assert_eq!(Vector::new(vec![3f64, 2f64]), a.solve_vector(b));
+    }
+
+    #[test]
+    fn solve_several_right_hand_sides() {
+        let a = matrix(&[&[2f64, 0f64], &[0f64, 4f64]]);
+        let b = matrix(&[&[2f64, 4f64], &[4f64, 8f64]]);
+        assert_eq!(Ok(matrix(&[&[1f64, 2f64], &[1f64, 2f64]])), a.solve(b));
+    }
+
+    #[test]
+    fn solve_mismatched_dimensions() {
+        let a = matrix(&[&[2f64, 0f64], &[0f64, 4f64]]);
+        let b = Vector::new(vec![1f64, 2f64, 3f64]).unwrap();
+        assert_eq!(Err(CalculatorError::DimensionMismatch { expected: 2, found: 3 }), a.solve_vector(b));
+    }
+
+    #[test]
+    fn solve_singular() {
+        let a = matrix(&[&[1f64, 2f64], &[2f64, 4f64]]);
+        let b = Vector::new(vec![1f64, 2f64]).unwrap();
+        assert_eq!(Err(CalculatorError::SingularMatrix), a.solve_vector(b));
+    }
+
+    #[test]
+    fn solve_ill_conditioned() {
+        let a = matrix(&[&[1f64, 1f64], &[1f64, 1f64 + 1e-14]]);
+        let b = Vector::new(vec![2f64, 2f64]).unwrap();
+        assert!(matches!(a.solve_vector(b), Err(CalculatorError::IllConditioned(condition)) if condition > 1e12));
+    }
+
+    #[test]
+    fn solve_exactly_near_singular() {
+        let small = Rational::new(1, 10_000_000_000_000);
+        let a = Matrix::new(vec![vec![Rational::new(1, 1), Rational::new(1, 1)], vec![Rational::new(1, 1), Rational::new(1, 1) + small.clone()]]).unwrap();
+        let b = Vector::new(vec![Rational::new(2, 1), Rational::new(2, 1) + small]).unwrap();
+        assert_eq!(Ok(Vector::new(vec![Rational::new(1, 1), Rational::new(1, 1)]).unwrap()), a.solve_vector(b));
+    }
+
+    #[test]
+    fn solve_integers() {
+        let a = Matrix::new(vec![vec![2, 1], vec![1, 1]]).unwrap();
+        let b = Vector::new(vec![3, 2]).unwrap();
+        assert_eq!(Err(CalculatorError::FractionsOnly("solve".to_string())), a.solve_vector(b));
+    }
+
+    #[test]
+    fn solve_complex() {
+        let i = Complex::new(0f64, 1f64);
+        let one = Complex::new(1f64, 0f64);
+        let zero = Complex::new(0f64, 0f64);
+        let a = Matrix::new(vec![vec![i, zero], vec![zero, one]]).unwrap();
+        let b = Vector::new(vec![one, i]).unwrap();
+        assert_eq!(Vector::new(vec![Complex::new(0f64, -1f64), i]), a.solve_vector(b));
+    }
+
+    #[test]
+    fn power() {
+        let m = matrix(&[&[1f64, 1f64], &[1f64, 0f64]]);
+        assert_eq!(Ok(matrix(&[&[8f64, 5f64], &[5f64, 3f64]])), m.clone().power(5));
+        assert_eq!(Matrix::identity(2), m.clone().power(0));
+        assert_eq!(Ok(matrix(&[&[0f64, 1f64], &[1f64, -1f64]])), m.power(-1));
+    }
+
+    #[test]
+    fn power_overflow() {
+        assert_eq!(Err(CalculatorError::Overflow), matrix(&[&[1e100f64, 0f64], &[0f64, 1f64]]).power(4));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("[[1, 2], [3, 4]]", matrix(&[&[1f64, 2f64], &[3f64, 4f64]]).to_string());
+    }
+
+    #[test]
+    fn pretty_display() {
+        assert_eq!(
+            "[  1  2.5]\n[-10    4]",
+            format!("{:#}", matrix(&[&[1f64, 2.5f64], &[-10f64, 4f64]]))
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+
+use num::{BigInt, Integer, One, Signed, Zero};
+
+use crate::error::CalculatorError;
+use crate::factors::Factors;
+use crate::math::Math;
+
+/// Bases for which Miller–Rabin gives no false positives below 3.3 × 10^24, which covers every
+/// 64-bit integer. Larger numbers that pass are very probably prime.
+const WITNESSES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// Factors below this are found by trial division, larger ones by Pollard's rho
+const TRIAL_DIVISION_LIMIT: u32 = 1000;
+
+/// The largest magnitude below which every integer is exact in `T`, 2^53 for `f64`
+fn exact_limit<T: Math<T>>() -> f64 {
+    2f64 / T::EPSILON
+}
+
+/// The value as an integer, for the function `name` that needs one. Floating point numbers above
+/// [exact_limit] are rejected, as they may not be the integer that was written.
+fn integer<T: Math<T>>(name: &str, value: &T) -> Result<BigInt, CalculatorError> {
+    if value.magnitude() > exact_limit::<T>() {
+        return Err(CalculatorError::IntegerOnly(name.to_string()));
+    }
+    value.to_big_integer().ok_or_else(|| CalculatorError::IntegerOnly(name.to_string()))
+}
+
+/// The result as a `T`, an overflow where a floating point number cannot hold it exactly
+fn result<T: Math<T>>(value: BigInt) -> Result<T, CalculatorError> {
+    let result = T::from_big_integer(value)?;
+    if result.magnitude() > exact_limit::<T>() {
+        return Err(CalculatorError::Overflow);
+    }
+    Ok(result)
+}
+
+/// The greatest common divisor, which is never negative
+pub(crate) fn gcd<T: Math<T>>(a: T, b: T) -> Result<T, CalculatorError> {
+    result(integer("gcd", &a)?.gcd(&integer("gcd", &b)?))
+}
+
+/// The least common multiple, which is never negative
+pub(crate) fn lcm<T: Math<T>>(a: T, b: T) -> Result<T, CalculatorError> {
+    result(integer("lcm", &a)?.lcm(&integer("lcm", &b)?))
+}
+
+/// One if `n` is prime, otherwise zero
+pub(crate) fn is_prime<T: Math<T>>(n: T) -> Result<T, CalculatorError> {
+    Ok(if is_prime_integer(&integer("isprime", &n)?) { T::one() } else { T::zero() })
+}
+
+/// `b^e mod m`, in the range `0 ≤ result < |m|`. Negative exponents are powers of the inverse of `b`.
+pub(crate) fn modpow<T: Math<T>>(base: T, exponent: T, modulus: T) -> Result<T, CalculatorError> {
+    let modulus = integer("modpow", &modulus)?.abs();
+    if modulus.is_zero() {
+        return Err(CalculatorError::DivisionByZero);
+    }
+    let exponent = integer("modpow", &exponent)?;
+    let base = match exponent.is_negative() {
+        true => inverse(&integer("modpow", &base)?, &modulus)?,
+        false => integer("modpow", &base)?.mod_floor(&modulus),
+    };
+    result(base.modpow(&exponent.abs(), &modulus))
+}
+
+/// The `x` with `a × x mod m = 1`, in the range `0 ≤ x < |m|`
+pub(crate) fn modinv<T: Math<T>>(a: T, modulus: T) -> Result<T, CalculatorError> {
+    let modulus = integer("modinv", &modulus)?.abs();
+    if modulus.is_zero() {
+        return Err(CalculatorError::DivisionByZero);
+    }
+    result(inverse(&integer("modinv", &a)?, &modulus)?)
+}
+
+/// The inverse of `a` modulo a positive `modulus`, by the extended Euclidean algorithm
+fn inverse(a: &BigInt, modulus: &BigInt) -> Result<BigInt, CalculatorError> {
+    let extended = a.mod_floor(modulus).extended_gcd(modulus);
+    if !extended.gcd.is_one() {
+        return Err(CalculatorError::NoInverse { value: a.to_string(), modulus: modulus.to_string() });
+    }
+    Ok(extended.x.mod_floor(modulus))
+}
+
+/// The prime factors of `n`, with minus one as a factor of negative numbers
+pub(crate) fn factor<T: Math<T>>(n: T) -> Result<Factors<T>, CalculatorError> {
+    let mut n = integer("factor", &n)?;
+    if n.is_zero() {
+        return Err(CalculatorError::UnsupportedOperation("factor(0)".to_string()));
+    }
+    let mut factors = BTreeMap::new();
+    if n.is_negative() {
+        factors.insert(BigInt::from(-1), 1);
+        n = -n;
+    }
+    for p in 2..TRIAL_DIVISION_LIMIT {
+        let p = BigInt::from(p);
+        if &p * &p > n {
+            break;
+        }
+        while n.is_multiple_of(&p) {
+            *factors.entry(p.clone()).or_insert(0) += 1;
+            n /= &p;
+        }
+    }
+    factor_large(n, &mut factors);
+    let factors = factors.into_iter()
+        .map(|(prime, power)| Ok((result(prime)?, power)))
+        .collect::<Result<Vec<(T, u32)>, CalculatorError>>()?;
+    Ok(Factors::new(factors))
+}
+
+/// Adds the factors of `n`, which has no factors below the trial division limit
+fn factor_large(n: BigInt, factors: &mut BTreeMap<BigInt, u32>) {
+    if n.is_one() {
+        return;
+    }
+    if is_prime_integer(&n) {
+        *factors.entry(n).or_insert(0) += 1;
+        return;
+    }
+    let divisor = pollard_rho(&n);
+    factor_large(&n / &divisor, factors);
+    factor_large(divisor, factors);
+}
+
+/// A non-trivial divisor of the odd composite `n`, found by Floyd's cycle detection on
+/// `x → x² + c mod n`
+fn pollard_rho(n: &BigInt) -> BigInt {
+    let mut c = BigInt::one();
+    loop {
+        let step = |x: &BigInt| (x * x + &c) % n;
+        let (mut x, mut y, mut divisor) = (BigInt::from(2), BigInt::from(2), BigInt::one());
+        while divisor.is_one() {
+            x = step(&x);
+            y = step(&step(&y));
+            divisor = (&x - &y).abs().gcd(n);
+        }
+        if &divisor != n {
+            return divisor;
+        }
+        c += 1;
+    }
+}
+
+/// Miller–Rabin with the bases in [WITNESSES]
+fn is_prime_integer(n: &BigInt) -> bool {
+    if n < &BigInt::from(2) {
+        return false;
+    }
+    for witness in WITNESSES {
+        let witness = BigInt::from(witness);
+        if n == &witness {
+            return true;
+        }
+        if n.is_multiple_of(&witness) {
+            return false;
+        }
+    }
+    // n - 1 = d × 2^s with d odd
+    let n_minus_one: BigInt = n - 1;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    WITNESSES.iter().all(|&witness| {
+        let mut x = BigInt::from(witness).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            return true;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+#[cfg(test)]
+mod number_theory_tests {
+    use super::*;
+
+    fn primes(n: i64) -> Vec<(i64, u32)> {
+        factor(n as f64)
+            .unwrap()
+            .into_factors()
+            .into_iter()
+            .map(|(prime, power)| (prime as i64, power))
+            .collect()
+    }
+
+    #[test]
+    fn primality() {
+        let primes: Vec<i64> = (0..30).filter(|&n| is_prime_integer(&BigInt::from(n))).collect();
+        assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29], primes);
+        // The largest 64-bit prime, and a strong pseudoprime to the bases 2 to 23
+        assert!(is_prime_integer(&BigInt::from(18_446_744_073_709_551_557u64)));
+        assert!(!is_prime_integer(&"3825123056546413051".parse().unwrap()));
+        // A Carmichael number
+        assert!(!is_prime_integer(&BigInt::from(561)));
+    }
+
+    #[test]
+    fn factors() {
+        assert_eq!(vec![(2, 3), (3, 1), (5, 1)], primes(120));
+        assert_eq!(vec![(-1, 1), (7, 1)], primes(-7));
+        assert_eq!(Vec::<(i64, u32)>::new(), primes(1));
+        assert_eq!(vec![(1_000_003, 1), (1_000_033, 1)], primes(1_000_003 * 1_000_033));
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(Ok(445), modpow(4, 13, 497));
+        assert_eq!(Ok(4), modinv(3, 11));
+        assert_eq!(Ok(4), modpow(3, -1, 11));
+        assert_eq!(Ok(2), modpow(0 - 3, 1, 5));
+        assert_eq!(Err(CalculatorError::NoInverse { value: "2".to_string(), modulus: "4".to_string() }), modinv(2, 4));
+        assert_eq!(Err(CalculatorError::DivisionByZero), modpow(2, 2, 0));
+    }
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(Ok(6), gcd(12, 0 - 18));
+        assert_eq!(Ok(36), lcm(12, 18));
+        assert_eq!(Err(CalculatorError::IntegerOnly("gcd".to_string())), gcd(1.5, 3f64));
+        assert_eq!(Err(CalculatorError::Overflow), lcm(1_073_741_825f64, 1_073_741_827f64));
+    }
+
+    #[test]
+    fn inexact_floating_point() {
+        let large = 18_446_744_073_709_551_615f64;
+        assert_eq!(Err(CalculatorError::IntegerOnly("factor".to_string())), factor(large).map(Factors::into_factors));
+        assert_eq!(Err(CalculatorError::IntegerOnly("isprime".to_string())), is_prime(18_446_744_073_709_551_557f64));
+        assert_eq!(Ok(1f64), is_prime(9_007_199_254_740_881f64));
+    }
+}
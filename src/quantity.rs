@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+use std::fmt::Formatter;
+
+use crate::error::CalculatorError;
+use crate::format::{Format, FormatOptions};
+use crate::math::Math;
+use crate::rounding::Rounding;
+use crate::temporal;
+use crate::tokenizer::Token;
+use crate::units::Dimension;
+use crate::value::Value;
+
+/// A number with a unit, kept in SI base units, e.g. `3 km` is 3000 with the dimension m
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantity<T> {
+    value: T,
+    dimension: Dimension,
+    /// The value in the unit it was converted to with `to` or `in`, and that unit as written
+    converted: Option<(T, String)>,
+    /// The date of the exchange rates used to convert it from one currency to another
+    as_of: Option<String>,
+}
+
+impl<T> Quantity<T> {
+    pub fn new(value: T, dimension: Dimension) -> Self {
+        Quantity { value, dimension, converted: None, as_of: None }
+    }
+
+    pub fn dimension(&self) -> &Dimension {
+        &self.dimension
+    }
+
+    /// A plain number, as a quantity so that it can be combined with others
+    pub fn dimensionless(value: T) -> Self {
+        Self::new(value, Dimension::default())
+    }
+}
+
+impl<T: Math<T>> Quantity<T> {
+    /// Plain numbers are scalars, the units of `3 m/m` cancel out
+    pub(crate) fn into_value(self) -> Value<T> {
+        if self.dimension.is_dimensionless() {
+            return Value::Scalar(self.value);
+        }
+        Value::Quantity(self)
+    }
+
+    fn same_dimension(&self, rhs: &Self) -> Result<(), CalculatorError> {
+        if self.dimension != rhs.dimension {
+            return Err(CalculatorError::IncompatibleUnits(self.dimension.to_string(), rhs.dimension.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Applies a function that does not change the unit, such as `abs`
+    pub(crate) fn map(self, operation: impl Fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        Ok(Self::new(operation(self.value)?, self.dimension))
+    }
+
+    /// The dimension of this quantity to the power `exponent`, which must be an integer or the inverse of one
+    fn power_dimension(&self, exponent: &T) -> Result<Dimension, CalculatorError> {
+        if let Some(exponent) = exponent.to_integer() {
+            let exponent = i32::try_from(exponent).map_err(|_| CalculatorError::Overflow)?;
+            return self.dimension.power(exponent);
+        }
+        match T::one().checked_div(exponent.clone())?.to_integer() {
+            Some(index) => self.dimension.root(index),
+            None => Err(CalculatorError::UnsupportedOperation(format!("{} to a fractional power", self.dimension))),
+        }
+    }
+
+    /// Applies the binary operator `t`, working out the unit of the result
+    pub(crate) fn combine(self, t: &Token, rhs: Self, operation: fn(T, T) -> Result<T, CalculatorError>) -> Result<Value<T>, CalculatorError> {
+        let dimension = match t {
+            Token::Plus | Token::Minus | Token::Modulo => {
+                self.same_dimension(&rhs)?;
+                self.dimension.clone()
+            }
+            // `3 m + 10%` in percentage mode
+            Token::PercentIncrease | Token::PercentDecrease if rhs.dimension.is_dimensionless() => self.dimension.clone(),
+            Token::Multiply => self.dimension.multiply(&rhs.dimension)?,
+            Token::Divide => self.dimension.divide(&rhs.dimension)?,
+            Token::Power if rhs.dimension.is_dimensionless() => self.power_dimension(&rhs.value)?,
+            // `n√x`, the index comes first
+            Token::Root if self.dimension.is_dimensionless() => {
+                let index = self.value.to_integer()
+                    .ok_or_else(|| CalculatorError::UnsupportedOperation(format!("{} to a fractional power", rhs.dimension)))?;
+                rhs.dimension.root(index)?
+            }
+            t => return Err(CalculatorError::UnsupportedOperation(format!("{} {} {}", self.dimension, t, rhs.dimension))),
+        };
+        Ok(Self::new(operation(self.value, rhs.value)?, dimension).into_value())
+    }
+
+    /// Measures this quantity in `to` rather than the base unit `from`, `factor` of which are one `from`
+    pub(crate) fn replace_base(self, from: &str, to: &str, factor: T) -> Result<Self, CalculatorError> {
+        let power = self.dimension.power_of(from);
+        let replaced = Dimension::base(from).power(power)?;
+        let dimension = self.dimension.divide(&replaced)?.multiply(&Dimension::base(to).power(power)?)?;
+        let mut value = self.value;
+        for _ in 0..power.abs() {
+            value = if power > 0 { value * factor.clone() } else { value.checked_div(factor.clone())? };
+        }
+        Ok(Self::new(value, dimension))
+    }
+
+    pub(crate) fn with_as_of(self, date: &str) -> Self {
+        Quantity { as_of: Some(date.to_string()), ..self }
+    }
+
+    /// Orders quantities of the same dimension by their values with `order`
+    pub(crate) fn compare(&self, rhs: &Self, order: impl Fn(&T, &T) -> Option<Ordering>) -> Result<Option<Ordering>, CalculatorError> {
+        self.same_dimension(rhs)?;
+        Ok(order(&self.value, &rhs.value))
+    }
+
+    /// A duration in whole seconds, to add to a date
+    pub(crate) fn seconds(&self) -> Result<i64, CalculatorError> {
+        if self.dimension != Dimension::base("s") {
+            return Err(CalculatorError::IncompatibleUnits(self.dimension.to_string(), "s".to_string()));
+        }
+        if self.value.clone().im().magnitude() != 0f64 {
+            return Err(CalculatorError::UnsupportedOperation("date + complex duration".to_string()));
+        }
+        self.value.clone().round_to(0, Rounding::HalfUp)?.to_integer().ok_or(CalculatorError::Overflow)
+    }
+
+    /// Shows this quantity in the units of `target`, which is one of `name`
+    pub(crate) fn convert(self, target: Self, name: &str) -> Result<Self, CalculatorError> {
+        self.same_dimension(&target)?;
+        let converted = self.value.clone().checked_div(target.value)?;
+        Ok(Quantity { converted: Some((converted, name.to_string())), ..self })
+    }
+}
+
+/// In the unit it was converted to, otherwise in SI base units. Durations of a minute or more are
+/// also shown in ISO 8601 and in words, `12300 s (PT3H25M, 3 hours 25 minutes)`.
+impl<T: Format + Math<T>> Format for Quantity<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        match &self.converted {
+            Some((value, unit)) => {
+                value.format(f, options)?;
+                write!(f, " {}", unit)?;
+                match &self.as_of {
+                    Some(date) => write!(f, " (rates as of {})", date),
+                    None => Ok(()),
+                }
+            }
+            None => {
+                self.value.format(f, options)?;
+                write!(f, " {}", self.dimension)?;
+                match self.value.to_integer().filter(|seconds| seconds.abs() >= 60) {
+                    Some(seconds) if self.dimension == Dimension::base("s") =>
+                        write!(f, " ({}, {})", temporal::iso_duration(seconds), temporal::human_duration(seconds)),
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod quantity_tests {
+    use super::*;
+
+    fn metres(value: f64) -> Quantity<f64> {
+        Quantity::new(value, Dimension::base("m"))
+    }
+
+    fn seconds(value: f64) -> Quantity<f64> {
+        Quantity::new(value, Dimension::base("s"))
+    }
+
+    #[test]
+    fn units_of_results() {
+        let speed = metres(6f64).combine(&Token::Divide, seconds(2f64), |a, b| Ok(a / b)).unwrap();
+        assert_eq!("3 m/s", speed.to_string());
+        let area = metres(2f64).combine(&Token::Power, Quantity::dimensionless(2f64), |a, b| Ok(a.powf(b))).unwrap();
+        assert_eq!("4 m^2", area.to_string());
+        let ratio = metres(6f64).combine(&Token::Divide, metres(2f64), |a, b| Ok(a / b)).unwrap();
+        assert_eq!(Value::Scalar(3f64), ratio);
+    }
+
+    #[test]
+    fn incompatible_units() {
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            metres(1f64).combine(&Token::Plus, seconds(1f64), |a, b| Ok(a + b))
+        );
+        assert!(seconds(1f64).convert(metres(1f64), "m").is_err());
+    }
+
+    #[test]
+    fn conversion() {
+        let converted = metres(1500f64).convert(metres(1000f64), "km").unwrap();
+        assert_eq!("1.5 km", Value::Quantity(converted).to_string());
+    }
+}
@@ -0,0 +1,350 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use num::{BigInt, BigRational, FromPrimitive, Signed, ToPrimitive};
+use num::pow::Pow as NumPow;
+
+use crate::calculator::{FromStrValue, radix_literal};
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::{self, Rounding};
+use crate::math::{BigInteger, Bitwise, Checked, CheckedDiv, Compare, Elementary, Gamma, Inv, Magnitude, Modulo, One, Percent, Polar, Pow, Round, Real, ToInteger, Zero};
+
+/// An exact fraction of arbitrary size, parsed from decimals such as `2.5` and displayed as `5/2`
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct Rational(BigRational);
+
+impl Rational {
+    pub fn new(numerator: i64, denominator: i64) -> Self {
+        Rational(BigRational::new(numerator.into(), denominator.into()))
+    }
+
+    pub fn as_integer(&self) -> Option<BigInt> {
+        if self.0.is_integer() {
+            Some(self.0.to_integer())
+        } else {
+            None
+        }
+    }
+
+    /// The numerator and denominator in lowest terms, with the sign on the numerator
+    pub fn parts(&self) -> (Rational, Rational) {
+        let part = |integer: &BigInt| Rational(BigRational::from_integer(integer.clone()));
+        (part(self.0.numer()), part(self.0.denom()))
+    }
+
+    fn is_zero(&self) -> bool {
+        num::Zero::is_zero(&self.0)
+    }
+
+    /// Calculates a function without an exact form through `f64`
+    fn through_f64(self, function: fn(f64) -> f64) -> Self {
+        Self::from_f64(function(self.to_f64()))
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Rational(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Rational(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational(self.0 * rhs.0)
+    }
+}
+
+/// Panics when dividing by zero, [CheckedDiv] reports it instead
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Rational(self.0 / rhs.0)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        Rational(-self.0)
+    }
+}
+
+impl CheckedDiv for Rational {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs.is_zero() {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+}
+
+impl Zero for Rational {
+    fn zero() -> Self {
+        Rational(num::Zero::zero())
+    }
+}
+
+impl One for Rational {
+    fn one() -> Self {
+        Rational(num::One::one())
+    }
+}
+
+impl Inv for Rational {
+    fn inv(self) -> Self {
+        Rational(self.0.recip())
+    }
+}
+
+/// Integer powers are exact, others are calculated through `f64`
+impl Pow for Rational {
+    fn pow(self, rhs: Self) -> Self {
+        match rhs.to_integer() {
+            Some(exponent) => Rational(NumPow::pow(self.0, BigInt::from(exponent))),
+            None => Self::from_f64(self.to_f64().powf(rhs.to_f64())),
+        }
+    }
+}
+
+impl Percent for Rational {
+    fn percent(self) -> Self {
+        self / Rational::new(100, 1)
+    }
+}
+
+impl Elementary for Rational {
+    fn sqrt(self) -> Self {
+        self.through_f64(f64::sqrt)
+    }
+
+    fn exp(self) -> Self {
+        self.through_f64(f64::exp)
+    }
+
+    fn ln(self) -> Self {
+        self.through_f64(f64::ln)
+    }
+
+    fn sin(self) -> Self {
+        self.through_f64(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.through_f64(f64::cos)
+    }
+
+    fn tan(self) -> Self {
+        self.through_f64(f64::tan)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(Rational(self.0.abs()))
+    }
+}
+
+impl Polar for Rational {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        Self::zero()
+    }
+
+    fn arg(self) -> Self {
+        self.through_f64(|value| 0f64.atan2(value))
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        Ok(Self::from_f64(self.to_f64().cis()?))
+    }
+
+    fn degrees(self) -> Self {
+        self.through_f64(f64::to_radians)
+    }
+}
+
+impl Magnitude for Rational {
+    fn magnitude(&self) -> f64 {
+        self.to_f64().abs()
+    }
+}
+
+impl Modulo for Rational {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let quotient = self.clone().quotient(rhs.clone())?;
+        Ok(self - rhs * quotient)
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let ratio = self.checked_div(rhs.clone())?.0;
+        let quotient = if rhs.0.is_negative() { ratio.ceil() } else { ratio.floor() };
+        Ok(Rational(quotient))
+    }
+}
+
+impl Checked for Rational {}
+
+impl Bitwise for Rational {}
+
+impl BigInteger for Rational {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        self.as_integer()
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        Ok(Rational(BigRational::from_integer(value)))
+    }
+}
+
+impl Compare for Rational {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+}
+
+impl Round for Rational {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Ok(Rational(rounding::round_exact(&self.0, digits, rounding)))
+    }
+
+    fn sign(self) -> Self {
+        Rational(self.0.signum())
+    }
+}
+
+/// Exact for integers, otherwise calculated through `f64`
+impl Gamma for Rational {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        if self.0.is_integer() {
+            return combinatorics::factorial(self - Self::one());
+        }
+        Ok(Self::from_f64(Gamma::gamma(self.to_f64())?))
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        Ok(Self::from_f64(Gamma::ln_gamma(self.to_f64())?))
+    }
+}
+
+impl ToInteger for Rational {
+    fn to_integer(&self) -> Option<i64> {
+        if !self.0.is_integer() {
+            return None;
+        }
+        self.0.to_integer().to_i64()
+    }
+}
+
+impl Real for Rational {
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().unwrap_or(f64::NAN)
+    }
+
+    fn from_f64(value: f64) -> Self {
+        BigRational::from_f64(value).map_or_else(Self::zero, Rational)
+    }
+}
+
+/// Parses decimals, optionally with an exponent, e.g. `2.5` or `1.5e3`, and integers in other bases such as `0x1F`
+impl FromStr for Rational {
+    type Err = CalculatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || CalculatorError::Parse(s.to_string());
+        if let Some((radix, digits)) = radix_literal(s) {
+            let integer = BigInt::parse_bytes(digits.as_bytes(), radix).ok_or_else(error)?;
+            return Ok(Rational(BigRational::from_integer(integer)));
+        }
+        let (mantissa, exponent) = match s.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => (mantissa, exponent.parse::<i32>().map_err(|_| error())?),
+            None => (s, 0),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", whole, fraction);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+        let digits: BigInt = digits.parse().map_err(|_| error())?;
+        let scale = BigRational::from_integer(BigInt::from(10)).pow(exponent - fraction.len() as i32);
+        Ok(Rational(BigRational::from_integer(digits) * scale))
+    }
+}
+
+/// The constants are the nearest fractions to their `f64` values
+impl FromStrValue for Rational {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tau" => Ok(Self::from_f64(std::f64::consts::TAU)),
+            "pi" => Ok(Self::from_f64(std::f64::consts::PI)),
+            "e" => Ok(Self::from_f64(std::f64::consts::E)),
+            _ => s.parse()
+        }
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod rational_tests {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Rational, CalculatorError> {
+        <Rational as FromStrValue>::from_str(s)
+    }
+
+    #[test]
+    fn parse_decimals() {
+        assert_eq!(Ok(Rational::new(3, 1)), parse("3"));
+        assert_eq!(Ok(Rational::new(5, 2)), parse("2.5"));
+        assert_eq!(Ok(Rational::new(1, 8)), parse(".125"));
+        assert_eq!(Ok(Rational::new(1500, 1)), parse("1.5e3"));
+        assert_eq!(Ok(Rational::new(1, 4000)), parse("2.5e-4"));
+        assert_eq!(Ok(Rational::new(31, 1)), parse("0x1F"));
+        assert_eq!(Err(CalculatorError::Parse("x".to_string())), parse("x"));
+    }
+
+    #[test]
+    fn exact_arithmetic() {
+        let third = Rational::new(1, 3);
+        assert_eq!(Rational::one(), third.clone() + third.clone() + third.clone());
+        assert_eq!(Rational::new(1, 9), third.clone().pow(Rational::new(2, 1)));
+        assert_eq!(Rational::new(9, 1), third.pow(Rational::new(-2, 1)));
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(Err(CalculatorError::DivisionByZero), Rational::one().checked_div(Rational::zero()));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("-5/2", Rational::new(-5, 2).to_string());
+        assert_eq!("3", Rational::new(3, 1).to_string());
+    }
+}
@@ -0,0 +1,144 @@
+use bigdecimal::{BigDecimal, RoundingMode};
+use num::{BigInt, BigRational, Integer, One};
+use num::pow::Pow;
+
+/// Which way numbers between two multiples go. `round` uses the session mode, one of the first
+/// three, while `floor`, `ceil` and `trunc` always round the same way.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Rounding {
+    /// Halves go to the even neighbour, banker's rounding, so 2.5 is 2 and 3.5 is 4
+    HalfEven,
+    /// Halves go away from zero, so 2.5 is 3 and -2.5 is -3
+    #[default]
+    HalfUp,
+    TowardZero,
+    Floor,
+    Ceiling,
+}
+
+impl Rounding {
+    /// The rounding modes that can be selected for `round`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "half-even" | "even" | "bankers" => Some(Rounding::HalfEven),
+            "half-up" => Some(Rounding::HalfUp),
+            "toward-zero" | "truncate" => Some(Rounding::TowardZero),
+            _ => None,
+        }
+    }
+
+    /// Rounds to an integer
+    pub(crate) fn apply(self, value: f64) -> f64 {
+        match self {
+            Rounding::HalfEven => value.round_ties_even(),
+            Rounding::HalfUp => value.round(),
+            Rounding::TowardZero => value.trunc(),
+            Rounding::Floor => value.floor(),
+            Rounding::Ceiling => value.ceil(),
+        }
+    }
+
+    /// Rounds to an integer, exactly
+    fn apply_exact(self, value: &BigRational) -> BigInt {
+        match self {
+            Rounding::HalfEven => {
+                let floor = value.floor().to_integer();
+                let excess = value - BigRational::from_integer(floor.clone());
+                let half = BigRational::new(BigInt::one(), BigInt::from(2));
+                if excess > half || (excess == half && floor.is_odd()) {
+                    floor + 1
+                } else {
+                    floor
+                }
+            }
+            Rounding::HalfUp => value.round().to_integer(),
+            Rounding::TowardZero => value.trunc().to_integer(),
+            Rounding::Floor => value.floor().to_integer(),
+            Rounding::Ceiling => value.ceil().to_integer(),
+        }
+    }
+}
+
+/// Rounds `value` to `digits` decimal places, or to tens, hundreds and so on for negative `digits`.
+/// Floating point values are scaled, so halves that are not exact in binary, such as 2.675, can go
+/// either way.
+pub(crate) fn round_f64(value: f64, digits: i32, rounding: Rounding) -> f64 {
+    let scale = 10f64.powi(digits.abs());
+    if digits >= 0 {
+        let scaled = value * scale;
+        // Already more precise than it can be rounded to
+        if !scaled.is_finite() || scaled.abs() >= 2f64.powi(f64::MANTISSA_DIGITS as i32) {
+            return value;
+        }
+        rounding.apply(scaled) / scale
+    } else {
+        let multiple = rounding.apply(value / scale);
+        // Zero times a scale beyond the range of f64, which is infinite, would be NaN
+        if multiple == 0f64 { multiple } else { multiple * scale }
+    }
+}
+
+/// Rounds `value` to `digits` decimal places, exactly
+pub(crate) fn round_exact(value: &BigRational, digits: i32, rounding: Rounding) -> BigRational {
+    let scale = BigRational::from_integer(BigInt::from(10).pow(digits.unsigned_abs()));
+    if digits >= 0 {
+        BigRational::from_integer(rounding.apply_exact(&(value * &scale))) / scale
+    } else {
+        BigRational::from_integer(rounding.apply_exact(&(value / &scale))) * scale
+    }
+}
+
+/// Rounds a decimal to `digits` decimal places, exactly
+pub(crate) fn round_decimal(value: &BigDecimal, digits: i32, rounding: Rounding) -> BigDecimal {
+    let mode = match rounding {
+        Rounding::HalfEven => RoundingMode::HalfEven,
+        Rounding::HalfUp => RoundingMode::HalfUp,
+        Rounding::TowardZero => RoundingMode::Down,
+        Rounding::Floor => RoundingMode::Floor,
+        Rounding::Ceiling => RoundingMode::Ceiling,
+    };
+    value.with_scale_round(i64::from(digits), mode)
+}
+
+/// Rounds an integer to `digits` decimal places, which only changes it for negative `digits`
+pub(crate) fn round_integer(value: BigInt, digits: i32, rounding: Rounding) -> BigInt {
+    if digits >= 0 {
+        return value;
+    }
+    round_exact(&BigRational::from_integer(value), digits, rounding).to_integer()
+}
+
+#[cfg(test)]
+mod rounding_tests {
+    use super::*;
+
+    fn exact(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let halves = [-2.5, -1.5, -0.5, 0.5, 1.5, 2.5];
+        let round = |rounding: Rounding| halves.map(|value| round_f64(value, 0, rounding));
+        assert_eq!([-2.0, -2.0, -0.0, 0.0, 2.0, 2.0], round(Rounding::HalfEven));
+        assert_eq!([-3.0, -2.0, -1.0, 1.0, 2.0, 3.0], round(Rounding::HalfUp));
+        assert_eq!([-2.0, -1.0, -0.0, 0.0, 1.0, 2.0], round(Rounding::TowardZero));
+        assert_eq!([-3.0, -2.0, -1.0, 0.0, 1.0, 2.0], round(Rounding::Floor));
+        assert_eq!([-2.0, -1.0, -0.0, 1.0, 2.0, 3.0], round(Rounding::Ceiling));
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(1.23, round_f64(1.234_56, 2, Rounding::HalfUp));
+        assert_eq!(1200f64, round_f64(1234f64, -2, Rounding::HalfUp));
+        assert_eq!(1e300, round_f64(1e300, 5, Rounding::HalfUp));
+        assert_eq!(1.5, round_f64(1.5, 400, Rounding::HalfUp));
+        assert_eq!(0f64, round_f64(1.5, -400, Rounding::HalfUp));
+        assert_eq!(exact(31, 25), round_exact(&exact(1_245, 1_000), 2, Rounding::HalfEven));
+        assert_eq!(exact(63, 50), round_exact(&exact(1_255, 1_000), 2, Rounding::HalfEven));
+        assert_eq!(exact(-63, 50), round_exact(&exact(-1_255, 1_000), 2, Rounding::HalfUp));
+        assert_eq!(BigInt::from(1_300), round_integer(BigInt::from(1_250), -2, Rounding::HalfUp));
+        assert_eq!(BigInt::from(1_200), round_integer(BigInt::from(1_250), -2, Rounding::HalfEven));
+        assert_eq!(BigInt::from(1_259), round_integer(BigInt::from(1_259), 1, Rounding::HalfEven));
+    }
+}
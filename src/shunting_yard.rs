@@ -48,6 +48,8 @@ impl<T: ShuntingYardToken, I: Iterator<Item=T>> Shunt<T, I> for I {
 pub struct ShuntingYard<T: ShuntingYardToken> {
     output_queue: VecDeque<T>,
     operator_stack: Vec<(T, ShuntType)>,
+    /// The number of arguments seen so far within each open brace
+    argument_counts: Vec<usize>,
     last_shunt_type: Option<ShuntType>,
 }
 
@@ -68,6 +70,14 @@ pub enum ShuntType {
         associativity: Associativity,
         precedence: u8,
     },
+    /// A unary operator written before its operand, e.g. `~x`
+    Prefix {
+        precedence: u8,
+    },
+    /// Applied to the arguments in the braces that follow it
+    Function,
+    /// Separates the arguments within braces
+    Separator,
     OpenBrace,
     CloseBrace,
 }
@@ -76,6 +86,21 @@ pub trait ShuntingYardToken: Sized {
     fn shunt_type(&self) -> ShuntType;
     /// When two operands have no token between them, this is the token that should be assumed
     fn operand_separator() -> Option<Self>;
+    /// The precedence of an operand separator assumed before this token, where it should bind more
+    /// tightly than the separator otherwise does
+    fn separator_precedence(&self) -> Option<u8> {
+        None
+    }
+    /// Called on the function applied to braces at each separator between its arguments, with the
+    /// number of arguments before the separator. Any token returned is added to the output
+    fn separate(&self, _arguments: usize) -> Option<Self> {
+        None
+    }
+    /// Called on an open brace when it is closed, and then on the function applied to it if there is one, with the
+    /// number of arguments found between the braces. Any token returned is added to the output
+    fn close(self, _arguments: usize) -> Option<Self> {
+        None
+    }
 }
 
 impl<T: ShuntingYardToken> ShuntingYard<T> {
@@ -83,70 +108,128 @@ impl<T: ShuntingYardToken> ShuntingYard<T> {
         Self {
             output_queue: Default::default(),
             operator_stack: Default::default(),
+            argument_counts: Default::default(),
             last_shunt_type: Default::default(),
         }
     }
 
     pub fn push(&mut self, token: T) {
         let shunt_type = token.shunt_type();
+        if let ShuntType::Operand | ShuntType::Prefix { .. } | ShuntType::Function | ShuntType::OpenBrace = &shunt_type {
+            if let Some(ShuntType::Operand) = self.last_shunt_type {
+                if let Some(injected_separator_token) = <T as ShuntingYardToken>::operand_separator() {
+                    match token.separator_precedence() {
+                        Some(precedence) => {
+                            self.last_shunt_type = None;
+                            self.push_operator(injected_separator_token, ShuntType::Operator { associativity: Associativity::Left, precedence });
+                        }
+                        None => self.push(injected_separator_token),
+                    }
+                }
+            }
+        }
         match &shunt_type {
             ShuntType::Operand => {
-                let previous_shunt_type = self.last_shunt_type.replace(shunt_type);
+                self.last_shunt_type = Some(shunt_type);
                 self.output_queue.push_back(token);
-                if let Some(ShuntType::Operand) = previous_shunt_type {
-                    if let Some(injected_separator_token) = <T as ShuntingYardToken>::operand_separator() {
-                        let injected_shunt_type = injected_separator_token.shunt_type();
-                        self.operator_stack.push((injected_separator_token, injected_shunt_type));
-                    }
-                }
             }
-            ShuntType::Operator { associativity: _o1associativity, precedence: o1precedence } => {
+            ShuntType::Operator { .. } => {
                 self.last_shunt_type = None;
-                while !self.operator_stack.is_empty() {
-                    let (_, operator2) = self.operator_stack.last().unwrap();
-                    match operator2 {
-                        ShuntType::Operand => panic!("Operand in operator stack"),
-                        ShuntType::Operator { associativity: o2associativity, precedence: o2precedence } => {
-                            if o2precedence > o1precedence || o2precedence == o1precedence && o2associativity == &Associativity::Left {
-                                let (token, _) = self.operator_stack.pop().unwrap();
-                                self.output_queue.push_back(token);
-                            } else {
-                                break;
-                            }
-                        }
-                        ShuntType::OpenBrace => {
-                            break;
+                self.push_operator(token, shunt_type);
+            }
+            ShuntType::Prefix { .. } | ShuntType::Function => {
+                self.last_shunt_type = None;
+                self.operator_stack.push((token, shunt_type));
+            }
+            ShuntType::Separator => {
+                self.last_shunt_type = None;
+                self.pop_to_open_brace();
+                if let Some(count) = self.argument_counts.last_mut() {
+                    let function = self.operator_stack.len().checked_sub(2).map(|index| &self.operator_stack[index]);
+                    if let Some((function, ShuntType::Function)) = function {
+                        if let Some(token) = function.separate(*count) {
+                            self.output_queue.push_back(token);
                         }
-                        ShuntType::CloseBrace => panic!("CloseBrace in operator stack"),
                     }
+                    *count += 1;
                 }
-                self.operator_stack.push((token, shunt_type));
             }
             ShuntType::OpenBrace => {
+                self.last_shunt_type = Some(ShuntType::OpenBrace);
+                self.argument_counts.push(1);
                 self.operator_stack.push((token, shunt_type))
             }
             ShuntType::CloseBrace => {
-                while !self.operator_stack.is_empty() {
-                    let (_, operator2) = self.operator_stack.last().unwrap();
-                    if let ShuntType::OpenBrace = operator2 {
-                        // Discard
-                        self.operator_stack.pop();
+                let count = self.argument_counts.pop().unwrap_or_default();
+                let arguments = match self.last_shunt_type {
+                    Some(ShuntType::OpenBrace) => 0,
+                    _ => count,
+                };
+                self.last_shunt_type = Some(ShuntType::Operand);
+                self.pop_to_open_brace();
+                match self.operator_stack.pop() {
+                    Some((open_brace, _)) => {
+                        if let Some(token) = open_brace.close(arguments) {
+                            self.output_queue.push_back(token);
+                        }
+                    }
+                    // A close brace without an open brace is left in the output for the caller to report
+                    None => self.output_queue.push_back(token),
+                }
+                if let Some((_, ShuntType::Function)) = self.operator_stack.last() {
+                    let (function, _) = self.operator_stack.pop().unwrap();
+                    if let Some(token) = function.close(arguments) {
+                        self.output_queue.push_back(token);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Moves operators that bind at least as tightly as this binary operator to the output, then
+    /// stacks it
+    fn push_operator(&mut self, token: T, shunt_type: ShuntType) {
+        let o1precedence = match &shunt_type {
+            ShuntType::Operator { precedence, .. } => *precedence,
+            _ => panic!("Not a binary operator"),
+        };
+        while let Some((_, operator2)) = self.operator_stack.last() {
+            match operator2 {
+                ShuntType::Operand => panic!("Operand in operator stack"),
+                ShuntType::Operator { associativity: o2associativity, precedence: o2precedence } => {
+                    if *o2precedence > o1precedence || *o2precedence == o1precedence && o2associativity == &Associativity::Left {
+                        let (token, _) = self.operator_stack.pop().unwrap();
+                        self.output_queue.push_back(token);
+                    } else {
+                        break;
+                    }
+                }
+                ShuntType::Prefix { precedence: o2precedence } => {
+                    if *o2precedence >= o1precedence {
+                        let (token, _) = self.operator_stack.pop().unwrap();
+                        self.output_queue.push_back(token);
+                    } else {
                         break;
                     }
-                    let (token, _) = self.operator_stack.pop().unwrap();
-                    self.output_queue.push_back(token);
-                    // TODO, should stop on '(', also understand where that goes... not disposed here
-
-                    //while the operator at the top of the operator stack is not a left parenthesis:
-                    //             {assert the operator stack is not empty}
-                    //             /* If the stack runs out without finding a left parenthesis, then there are mismatched parentheses. */
-                    //             pop the operator from the operator stack into the output queue
-                    //         {assert there is a left parenthesis at the top of the operator stack}
-                    //         pop the left parenthesis from the operator stack and discard it
-                    //         if there is a function token at the top of the operator stack, then:
-                    //             pop the function from the operator stack into the output queue
                 }
+                ShuntType::Function | ShuntType::OpenBrace => {
+                    break;
+                }
+                ShuntType::Separator => panic!("Separator in operator stack"),
+                ShuntType::CloseBrace => panic!("CloseBrace in operator stack"),
+            }
+        }
+        self.operator_stack.push((token, shunt_type));
+    }
+
+    /// Moves operators to the output until the innermost open brace, which is left on the stack
+    fn pop_to_open_brace(&mut self) {
+        while let Some((_, operator2)) = self.operator_stack.last() {
+            if let ShuntType::OpenBrace = operator2 {
+                break;
             }
+            let (token, _) = self.operator_stack.pop().unwrap();
+            self.output_queue.push_back(token);
         }
     }
 
@@ -195,8 +278,11 @@ mod shunting_yard_tests {
                 "+" => ShuntType::Operator { associativity: Associativity::Left, precedence: 0 },
                 "-" => ShuntType::Operator { associativity: Associativity::Left, precedence: 0 },
                 "*" => ShuntType::Operator { associativity: Associativity::Left, precedence: 1 },
+                "~" => ShuntType::Prefix { precedence: 2 },
                 "(" => ShuntType::OpenBrace,
                 ")" => ShuntType::CloseBrace,
+                "," => ShuntType::Separator,
+                "f" => ShuntType::Function,
                 _ => ShuntType::Operand
             }
         }
@@ -204,6 +290,13 @@ mod shunting_yard_tests {
         fn operand_separator() -> Option<Self> {
             Some("*")
         }
+
+        fn close(self, _arguments: usize) -> Option<Self> {
+            match self {
+                "f" => Some(self),
+                _ => None,
+            }
+        }
     }
 
     #[test]
@@ -323,4 +416,95 @@ mod shunting_yard_tests {
         yard.push("4");
         assert_eq!(vec!["3", "4", "*"], yard.into_vec());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn automatic_multiplication_before_brace() {
+        let mut yard = ShuntingYard::new();
+        yard.push("2");
+        yard.push("(");
+        yard.push("3");
+        yard.push("+");
+        yard.push("4");
+        yard.push(")");
+        assert_eq!(vec!["2", "3", "4", "+", "*"], yard.into_vec());
+    }
+
+    #[test]
+    fn prefix_operator() {
+        let mut yard = ShuntingYard::new();
+        yard.push("~");
+        yard.push("1");
+        yard.push("*");
+        yard.push("2");
+        assert_eq!(vec!["1", "~", "2", "*"], yard.into_vec());
+    }
+
+    #[test]
+    fn prefix_operator_after_binary_operator() {
+        let mut yard = ShuntingYard::new();
+        yard.push("1");
+        yard.push("+");
+        yard.push("~");
+        yard.push("2");
+        assert_eq!(vec!["1", "2", "~", "+"], yard.into_vec());
+    }
+
+    #[test]
+    fn function() {
+        let mut yard = ShuntingYard::new();
+        yard.push("f");
+        yard.push("(");
+        yard.push("1");
+        yard.push(")");
+        assert_eq!(vec!["1", "f"], yard.into_vec());
+    }
+
+    #[test]
+    fn function_with_expression_arguments() {
+        let mut yard = ShuntingYard::new();
+        yard.push("f");
+        yard.push("(");
+        yard.push("1");
+        yard.push("+");
+        yard.push("2");
+        yard.push(",");
+        yard.push("3");
+        yard.push("*");
+        yard.push("4");
+        yard.push(")");
+        assert_eq!(vec!["1", "2", "+", "3", "4", "*", "f"], yard.into_vec());
+    }
+
+    #[test]
+    fn function_inside_expression() {
+        let mut yard = ShuntingYard::new();
+        yard.push("1");
+        yard.push("+");
+        yard.push("f");
+        yard.push("(");
+        yard.push("2");
+        yard.push(")");
+        yard.push("*");
+        yard.push("3");
+        assert_eq!(vec!["1", "2", "f", "3", "*", "+"], yard.into_vec());
+    }
+
+    #[test]
+    fn unmatched_close_brace() {
+        let mut yard = ShuntingYard::new();
+        yard.push("1");
+        yard.push(")");
+        assert_eq!(vec!["1", ")"], yard.into_vec());
+    }
+
+    #[test]
+    fn automatic_multiplication_before_function() {
+        let mut yard = ShuntingYard::new();
+        yard.push("2");
+        yard.push("f");
+        yard.push("(");
+        yard.push("3");
+        yard.push(")");
+        assert_eq!(vec!["2", "3", "f", "*"], yard.into_vec());
+    }
+}
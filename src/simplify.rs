@@ -0,0 +1,242 @@
+use crate::expression::Expression;
+use crate::math::{One, Zero};
+use crate::number_theory;
+use crate::rational::Rational;
+use crate::tokenizer::Token;
+
+/// Factors with their exponents, those with negative exponents being in the denominator
+type Factors = Vec<(Expression, Expression)>;
+
+/// A product as a number times other factors, each base once with its exponents added up
+struct Product {
+    coefficient: Rational,
+    factors: Factors,
+}
+
+/// Numbers first, then names and functions, so that `y*x` and `x*y` are the same term
+fn order(base: &Expression) -> (u8, String) {
+    let rank = match base {
+        Expression::Number(_) => 0,
+        Expression::Symbol(_) => 1,
+        Expression::Function(..) => 2,
+        _ => 3,
+    };
+    (rank, base.to_string())
+}
+
+impl Product {
+    fn of(expression: Expression) -> Product {
+        let mut product = Product { coefficient: Rational::one(), factors: vec![] };
+        product.collect(expression, false);
+        product.factors.retain(|(_, exponent)| !exponent.is_number(0));
+        product.factors.sort_by_cached_key(|(base, _)| order(base));
+        product
+    }
+
+    /// Adds the factors of `expression`, or of its inverse when it is in the denominator
+    fn collect(&mut self, expression: Expression, inverse: bool) {
+        match expression {
+            Expression::Number(number) if !inverse => self.coefficient = self.coefficient.clone() * number,
+            Expression::Number(number) if number != Rational::zero() => self.coefficient = self.coefficient.clone() / number,
+            Expression::Negate(operand) => {
+                self.coefficient = -self.coefficient.clone();
+                self.collect(*operand, inverse);
+            }
+            Expression::Binary(Token::Multiply, left, right) => {
+                self.collect(*left, inverse);
+                self.collect(*right, inverse);
+            }
+            Expression::Binary(Token::Divide, left, right) => {
+                self.collect(*left, inverse);
+                self.collect(*right, !inverse);
+            }
+            // `(x^a)^n` is `x^(a*n)` for integers n
+            Expression::Binary(Token::Power, base, exponent) => match *base {
+                Expression::Binary(Token::Power, inner, inner_exponent) if matches!(&*exponent, Expression::Number(n) if n.as_integer().is_some()) =>
+                    self.factor(*inner, inner_exponent.times(*exponent), inverse),
+                base => self.factor(base, *exponent, inverse),
+            },
+            // Common factors of the terms of a sum come out, so that `(2*x + 4)/2` cancels to `x + 2`
+            sum @ Expression::Binary(Token::Plus | Token::Minus, ..) => {
+                let (content, rest) = Sum::of(sum).content();
+                self.collect(Expression::Number(content), inverse);
+                match rest {
+                    sum @ Expression::Binary(Token::Plus | Token::Minus, ..) => self.factor(sum, Expression::number(1), inverse),
+                    rest => self.collect(rest, inverse),
+                }
+            }
+            expression => self.factor(expression, Expression::number(1), inverse),
+        }
+    }
+
+    fn factor(&mut self, base: Expression, exponent: Expression, inverse: bool) {
+        let exponent = if inverse { exponent.negate() } else { exponent };
+        match self.factors.iter_mut().find(|(factor, _)| *factor == base) {
+            Some((_, total)) => *total = std::mem::replace(total, Expression::number(0)).plus(exponent),
+            None => self.factors.push((base, exponent)),
+        }
+    }
+
+    /// The product as a fraction, with the numbers in front of the numerator and denominator
+    fn expression(coefficient: Rational, factors: Factors) -> Expression {
+        let (numerator, denominator) = coefficient.parts();
+        let (mut above, mut below) = (Expression::Number(numerator), Expression::Number(denominator));
+        for (base, exponent) in factors {
+            match exponent.negated() {
+                Some(exponent) => below = below.times(base.power(exponent)),
+                None => above = above.times(base.power(exponent)),
+            }
+        }
+        above.over(below)
+    }
+
+    fn into_expression(self) -> Expression {
+        Product::expression(self.coefficient, self.factors)
+    }
+}
+
+/// A sum as the coefficients of its distinct terms, the constant being the term without factors
+struct Sum {
+    terms: Vec<(Rational, Factors)>,
+}
+
+impl Sum {
+    fn of(expression: Expression) -> Sum {
+        let mut sum = Sum { terms: vec![] };
+        sum.collect(expression, false);
+        sum.terms.retain(|(coefficient, _)| *coefficient != Rational::zero());
+        sum
+    }
+
+    fn collect(&mut self, expression: Expression, negative: bool) {
+        match expression {
+            Expression::Binary(Token::Plus, left, right) => {
+                self.collect(*left, negative);
+                self.collect(*right, negative);
+            }
+            Expression::Binary(Token::Minus, left, right) => {
+                self.collect(*left, negative);
+                self.collect(*right, !negative);
+            }
+            Expression::Negate(operand) => self.collect(*operand, !negative),
+            term => {
+                let Product { coefficient, factors } = Product::of(term);
+                let coefficient = if negative { -coefficient } else { coefficient };
+                match self.terms.iter_mut().find(|(_, term)| *term == factors) {
+                    Some((total, _)) => *total = total.clone() + coefficient,
+                    None => self.terms.push((coefficient, factors)),
+                }
+            }
+        }
+    }
+
+    /// The greatest common divisor of integer coefficients, and the sum divided by it
+    fn content(mut self) -> (Rational, Expression) {
+        let content = self.terms.iter()
+            .map(|(coefficient, _)| coefficient.clone())
+            .try_fold(Rational::zero(), number_theory::gcd)
+            .unwrap_or_else(|_| Rational::one());
+        if content > Rational::one() {
+            self.terms.iter_mut().for_each(|(coefficient, _)| *coefficient = coefficient.clone() / content.clone());
+            return (content, self.expression());
+        }
+        (Rational::one(), self.expression())
+    }
+
+    /// The terms in the order they first appeared, with the constant last
+    fn expression(self) -> Expression {
+        let (constants, terms): (Vec<_>, Vec<_>) = self.terms.into_iter().partition(|(_, factors)| factors.is_empty());
+        terms.into_iter().chain(constants)
+            .map(|(coefficient, factors)| Product::expression(coefficient, factors))
+            .fold(Expression::number(0), Expression::plus)
+    }
+}
+
+impl Expression {
+    /// Folds constants, removes identities such as `x*1`, `x + 0` and `x^1`, combines like terms
+    /// and powers of the same base, and cancels common factors of fractions. Factors cancel even
+    /// where they may be zero, so `x/x` is 1.
+    pub fn simplify(self) -> Expression {
+        match self {
+            Expression::Negate(operand) => Sum::of(operand.simplify().negate()).expression(),
+            Expression::Binary(t, left, right) => {
+                let (left, right) = (left.simplify(), right.simplify());
+                match t {
+                    Token::Plus => Sum::of(left.plus(right)).expression(),
+                    Token::Minus => Sum::of(left.minus(right)).expression(),
+                    Token::Multiply => Product::of(left.times(right)).into_expression(),
+                    Token::Divide => Product::of(left.over(right)).into_expression(),
+                    Token::Power => Product::of(left.power(right)).into_expression(),
+                    t => Expression::binary(t, left, right),
+                }
+            }
+            Expression::Function(name, arguments) => {
+                let mut arguments: Vec<Expression> = arguments.into_iter().map(Expression::simplify).collect();
+                match arguments.len() {
+                    1 => Expression::function(&name, arguments.remove(0)),
+                    _ => Expression::Function(name, arguments),
+                }
+            }
+            expression => expression,
+        }
+    }
+}
+
+#[cfg(test)]
+mod simplify_tests {
+    use super::*;
+
+    fn simplified(expression: &str) -> String {
+        Expression::parse(expression).unwrap().simplify().to_string()
+    }
+
+    #[test]
+    fn constants_and_identities() {
+        assert_eq!("7", simplified("1 + 2*3"));
+        assert_eq!("3/10", simplified("0.1 + 0.2"));
+        assert_eq!("x", simplified("(x*1 + 0)^1"));
+        assert_eq!("0", simplified("0*sin(x)"));
+        assert_eq!("1", simplified("ln(e)"));
+        assert_eq!("x - 1", simplified("-(1 - x)"));
+    }
+
+    #[test]
+    fn like_terms() {
+        assert_eq!("5*x", simplified("2x + 3x"));
+        assert_eq!("3*x*y + 2", simplified("x*y + 2*y*x + 5 - 3"));
+        assert_eq!("x^2 - x", simplified("x^2 + x - 2x"));
+        assert_eq!("0", simplified("sin(x) - sin(x)"));
+        assert_eq!("x/2", simplified("x/4 + x/4"));
+        assert_eq!("x^5", simplified("x^2 * x^3"));
+        assert_eq!("x^6", simplified("(x^2)^3"));
+    }
+
+    #[test]
+    fn common_factors() {
+        assert_eq!("x^2", simplified("x^3/x"));
+        assert_eq!("1/(2*x)", simplified("2x/(4x^2)"));
+        assert_eq!("y", simplified("(x + 1)*y/(x + 1)"));
+        assert_eq!("x + 2", simplified("(2x + 4)/2"));
+        assert_eq!("2", simplified("(2x + 4)/(x + 2)"));
+        assert_eq!("x/0", simplified("x/0"));
+    }
+
+    #[test]
+    fn readable_derivatives() {
+        let derivative = |expression: &str| Expression::parse(expression).unwrap().derivative("x").unwrap().simplify().to_string();
+        assert_eq!("1/x", derivative("ln(2x)"));
+        assert_eq!("x^x*(ln(x) + 1)", derivative("x^x"));
+        assert_eq!("x^2*cos(x) + 2*x*sin(x)", derivative("sin(x) * x^2"));
+        assert_eq!("1/(2*x^(1/2))", derivative("2√x"));
+        assert_eq!("2*(x + 1)", derivative("(x + 1)^2"));
+        assert_eq!("3", derivative("x^2 - x*x + 3x"));
+    }
+
+    #[test]
+    fn idempotent() {
+        for expression in ["x^x*(ln(x) + 1)", "3*x*y + 2", "1/(2*x)", "x^2*cos(x) + 2*x*sin(x)", "(x + 1)^2 - 1"] {
+            let once = Expression::parse(expression).unwrap().simplify();
+            assert_eq!(once.clone(), once.simplify());
+        }
+    }
+}
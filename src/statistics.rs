@@ -0,0 +1,185 @@
+use std::cmp::Ordering;
+
+use num::BigInt;
+
+use crate::error::CalculatorError;
+use crate::math::Math;
+use crate::rounding::Rounding;
+use crate::value::Value;
+
+/// The numbers a statistics function is given, as a list of arguments, vectors or both, so that
+/// `mean(1, 2, 3)` and `mean([1, 2, 3])` are the same
+pub(crate) fn values<T: Math<T>>(name: &str, arguments: Vec<Value<T>>) -> Result<Vec<T>, CalculatorError> {
+    let mut values = vec![];
+    for argument in arguments {
+        match argument {
+            Value::Scalar(value) => values.push(value),
+            Value::Vector(vector) => values.extend(vector.into_elements()),
+            argument => return Err(CalculatorError::UnsupportedOperation(format!("{}({})", name, argument.kind()))),
+        }
+    }
+    if values.is_empty() {
+        return Err(CalculatorError::UnsupportedOperation(format!("{} of no values", name)));
+    }
+    Ok(values)
+}
+
+/// Calculates the statistic `name` of `values`, which are not empty
+pub(crate) fn calculate<T: Math<T>>(name: &str, values: Vec<T>) -> Result<T, CalculatorError> {
+    match name {
+        "sum" => sum(values),
+        "mean" => mean(values),
+        "median" => median(sorted(name, values)?),
+        "mode" => Ok(mode(sorted(name, values)?)),
+        "min" => Ok(sorted(name, values)?.swap_remove(0)),
+        "max" => Ok(sorted(name, values)?.pop().expect("values are not empty")),
+        "range" => {
+            let values = sorted(name, values)?;
+            values[values.len() - 1].clone().checked_minus(values[0].clone())
+        }
+        "var" => variance(values, true),
+        "varp" => variance(values, false),
+        "stdev" => Ok(variance(values, true)?.sqrt()),
+        "stdevp" => Ok(variance(values, false)?.sqrt()),
+        _ => Err(CalculatorError::UnknownFunction(name.to_string())),
+    }
+}
+
+fn count<T: Math<T>>(count: usize) -> Result<T, CalculatorError> {
+    T::from_big_integer(BigInt::from(count))
+}
+
+fn sum<T: Math<T>>(values: Vec<T>) -> Result<T, CalculatorError> {
+    values.into_iter().try_fold(T::zero(), T::checked_plus)
+}
+
+fn mean<T: Math<T>>(values: Vec<T>) -> Result<T, CalculatorError> {
+    let n = count(values.len())?;
+    sum(values)?.checked_div(n)
+}
+
+/// Sorts real numbers, the statistics that need an order are not defined for complex numbers
+fn sorted<T: Math<T>>(name: &str, mut values: Vec<T>) -> Result<Vec<T>, CalculatorError> {
+    let mut unordered = false;
+    values.sort_by(|a, b| a.compare(b).unwrap_or_else(|| {
+        unordered = true;
+        Ordering::Equal
+    }));
+    // A single value is never compared
+    if unordered || values.iter().any(|value| value.compare(value).is_none()) {
+        return Err(CalculatorError::Unordered(name.to_string()));
+    }
+    Ok(values)
+}
+
+/// The middle value, or the mean of the two middle values of an even number of values
+fn median<T: Math<T>>(mut values: Vec<T>) -> Result<T, CalculatorError> {
+    let middle = values.len() / 2;
+    if values.len() % 2 == 1 {
+        return Ok(values.swap_remove(middle));
+    }
+    mean(vec![values[middle - 1].clone(), values[middle].clone()])
+}
+
+/// The most frequent value, the smallest of them if there is a tie
+fn mode<T: Math<T>>(values: Vec<T>) -> T {
+    let mut best: Option<(T, usize)> = None;
+    let mut values = values.into_iter().peekable();
+    while let Some(value) = values.next() {
+        let mut frequency = 1;
+        while values.next_if(|next| next.compare(&value) == Some(Ordering::Equal)).is_some() {
+            frequency += 1;
+        }
+        if best.as_ref().is_none_or(|(_, most)| frequency > *most) {
+            best = Some((value, frequency));
+        }
+    }
+    best.expect("values are not empty").0
+}
+
+/// The mean squared deviation from the mean, divided by n - 1 for a `sample` rather than by n for
+/// a whole population
+fn variance<T: Math<T>>(values: Vec<T>, sample: bool) -> Result<T, CalculatorError> {
+    let n = values.len();
+    if sample && n < 2 {
+        return Err(CalculatorError::UnsupportedOperation("sample variance of a single value".to_string()));
+    }
+    let mean = mean(values.clone())?;
+    let squares = values.into_iter()
+        .map(|value| {
+            let deviation = value.checked_minus(mean.clone())?;
+            deviation.clone().checked_times(deviation)
+        })
+        .collect::<Result<Vec<T>, CalculatorError>>()?;
+    sum(squares)?.checked_div(count(if sample { n - 1 } else { n })?)
+}
+
+/// The value below which `p` percent of values fall, interpolating linearly between the two
+/// nearest values as spreadsheets do
+pub(crate) fn percentile<T: Math<T>>(p: T, values: Vec<T>) -> Result<T, CalculatorError> {
+    let values = sorted("percentile", values)?;
+    let (zero, hundred) = (T::zero(), count::<T>(100)?);
+    if p.compare(&zero) == Some(Ordering::Less) || p.compare(&hundred) == Some(Ordering::Greater) || p.compare(&p).is_none() {
+        return Err(CalculatorError::UnsupportedOperation("percentile outside 0 to 100".to_string()));
+    }
+    let position = p.checked_times(count(values.len() - 1)?)?.checked_div(hundred)?;
+    let below = position.clone().round_to(0, Rounding::Floor)?;
+    let index = below.to_integer().expect("positions are integers after rounding down") as usize;
+    match values.get(index + 1) {
+        Some(above) => {
+            let step = above.clone().checked_minus(values[index].clone())?.checked_times(position - below)?;
+            values[index].clone().checked_plus(step)
+        }
+        None => Ok(values[index].clone()),
+    }
+}
+
+#[cfg(test)]
+mod statistics_tests {
+    use super::*;
+
+    #[test]
+    fn averages() {
+        assert_eq!(Ok(2.5), calculate("mean", vec![1f64, 2f64, 3f64, 4f64]));
+        assert_eq!(Ok(3f64), calculate("median", vec![5f64, 1f64, 3f64]));
+        assert_eq!(Ok(2.5), calculate("median", vec![4f64, 1f64, 3f64, 2f64]));
+        assert_eq!(Ok(2f64), calculate("mode", vec![3f64, 2f64, 1f64, 2f64, 3f64]));
+        assert_eq!(Ok(7f64), calculate("mode", vec![7f64]));
+    }
+
+    #[test]
+    fn spread() {
+        let values = vec![2f64, 4f64, 4f64, 4f64, 5f64, 5f64, 7f64, 9f64];
+        assert_eq!(Ok(4f64), calculate("varp", values.clone()));
+        assert_eq!(Ok(2f64), calculate("stdevp", values.clone()));
+        assert_eq!(Ok(32f64 / 7f64), calculate("var", values.clone()));
+        assert_eq!(Ok(7f64), calculate("range", values));
+        assert!(calculate("var", vec![1f64]).is_err());
+        assert_eq!(Ok(0f64), calculate("varp", vec![1f64]));
+    }
+
+    #[test]
+    fn percentiles() {
+        let values = vec![15f64, 20f64, 35f64, 40f64, 50f64];
+        assert_eq!(Ok(15f64), percentile(0f64, values.clone()));
+        assert_eq!(Ok(35f64), percentile(50f64, values.clone()));
+        assert_eq!(Ok(29f64), percentile(40f64, values.clone()));
+        assert_eq!(Ok(50f64), percentile(100f64, values.clone()));
+        assert!(percentile(101f64, values).is_err());
+    }
+
+    #[test]
+    fn order_needs_real_numbers() {
+        assert_eq!(Err(CalculatorError::Unordered("max".to_string())), calculate("max", vec![1f64, f64::NAN]));
+        assert_eq!(Err(CalculatorError::Unordered("min".to_string())), calculate("min", vec![f64::NAN]));
+    }
+
+    #[test]
+    fn integer_overflow() {
+        assert_eq!(Err(CalculatorError::Overflow), calculate("sum", vec![i32::MAX, 1]));
+        assert_eq!(Err(CalculatorError::Overflow), calculate("mean", vec![i32::MAX, 1]));
+        assert_eq!(Err(CalculatorError::Overflow), calculate("var", vec![i32::MAX, -i32::MAX]));
+        assert_eq!(Err(CalculatorError::Overflow), calculate("range", vec![i32::MAX, -i32::MAX]));
+        assert_eq!(Ok(i32::MAX), calculate("sum", vec![i32::MAX - 1, 1]));
+    }
+}
@@ -0,0 +1,379 @@
+use std::fmt::{Display, Formatter};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::CalculatorError;
+use crate::tokenizer::Token;
+
+const MINUTE: i64 = 60;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+
+/// The forms of date and time literals, where `D` is a digit and `±` a sign. A date-time may have
+/// a fixed offset from UTC, `Z` for UTC itself.
+const LITERALS: &[&str] = &[
+    "DDDD-DD-DD",
+    "DDDD-DD-DDTDD:DD",
+    "DDDD-DD-DDTDD:DDZ",
+    "DDDD-DD-DDTDD:DD±DD:DD",
+    "DDDD-DD-DDTDD:DD:DD",
+    "DDDD-DD-DDTDD:DD:DDZ",
+    "DDDD-DD-DDTDD:DD:DD±DD:DD",
+];
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+/// From 1970-01-01, a Thursday
+const WEEKDAYS: [&str; 7] = ["Thursday", "Friday", "Saturday", "Sunday", "Monday", "Tuesday", "Wednesday"];
+
+/// The units of a compound duration such as `3h 25m`, largest first, where `m` is minutes
+const DURATION_UNITS: &[(&str, &str)] = &[("d", "d"), ("h", "h"), ("m", "min"), ("min", "min"), ("s", "s")];
+
+fn matches(literal: &[char], form: &str) -> bool {
+    literal.len() <= form.chars().count() && literal.iter().zip(form.chars()).all(|(c, form)| match form {
+        'D' => c.is_ascii_digit(),
+        '±' => *c == '+' || *c == '-',
+        form => *c == form,
+    })
+}
+
+/// Whether `literal` is the start of a date or time literal, so the tokenizer reads on
+pub(crate) fn is_prefix(literal: &[char]) -> bool {
+    LITERALS.iter().any(|form| matches(literal, form))
+}
+
+/// Date and time literals, and `today` and `now`
+pub(crate) fn is_literal(literal: &str) -> bool {
+    let chars: Vec<char> = literal.chars().collect();
+    is_keyword(literal) || LITERALS.iter().any(|form| form.chars().count() == chars.len() && matches(&chars, form))
+}
+
+pub(crate) fn is_keyword(name: &str) -> bool {
+    name == "today" || name == "now"
+}
+
+/// The unit and number of a name such as `h30`, which is how the tokenizer reads the middle of
+/// `2h30m`
+fn split_compact(name: &str) -> Option<(&str, &str)> {
+    let digits = name.find(|c: char| c.is_ascii_digit())?;
+    let (unit, number) = name.split_at(digits);
+    (DURATION_UNITS.iter().any(|(name, _)| *name == unit) && number.chars().all(|c| c.is_ascii_digit()))
+        .then_some((unit, number))
+}
+
+/// Separates compact durations, `2h30m`, into numbers and units, `2 h 30 m`, where a name such as
+/// `h30` comes after a number and before a unit or another such name
+fn separate(tokens: impl Iterator<Item=Token>) -> Vec<Token> {
+    let tokens: Vec<Token> = tokens.collect();
+    let mut result: Vec<Token> = vec![];
+    for (index, token) in tokens.iter().enumerate() {
+        let after_number = matches!(result.last(), Some(Token::T(number)) if number.starts_with(|c: char| c.is_ascii_digit()));
+        let before_unit = match tokens.get(index + 1) {
+            Some(Token::Unit(_)) => true,
+            Some(Token::T(name)) => split_compact(name).is_some(),
+            _ => false,
+        };
+        match token {
+            Token::T(name) if after_number && before_unit => match split_compact(name) {
+                Some((unit, number)) => result.extend([Token::Unit(unit.to_string()), Token::T(number.to_string())]),
+                None => result.push(token.clone()),
+            },
+            token => result.push(token.clone()),
+        }
+    }
+    result
+}
+
+/// Rewrites compound durations, `3h 25m` or `3h25m`, as sums, `(3 h + 25 min)`. A run of at least
+/// two numbers with units of time from largest to smallest is a duration, so `m` means minutes in
+/// `3h 25m` but metres in `25m`.
+pub(crate) fn durations(tokens: impl Iterator<Item=Token>) -> Vec<Token> {
+    let tokens = separate(tokens);
+    let rank = |unit: &str| DURATION_UNITS.iter().position(|(name, _)| *name == unit);
+    let mut result = vec![];
+    let mut index = 0;
+    while index < tokens.len() {
+        let mut run: Vec<(&String, &'static str)> = vec![];
+        let mut last = None;
+        while let (Some(Token::T(number)), Some(Token::Unit(unit))) = (tokens.get(index + 2 * run.len()), tokens.get(index + 2 * run.len() + 1)) {
+            match rank(unit) {
+                Some(rank) if number.starts_with(|c: char| c.is_ascii_digit()) && last.is_none_or(|last| rank > last) => {
+                    run.push((number, DURATION_UNITS[rank].1));
+                    // `m` and `min` are the same unit
+                    last = Some(if DURATION_UNITS[rank].1 == "min" { 3 } else { rank });
+                }
+                _ => break,
+            }
+        }
+        if run.len() < 2 {
+            result.push(tokens[index].clone());
+            index += 1;
+            continue;
+        }
+        index += 2 * run.len();
+        result.push(Token::OpenBrace);
+        for (position, (number, unit)) in run.into_iter().enumerate() {
+            if position > 0 {
+                result.push(Token::Plus);
+            }
+            result.push(Token::T(number.clone()));
+            result.push(Token::Unit(unit.to_string()));
+        }
+        result.push(Token::CloseBrace);
+    }
+    result
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The year, month and day of a number of days since 1970-01-01
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    days_from_civil(year + month / 12, month % 12 + 1, 1) - days_from_civil(year, month, 1)
+}
+
+/// An offset from UTC as a conversion target, `UTC`, `Z`, `UTC+2`, `+05:30` or `-08:00`, in minutes
+pub(crate) fn parse_offset(target: &str) -> Option<i32> {
+    let offset = match target.strip_prefix("UTC") {
+        Some("") => return Some(0),
+        Some(offset) => offset,
+        None if target == "Z" => return Some(0),
+        None => target,
+    };
+    let sign = match offset.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+    let (hours, minutes): (i32, i32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours <= 14 && minutes < 60).then_some(sign * (hours * 60 + minutes))
+}
+
+/// A point in time, a date or a date and time, shown at a fixed offset from UTC
+#[derive(Clone, Debug, PartialEq)]
+pub struct DateTime {
+    /// Seconds since 1970-01-01T00:00:00Z
+    seconds: i64,
+    /// Minutes ahead of UTC
+    offset: i32,
+    /// A date without a time, which stays a date while whole days are added to it
+    date_only: bool,
+}
+
+impl DateTime {
+    /// Reads a date or time literal, or `today` or `now` in UTC from the system clock
+    pub fn parse(literal: &str) -> Result<Self, CalculatorError> {
+        let invalid = || CalculatorError::Parse(literal.to_string());
+        if is_keyword(literal) {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|_| invalid())?.as_secs() as i64;
+            return Ok(match literal {
+                "today" => DateTime { seconds: now - now.rem_euclid(DAY), offset: 0, date_only: true },
+                _ => DateTime { seconds: now, offset: 0, date_only: false },
+            });
+        }
+        if !is_literal(literal) {
+            return Err(invalid());
+        }
+        let number = |range: std::ops::Range<usize>| literal.get(range).map_or(Ok(0), |digits| digits.parse::<i64>().map_err(|_| invalid()));
+        let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+        let (hour, minute) = (number(11..13)?, number(14..16)?);
+        let second = if literal.get(16..17) == Some(":") { number(17..19)? } else { 0 };
+        let offset = match literal.find(['Z', '+']).or_else(|| literal.rfind('-').filter(|index| *index > 10)) {
+            Some(index) => parse_offset(&literal[index..]).ok_or_else(invalid)?,
+            None => 0,
+        };
+        if !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+            return Err(invalid());
+        }
+        let local = days_from_civil(year, month, day) * DAY + hour * HOUR + minute * MINUTE + second;
+        Ok(DateTime { seconds: local - offset as i64 * MINUTE, offset, date_only: literal.len() == 10 })
+    }
+
+    /// Adds a number of seconds, a date stays a date if they are whole days
+    pub fn add(self, seconds: i64) -> Result<Self, CalculatorError> {
+        Ok(DateTime {
+            seconds: self.seconds.checked_add(seconds).ok_or(CalculatorError::Overflow)?,
+            date_only: self.date_only && seconds % DAY == 0,
+            ..self
+        })
+    }
+
+    /// The number of seconds from `earlier` to this
+    pub fn since(&self, earlier: &Self) -> Result<i64, CalculatorError> {
+        self.seconds.checked_sub(earlier.seconds).ok_or(CalculatorError::Overflow)
+    }
+
+    /// The same point in time at another offset from UTC, with its time shown
+    pub fn with_offset(self, offset: i32) -> Self {
+        DateTime { offset, date_only: false, ..self }
+    }
+
+    /// The days since 1970-01-01 and seconds since midnight, at its offset
+    fn local(&self) -> (i64, i64) {
+        let local = self.seconds + self.offset as i64 * MINUTE;
+        (local.div_euclid(DAY), local.rem_euclid(DAY))
+    }
+
+    /// In words, `Saturday 17 October 2026` or `Saturday 17 October 2026 14:30`
+    pub fn human(&self) -> String {
+        let (days, time) = self.local();
+        let (year, month, day) = civil_from_days(days);
+        let date = format!("{} {} {} {}", WEEKDAYS[days.rem_euclid(7) as usize], day, MONTHS[month as usize - 1], year);
+        if self.date_only {
+            return date;
+        }
+        format!("{} {:02}:{:02}", date, time / HOUR, time % HOUR / MINUTE)
+    }
+}
+
+/// In ISO 8601, `2026-10-17` or `2026-10-17T14:30:00+02:00`
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (days, time) = self.local();
+        let (year, month, day) = civil_from_days(days);
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)?;
+        if self.date_only {
+            return Ok(());
+        }
+        write!(f, "T{:02}:{:02}:{:02}", time / HOUR, time % HOUR / MINUTE, time % MINUTE)?;
+        match self.offset {
+            0 => f.write_str("Z"),
+            offset => write!(f, "{}{:02}:{:02}", if offset < 0 { '-' } else { '+' }, offset.abs() / 60, offset.abs() % 60),
+        }
+    }
+}
+
+/// The days, hours, minutes and seconds of a duration, leaving out those that are 0
+fn duration_parts(seconds: i64) -> Vec<(i64, &'static str)> {
+    let seconds = seconds.abs();
+    [(seconds / DAY, "day"), (seconds % DAY / HOUR, "hour"), (seconds % HOUR / MINUTE, "minute"), (seconds % MINUTE, "second")]
+        .into_iter()
+        .filter(|(count, _)| *count != 0)
+        .collect()
+}
+
+/// A duration in ISO 8601, `P90D` or `PT3H25M`
+pub(crate) fn iso_duration(seconds: i64) -> String {
+    let mut date = String::new();
+    let mut time = String::new();
+    for (count, unit) in duration_parts(seconds) {
+        match unit {
+            "day" => date.push_str(&format!("{}D", count)),
+            unit => time.push_str(&format!("{}{}", count, unit[..1].to_uppercase())),
+        }
+    }
+    let sign = if seconds < 0 { "-" } else { "" };
+    match (date.is_empty(), time.is_empty()) {
+        (_, true) if seconds == 0 => "PT0S".to_string(),
+        (_, true) => format!("{}P{}", sign, date),
+        _ => format!("{}P{}T{}", sign, date, time),
+    }
+}
+
+/// A duration in words, `3 hours 25 minutes`
+pub(crate) fn human_duration(seconds: i64) -> String {
+    let parts: Vec<String> = duration_parts(seconds).into_iter()
+        .map(|(count, unit)| format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" }))
+        .collect();
+    let sign = if seconds < 0 { "-" } else { "" };
+    match parts.is_empty() {
+        true => "0 seconds".to_string(),
+        false => format!("{}{}", sign, parts.join(" ")),
+    }
+}
+
+#[cfg(test)]
+mod temporal_tests {
+    use crate::tokenizer::Tokenize;
+
+    use super::*;
+
+    #[test]
+    fn literals() {
+        let prefix = |literal: &str| is_prefix(&literal.chars().collect::<Vec<char>>());
+        assert!(prefix("2026-1"));
+        assert!(prefix("2026-10-17T14:30+0"));
+        assert!(!prefix("2026-10-17-"));
+        assert!(is_literal("2026-10-17"));
+        assert!(is_literal("2026-10-17T14:30:15Z"));
+        assert!(is_literal("today"));
+        assert!(!is_literal("2026-10"));
+    }
+
+    #[test]
+    fn dates() {
+        let date = DateTime::parse("2026-10-17").unwrap();
+        assert_eq!("2026-10-17", date.to_string());
+        assert_eq!("Saturday 17 October 2026", date.human());
+        assert_eq!("2027-01-15", date.clone().add(90 * DAY).unwrap().to_string());
+        assert_eq!("2026-10-17T12:00:00Z", date.clone().add(12 * HOUR).unwrap().to_string());
+        assert_eq!("2024-02-29", DateTime::parse("2024-02-29").unwrap().to_string());
+        assert_eq!("1969-12-31", DateTime::parse("1970-01-01").unwrap().add(-DAY).unwrap().to_string());
+        for invalid in ["2026-02-29", "2026-13-01", "2026-10-17T24:00", "2026-10-17T12:00+15:00"] {
+            assert_eq!(Err(CalculatorError::Parse(invalid.to_string())), DateTime::parse(invalid));
+        }
+    }
+
+    #[test]
+    fn time_zones() {
+        let time = DateTime::parse("2026-10-17T14:30+02:00").unwrap();
+        assert_eq!("2026-10-17T14:30:00+02:00", time.to_string());
+        assert_eq!("2026-10-17T12:30:00Z", time.clone().with_offset(0).to_string());
+        assert_eq!("2026-10-17T07:30:00-05:00", time.clone().with_offset(-300).to_string());
+        assert_eq!(Ok(2 * HOUR), DateTime::parse("2026-10-17T14:30Z").unwrap().since(&time));
+        assert_eq!(Some(330), parse_offset("+05:30"));
+        assert_eq!(Some(-120), parse_offset("UTC-2"));
+        assert_eq!(Some(0), parse_offset("UTC"));
+        assert_eq!(None, parse_offset("UTC+15"));
+        assert_eq!(None, parse_offset("EUR"));
+    }
+
+    #[test]
+    fn duration_forms() {
+        assert_eq!("PT3H25M", iso_duration(3 * HOUR + 25 * MINUTE));
+        assert_eq!("P90D", iso_duration(90 * DAY));
+        assert_eq!("-P1DT1S", iso_duration(-DAY - 1));
+        assert_eq!("PT0S", iso_duration(0));
+        assert_eq!("3 hours 25 minutes", human_duration(3 * HOUR + 25 * MINUTE));
+        assert_eq!("1 day 1 second", human_duration(DAY + 1));
+    }
+
+    #[test]
+    fn compound_durations() {
+        let rewritten = |expression: &str| durations(expression.chars().tokenize())
+            .iter()
+            .map(|token| token.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        assert_eq!("( 3 h + 25 min ) * 4", rewritten("3h 25m * 4"));
+        assert_eq!("( 1 d + 2 h + 3 min + 4 s )", rewritten("1d 2h 3m 4s"));
+        assert_eq!("25 m", rewritten("25m"));
+        assert_eq!("3 s 25 m", rewritten("3s 25m"));
+        assert_eq!("( 2 h + 30 min )", rewritten("2h30m"));
+        assert_eq!("( 1 d + 2 h + 3 min + 4 s )", rewritten("1d2h3m4s"));
+        assert_eq!("2 h30", rewritten("2h30"));
+    }
+}
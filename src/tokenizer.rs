@@ -2,40 +2,150 @@ use core::fmt;
 use core::fmt::Formatter;
 use std::collections::VecDeque;
 
+use crate::functions::FUNCTIONS;
 use crate::shunting_yard::{Associativity, ShuntingYardToken, ShuntType};
+use crate::temporal;
+use crate::units;
+
+/// The operators of two characters, whose first character may also be an operator on its own
+const TWO_CHARACTER_OPERATORS: &[&str] = &["<<", ">>", "//", "<=", ">=", "==", "!="];
 
 enum Mode {
     None,
     Number,
+    /// After the `e` or `E` of a number, which is only an exponent if digits follow, otherwise it is
+    /// Euler's number, so `2e` is 2×e while `2e3` is 2000
+    Exponent,
+    /// After the sign of an exponent, `2e-3`, which also needs digits to follow
+    ExponentSign,
+    ExponentDigits,
+    /// After the `0x`, `0b` or `0o` prefix of an integer in base 16, 2 or 8, which needs digits to follow
+    RadixPrefix(u32),
+    RadixDigits(u32),
+    /// After an imaginary unit suffix, which is only part of the number if no letters follow
+    Imaginary,
+    /// After the first character of an operator that may have two, `<` of `<<` and `<=`, `/` of `//`
+    /// or `!` of `!=`
+    Operator,
+    /// After a minus, which is negation where an operand is expected unless nothing follows it
+    Minus,
+    /// After the `2026-` of a date, which is read for as long as it may be a date or time literal
+    Temporal,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Token {
     T(String),
+    /// A unit of measurement, e.g. `km`
+    Unit(String),
+    /// A date or time, e.g. `2026-10-17`, `2026-10-17T14:30+02:00` or `today`
+    Temporal(String),
     Plus,
     Minus,
     Multiply,
     Divide,
+    /// Postfix, `x%` is x/100. The remainder is the `mod` keyword so that the two cannot be confused
     Percent,
+    /// `a + b%` in percentage mode, produced from `Plus` after `Percent`
+    PercentIncrease,
+    /// `a - b%` in percentage mode
+    PercentDecrease,
+    /// Euclidean remainder, `a mod b`
+    Modulo,
+    /// Euclidean division, `a // b`
+    Quotient,
     Power,
     Root,
+    /// Polar form, magnitude∠angle
+    Angle,
+    /// An angle in degrees, converted to radians
+    Degree,
+    /// Postfix, `n!`
+    Factorial,
+    BitAnd,
+    BitOr,
+    Xor,
+    /// Prefix bitwise complement, `~x`
+    BitNot,
+    /// Prefix minus, `-x`, where an operand is expected rather than after one
+    Negate,
+    ShiftLeft,
+    ShiftRight,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    /// Equal within the session tolerance, even for exact numbers, `a ≈ b`
+    Approx,
+    /// Logical operators on the booleans comparisons give
+    And,
+    Or,
+    /// Prefix logical negation, `not x`
+    Not,
     OpenBrace,
     CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Separator,
+    /// A function name, and once shunted the number of arguments it is applied to
+    Function(String, usize),
+    /// Collects the given number of values into a vector, produced by the shunting yard from brackets
+    Vector(usize),
+    /// Produced by the shunting yard after the condition of `if`, which skips to `Else` when it is false
+    Then,
+    /// Produced by the shunting yard after the first branch of `if`, which skips the second branch
+    Else,
 }
 
 impl fmt::Display for Token {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Token::T(s) => f.write_str(s),
+            Token::Unit(name) => f.write_str(name),
+            Token::Temporal(literal) => f.write_str(literal),
             Token::Plus => f.write_str("+"),
             Token::Minus => f.write_str("-"),
             Token::Multiply => f.write_str("*"),
             Token::Divide => f.write_str("/"),
             Token::Percent => f.write_str("%"),
+            // As written, the percent sign being on the right operand
+            Token::PercentIncrease => f.write_str("+"),
+            Token::PercentDecrease => f.write_str("-"),
+            Token::Modulo => f.write_str("mod"),
+            Token::Quotient => f.write_str("//"),
             Token::Power => f.write_str("^"),
             Token::Root => f.write_str("√"),
+            Token::Angle => f.write_str("∠"),
+            Token::Degree => f.write_str("°"),
+            Token::Factorial => f.write_str("!"),
+            Token::BitAnd => f.write_str("&"),
+            Token::BitOr => f.write_str("|"),
+            Token::Xor => f.write_str("xor"),
+            Token::BitNot => f.write_str("~"),
+            Token::Negate => f.write_str("-"),
+            Token::ShiftLeft => f.write_str("<<"),
+            Token::ShiftRight => f.write_str(">>"),
+            Token::Equal => f.write_str("=="),
+            Token::NotEqual => f.write_str("!="),
+            Token::Less => f.write_str("<"),
+            Token::LessEqual => f.write_str("<="),
+            Token::Greater => f.write_str(">"),
+            Token::GreaterEqual => f.write_str(">="),
+            Token::Approx => f.write_str("≈"),
+            Token::And => f.write_str("and"),
+            Token::Or => f.write_str("or"),
+            Token::Not => f.write_str("not"),
             Token::OpenBrace => f.write_str("("),
             Token::CloseBrace => f.write_str(")"),
+            Token::OpenBracket => f.write_str("["),
+            Token::CloseBracket => f.write_str("]"),
+            Token::Separator => f.write_str(","),
+            Token::Function(name, _) => f.write_str(name),
+            Token::Vector(_) => f.write_str("[]"),
+            Token::Then => f.write_str("then"),
+            Token::Else => f.write_str("else"),
         }
     }
 }
@@ -44,22 +154,91 @@ struct TokenizerState {
     mode: Mode,
     tokens: VecDeque<Token>,
     current_token: Vec<char>,
+    /// Whether the last token ends an operand, after which a minus is subtraction rather than negation
+    after_operand: bool,
+    /// The character that ends the current node, a name followed by `(` is a function
+    next: Option<char>,
+    /// Whether a lone x may be a name, in the arguments of symbolic functions such as `diff`,
+    /// rather than always multiplication
+    symbolic: bool,
+    /// A lone x after an operand and a space, multiplication if an operand follows as in `2 x 3`,
+    /// otherwise a name as in `3 x + 1`
+    pending_x: bool,
 }
 
 impl TokenizerState {
-    fn new() -> TokenizerState {
+    fn new(symbolic: bool) -> TokenizerState {
         TokenizerState {
             mode: Mode::None,
             tokens: Default::default(),
             current_token: vec![],
+            after_operand: false,
+            next: None,
+            symbolic,
+            pending_x: false,
         }
     }
 
+    #[cfg(test)]
     fn complete(mut self) -> Vec<Token> {
-        self.end_node();
+        self.finish();
         self.tokens.into()
     }
 
+    /// Whether a lone x is multiplication, as always outside symbolic functions and between
+    /// operands in `2x3`, rather than a name, as in `x^2` and `2x + 1`
+    fn x_multiplies(&self) -> bool {
+        !self.symbolic || self.after_operand && self.next.is_some_and(|c| c.is_alphanumeric() || c == '(' || c == '[' || c == '.')
+    }
+
+    /// Decides what a pending x is once the token after it, the last token, is known
+    fn resolve_x(&mut self) {
+        if !std::mem::take(&mut self.pending_x) {
+            return;
+        }
+        let next = self.tokens.pop_back();
+        let operand = matches!(
+            next,
+            Some(Token::T(_) | Token::Unit(_) | Token::Temporal(_) | Token::Function(..) | Token::OpenBrace | Token::OpenBracket)
+        );
+        self.tokens.push_back(if operand { Token::Multiply } else { Token::T("x".to_string()) });
+        self.tokens.extend(next);
+    }
+
+    /// Ends the last node at the end of the input
+    fn finish(&mut self) {
+        self.next = None;
+        match self.mode {
+            Mode::Exponent | Mode::RadixPrefix(_) => self.split_suffix(1),
+            Mode::ExponentSign => self.split_suffix(2),
+            Mode::Minus => self.after_operand = true,
+            Mode::Temporal => self.end_temporal(),
+            _ => {}
+        }
+        self.end_node();
+        if std::mem::take(&mut self.pending_x) {
+            self.tokens.push_back(Token::T("x".to_string()));
+        }
+        self.mode = Mode::None;
+    }
+
+    /// Ends the number before its last `length` characters, which are read again as what follows it
+    fn split_suffix(&mut self, length: usize) {
+        let suffix = self.current_token.split_off(self.current_token.len() - length);
+        self.end_node();
+        self.mode = Mode::None;
+        suffix.into_iter().for_each(|c| self.push_char(c));
+    }
+
+    /// Ends the longest date or time literal read, or the year before it when there is none, so
+    /// that `2026-10` is a subtraction
+    fn end_temporal(&mut self) {
+        let length = (1..=self.current_token.len()).rev()
+            .find(|length| temporal::is_literal(&self.current_token[..*length].iter().collect::<String>()))
+            .unwrap_or_else(|| self.current_token.iter().take_while(|c| c.is_ascii_digit()).count());
+        self.split_suffix(self.current_token.len() - length);
+    }
+
     fn end_node(&mut self) {
         let contents = std::mem::take(&mut self.current_token);
         if !contents.is_empty() {
@@ -67,14 +246,19 @@ impl TokenizerState {
             // TODO: annoying repeat 1/2
             if string.as_str() == "+" {
                 self.tokens.push_back(Token::Plus)
-            } else if string.as_str() == "-" {
+            } else if string.as_str() == "-" && self.after_operand {
                 self.tokens.push_back(Token::Minus)
+            } else if string.as_str() == "-" {
+                self.tokens.push_back(Token::Negate)
             } else if string.as_str() == "(" {
                 self.tokens.push_back(Token::OpenBrace)
             } else if string.as_str() == ")" {
                 self.tokens.push_back(Token::CloseBrace)
-            } else if string.as_str() == "*" || string.as_str() == "x" {
+            } else if string.as_str() == "*" || string.as_str() == "x" && self.x_multiplies() {
                 self.tokens.push_back(Token::Multiply)
+            } else if string.as_str() == "x" && self.after_operand && self.next == Some(' ') {
+                self.pending_x = true;
+                return;
             } else if string.as_str() == "/" {
                 self.tokens.push_back(Token::Divide)
             } else if string.as_str() == "%" {
@@ -83,13 +267,74 @@ impl TokenizerState {
                 self.tokens.push_back(Token::Power)
             } else if string.as_str() == "√" {
                 self.tokens.push_back(Token::Root)
+            } else if string.as_str() == "∠" {
+                self.tokens.push_back(Token::Angle)
+            } else if string.as_str() == "°" {
+                self.tokens.push_back(Token::Degree)
+            } else if string.as_str() == "!" {
+                self.tokens.push_back(Token::Factorial)
+            } else if string.as_str() == "mod" {
+                self.tokens.push_back(Token::Modulo)
+            } else if string.as_str() == "//" {
+                self.tokens.push_back(Token::Quotient)
+            } else if string.as_str() == "&" {
+                self.tokens.push_back(Token::BitAnd)
+            } else if string.as_str() == "|" {
+                self.tokens.push_back(Token::BitOr)
+            } else if string.as_str() == "xor" {
+                self.tokens.push_back(Token::Xor)
+            } else if string.as_str() == "~" {
+                self.tokens.push_back(Token::BitNot)
+            } else if string.as_str() == "<<" {
+                self.tokens.push_back(Token::ShiftLeft)
+            } else if string.as_str() == ">>" {
+                self.tokens.push_back(Token::ShiftRight)
+            } else if string.as_str() == "==" {
+                self.tokens.push_back(Token::Equal)
+            } else if string.as_str() == "!=" {
+                self.tokens.push_back(Token::NotEqual)
+            } else if string.as_str() == "<" {
+                self.tokens.push_back(Token::Less)
+            } else if string.as_str() == "<=" {
+                self.tokens.push_back(Token::LessEqual)
+            } else if string.as_str() == ">" {
+                self.tokens.push_back(Token::Greater)
+            } else if string.as_str() == ">=" {
+                self.tokens.push_back(Token::GreaterEqual)
+            } else if string.as_str() == "≈" {
+                self.tokens.push_back(Token::Approx)
+            } else if string.as_str() == "and" {
+                self.tokens.push_back(Token::And)
+            } else if string.as_str() == "or" {
+                self.tokens.push_back(Token::Or)
+            } else if string.as_str() == "not" {
+                self.tokens.push_back(Token::Not)
+            } else if string.as_str() == "[" {
+                self.tokens.push_back(Token::OpenBracket)
+            } else if string.as_str() == "]" {
+                self.tokens.push_back(Token::CloseBracket)
+            } else if string.as_str() == "," {
+                self.tokens.push_back(Token::Separator)
+            } else if temporal::is_literal(&string) {
+                self.tokens.push_back(Token::Temporal(string))
+            } else if FUNCTIONS.contains(&string.as_str()) && (self.next == Some('(') || !units::is_unit(&string)) {
+                // `min(a, b)` is a function, `5 min` is minutes
+                self.tokens.push_back(Token::Function(string, 0))
+            } else if units::is_unit(&string) {
+                self.tokens.push_back(Token::Unit(string))
             } else {
                 self.tokens.push_back(Token::T(string));
             }
+            self.resolve_x();
+            self.after_operand = matches!(
+                self.tokens.back(),
+                Some(Token::T(_) | Token::Unit(_) | Token::Temporal(_) | Token::CloseBrace | Token::CloseBracket | Token::Percent | Token::Degree | Token::Factorial)
+            );
         }
     }
 
     fn push_char(&mut self, c: char) {
+        self.next = Some(c);
         match self.mode {
             Mode::None => {
                 if c == ' ' {
@@ -97,11 +342,23 @@ impl TokenizerState {
                     return;
                 }
                 // TODO: Annoying repeat 2/2
-                if c == '+' || c == '-' || c == '*' || c == 'x' || c == '/' || c == '%' || c == '^' || c == '√' || c == '(' || c == ')' {
+                if c == '+' || c == '*' || c == '%' || c == '^' || c == '√' || c == '∠' || c == '°' || c == '≈' || c == '&' || c == '|' || c == '~' || c == '(' || c == ')' || c == '[' || c == ']' || c == ',' {
                     self.end_node();
                     self.current_token.push(c);
                     self.end_node();
+                } else if c == '-' {
+                    self.end_node();
+                    self.current_token.push(c);
+                    self.mode = Mode::Minus;
+                } else if c == '<' || c == '>' || c == '/' || c == '=' || c == '!' {
+                    self.end_node();
+                    self.current_token.push(c);
+                    self.mode = Mode::Operator;
                 } else if c.is_numeric() {
+                    // A lone x before a number is multiplication, as in 2x3, elsewhere it may be part of a name
+                    if self.current_token == ['x'] {
+                        self.end_node();
+                    }
                     self.current_token.push(c);
                     self.mode = Mode::Number;
                 } else {
@@ -109,8 +366,112 @@ impl TokenizerState {
                 }
             }
             Mode::Number => {
+                let radix = match c {
+                    'x' => Some(16),
+                    'b' => Some(2),
+                    'o' => Some(8),
+                    _ => None,
+                };
                 if c.is_numeric() || c == '.' {
                     self.current_token.push(c);
+                } else if c == '-' && temporal::is_prefix(&[self.current_token.as_slice(), &[c]].concat()) {
+                    self.current_token.push(c);
+                    self.mode = Mode::Temporal;
+                } else if let (Some(radix), ['0']) = (radix, self.current_token.as_slice()) {
+                    self.current_token.push(c);
+                    self.mode = Mode::RadixPrefix(radix);
+                } else if c == 'e' || c == 'E' {
+                    self.current_token.push(c);
+                    self.mode = Mode::Exponent;
+                } else if c == 'i' || c == 'j' {
+                    self.current_token.push(c);
+                    self.mode = Mode::Imaginary;
+                } else {
+                    self.end_node();
+                    self.mode = Mode::None;
+                    self.push_char(c);
+                }
+            }
+            Mode::Exponent => {
+                if c.is_numeric() {
+                    self.current_token.push(c);
+                    self.mode = Mode::ExponentDigits;
+                } else if c == '+' || c == '-' {
+                    self.current_token.push(c);
+                    self.mode = Mode::ExponentSign;
+                } else {
+                    self.split_suffix(1);
+                    self.push_char(c);
+                }
+            }
+            Mode::ExponentSign => {
+                if c.is_numeric() {
+                    self.current_token.push(c);
+                    self.mode = Mode::ExponentDigits;
+                } else {
+                    self.split_suffix(2);
+                    self.push_char(c);
+                }
+            }
+            Mode::ExponentDigits => {
+                if c.is_numeric() {
+                    self.current_token.push(c);
+                } else if c == 'i' || c == 'j' {
+                    self.current_token.push(c);
+                    self.mode = Mode::Imaginary;
+                } else {
+                    self.end_node();
+                    self.mode = Mode::None;
+                    self.push_char(c);
+                }
+            }
+            Mode::RadixPrefix(radix) => {
+                if c.is_digit(radix) {
+                    self.current_token.push(c);
+                    self.mode = Mode::RadixDigits(radix);
+                } else {
+                    self.split_suffix(1);
+                    self.push_char(c);
+                }
+            }
+            Mode::RadixDigits(radix) => {
+                // Other digits, letters and points are kept so that the literal fails to parse as a whole
+                if c.is_digit(radix) || c.is_alphanumeric() || c == '.' {
+                    self.current_token.push(c);
+                } else {
+                    self.end_node();
+                    self.mode = Mode::None;
+                    self.push_char(c);
+                }
+            }
+            Mode::Imaginary => {
+                if c.is_alphabetic() {
+                    self.split_suffix(1);
+                } else {
+                    self.end_node();
+                    self.mode = Mode::None;
+                }
+                self.push_char(c);
+            }
+            Mode::Minus => {
+                self.end_node();
+                self.mode = Mode::None;
+                self.push_char(c);
+            }
+            Mode::Temporal => {
+                if temporal::is_prefix(&[self.current_token.as_slice(), &[c]].concat()) {
+                    self.current_token.push(c);
+                } else {
+                    self.end_temporal();
+                    self.push_char(c);
+                }
+            }
+            Mode::Operator => {
+                let operator: String = self.current_token.iter().chain([&c]).collect();
+                if TWO_CHARACTER_OPERATORS.contains(&operator.as_str()) {
+                    self.current_token.push(c);
+                    self.end_node();
+                    self.mode = Mode::None;
                 } else {
                     self.end_node();
                     self.mode = Mode::None;
@@ -127,10 +488,10 @@ pub struct TokenStream<I: Iterator<Item=char>> {
 }
 
 impl<I: Iterator<Item=char>> TokenStream<I> {
-    fn new(iter: I) -> TokenStream<I> {
+    fn new(iter: I, symbolic: bool) -> TokenStream<I> {
         Self {
             iter,
-            state: TokenizerState::new(),
+            state: TokenizerState::new(symbolic),
         }
     }
 }
@@ -147,7 +508,7 @@ impl<I: Iterator<Item=char>> Iterator for TokenStream<I> {
             if let Some(c) = self.iter.next() {
                 self.state.push_char(c);
             } else {
-                self.state.end_node();
+                self.state.finish();
                 if self.state.tokens.is_empty() {
                     return None;
                 }
@@ -158,15 +519,23 @@ impl<I: Iterator<Item=char>> Iterator for TokenStream<I> {
 
 pub trait Tokenize<R: Iterator<Item=Token>> {
     fn tokenize(self) -> R;
+    /// Tokenizes the arguments of symbolic functions, where x is a name unless it is between
+    /// operands as in `2x3`
+    fn tokenize_symbolic(self) -> R;
 }
 
 impl<I: Iterator<Item=char>> Tokenize<TokenStream<I>> for I {
     fn tokenize(self) -> TokenStream<I> {
-        TokenStream::new(self)
+        TokenStream::new(self, false)
+    }
+
+    fn tokenize_symbolic(self) -> TokenStream<I> {
+        TokenStream::new(self, true)
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_conversion, clippy::get_first)]
 mod tokenizer_tests {
     use super::*;
 
@@ -218,6 +587,16 @@ mod tokenizer_tests {
         expect_token("√", &Token::Root);
     }
 
+    #[test]
+    fn angle() {
+        expect_token("∠", &Token::Angle);
+    }
+
+    #[test]
+    fn degree() {
+        expect_token("°", &Token::Degree);
+    }
+
     #[test]
     fn open_brace() {
         expect_token("(", &Token::OpenBrace);
@@ -228,6 +607,31 @@ mod tokenizer_tests {
         expect_token(")", &Token::CloseBrace);
     }
 
+    #[test]
+    fn open_bracket() {
+        expect_token("[", &Token::OpenBracket);
+    }
+
+    #[test]
+    fn close_bracket() {
+        expect_token("]", &Token::CloseBracket);
+    }
+
+    #[test]
+    fn separator() {
+        expect_token(",", &Token::Separator);
+    }
+
+    #[test]
+    fn function() {
+        expect_token("norm", &Token::Function("norm".to_string(), 0));
+    }
+
+    #[test]
+    fn function_containing_x() {
+        expect_token("exp", &Token::Function("exp".to_string(), 0));
+    }
+
     fn expect_token(input: &str, expected: &Token) {
         let tokens = input.chars().into_iter().tokenize();
         let map: Vec<Token> = tokens.into_iter().collect();
@@ -248,30 +652,312 @@ mod tokenizer_tests {
         let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
         assert_eq!(vec!["123", "+", "456"], map);
     }
+
+    #[test]
+    fn multiply_with_lower_case_x_between_numbers() {
+        let tokens = "2x3".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["2", "*", "3"], map);
+    }
+
+    #[test]
+    fn x_as_a_name() {
+        let tokens = "x^2 + 3x - 2 x 4 + sin(x) + 2 x".chars().tokenize_symbolic();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["x", "^", "2", "+", "3", "x", "-", "2", "*", "4", "+", "sin", "(", "x", ")", "+", "2", "x"], map);
+        let tokens = "3 x - 1".chars().tokenize_symbolic();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["3", "x", "-", "1"], map);
+        let tokens = "x".chars().tokenize_symbolic();
+        assert_eq!(vec![Token::T("x".to_string())], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn imaginary_literal() {
+        expect_token("4i", &Token::T("4i".to_string()));
+        expect_token("2.5j", &Token::T("2.5j".to_string()));
+        expect_token("1.5e3i", &Token::T("1.5e3i".to_string()));
+    }
+
+    #[test]
+    fn imaginary_literal_in_expression() {
+        let tokens = "3+4i*2".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["3", "+", "4i", "*", "2"], map);
+    }
+
+    #[test]
+    fn number_before_name_starting_with_i() {
+        let tokens = "4in".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["4", "in"], map);
+    }
+
+    #[test]
+    fn number_before_function_starting_with_e() {
+        let tokens = "2exp(1)".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["2", "exp", "(", "1", ")"], map);
+    }
+
+    #[test]
+    fn scientific_notation() {
+        expect_token("1e3", &Token::T("1e3".to_string()));
+        expect_token("1E+3", &Token::T("1E+3".to_string()));
+        expect_token("2.5e-4", &Token::T("2.5e-4".to_string()));
+    }
+
+    #[test]
+    fn scientific_notation_in_expression() {
+        let tokens = "1e-9*6.02E23".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["1e-9", "*", "6.02E23"], map);
+    }
+
+    #[test]
+    fn number_before_constant_e() {
+        let tokens = "2e".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["2", "e"], map);
+    }
+
+    #[test]
+    fn number_before_constant_e_minus() {
+        let tokens = "2e-y".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["2", "e", "-", "y"], map);
+        let tokens = "2e-".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["2", "e", "-"], map);
+    }
+
+    #[test]
+    fn radix_literals() {
+        expect_token("0x1F", &Token::T("0x1F".to_string()));
+        expect_token("0b1010", &Token::T("0b1010".to_string()));
+        expect_token("0o17", &Token::T("0o17".to_string()));
+    }
+
+    #[test]
+    fn radix_literal_keeps_invalid_digits() {
+        let tokens = "0b1012".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["0b1012"], map);
+        expect_token("0x1.5", &Token::T("0x1.5".to_string()));
+    }
+
+    #[test]
+    fn zero_multiplied_with_lower_case_x() {
+        let tokens = "0x(3)".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["0", "*", "(", "3", ")"], map);
+    }
+
+    #[test]
+    fn factorial() {
+        expect_token("!", &Token::Factorial);
+    }
+
+    #[test]
+    fn modulo() {
+        expect_token("mod", &Token::Modulo);
+    }
+
+    #[test]
+    fn quotient() {
+        expect_token("//", &Token::Quotient);
+    }
+
+    #[test]
+    fn percent_and_modulo() {
+        let tokens = "7%//2 mod 3/4".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["7", "%", "//", "2", "mod", "3", "/", "4"], map);
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        expect_token("&", &Token::BitAnd);
+        expect_token("|", &Token::BitOr);
+        expect_token("xor", &Token::Xor);
+        expect_token("~", &Token::BitNot);
+        expect_token("<<", &Token::ShiftLeft);
+        expect_token(">>", &Token::ShiftRight);
+    }
+
+    #[test]
+    fn shifts_between_numbers() {
+        let tokens = "1<<4>>~2".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["1", "<<", "4", ">>", "~", "2"], map);
+    }
+
+    #[test]
+    fn negation() {
+        let tokens: Vec<Token> = "-2 - -(1)^-3".chars().tokenize().collect();
+        assert_eq!(vec![
+            Token::Negate,
+            Token::T("2".to_string()),
+            Token::Minus,
+            Token::Negate,
+            Token::OpenBrace,
+            Token::T("1".to_string()),
+            Token::CloseBrace,
+            Token::Power,
+            Token::Negate,
+            Token::T("3".to_string()),
+        ], tokens);
+        let tokens: Vec<Token> = "3! - 1".chars().tokenize().collect();
+        assert_eq!(Token::Minus, tokens[2]);
+    }
+
+    #[test]
+    fn units() {
+        let tokens: Vec<Token> = "3 km/h".chars().tokenize().collect();
+        assert_eq!(vec![
+            Token::T("3".to_string()),
+            Token::Unit("km".to_string()),
+            Token::Divide,
+            Token::Unit("h".to_string()),
+        ], tokens);
+        expect_token("pi", &Token::T("pi".to_string()));
+    }
+
+    #[test]
+    fn comparisons() {
+        let tokens = "1 < 2 <= 3 == 4 != 5 >= 6 > 7 << 8≈9".chars().tokenize();
+        let map: Vec<String> = tokens.map(|token| token.to_string()).collect();
+        assert_eq!(vec!["1", "<", "2", "<=", "3", "==", "4", "!=", "5", ">=", "6", ">", "7", "<<", "8", "≈", "9"], map);
+        let tokens: Vec<Token> = "not 3! == 6 and x or y".chars().tokenize().collect();
+        assert_eq!(Token::Not, tokens[0]);
+        assert_eq!(Token::Factorial, tokens[2]);
+        assert_eq!(Token::And, tokens[5]);
+        assert_eq!(Token::Or, tokens[7]);
+    }
+
+    #[test]
+    fn dates() {
+        let tokens: Vec<Token> = "2026-10-17T14:30+02:00 - 2026-10".chars().tokenize().collect();
+        assert_eq!(vec![
+            Token::Temporal("2026-10-17T14:30+02:00".to_string()),
+            Token::Minus,
+            Token::T("2026".to_string()),
+            Token::Minus,
+            Token::T("10".to_string()),
+        ], tokens);
+        let tokens: Vec<Token> = "today-2026-01-01T1".chars().tokenize().collect();
+        assert_eq!(vec![
+            Token::Temporal("today".to_string()),
+            Token::Minus,
+            Token::Temporal("2026-01-01".to_string()),
+            Token::T("T1".to_string()),
+        ], tokens);
+    }
+
+    #[test]
+    fn minutes_or_minimum() {
+        let tokens: Vec<Token> = "5 min".chars().tokenize().collect();
+        assert_eq!(Token::Unit("min".to_string()), tokens[1]);
+        let tokens: Vec<Token> = "min(5, 2)".chars().tokenize().collect();
+        assert_eq!(Token::Function("min".to_string(), 0), tokens[0]);
+    }
+
+    #[test]
+    fn vector() {
+        let tokens = "[1, 2,3]".chars().tokenize();
+        let map: Vec<String> = tokens.into_iter().map(|t| { t.to_string() }).collect();
+        assert_eq!(vec!["[", "1", ",", "2", ",", "3", "]"], map);
+    }
 }
 
 impl ShuntingYardToken for Token {
     fn shunt_type(&self) -> ShuntType {
         match &self {
             Token::T(_) => ShuntType::Operand,
-            Token::Plus => ShuntType::Operator { associativity: Associativity::Left, precedence: 0 },
-            Token::Minus => ShuntType::Operator { associativity: Associativity::Left, precedence: 0 },
-            Token::Multiply => ShuntType::Operator { associativity: Associativity::Left, precedence: 1 },
-            Token::Divide => ShuntType::Operator { associativity: Associativity::Left, precedence: 1 },
-            Token::Percent => ShuntType::Operator { associativity: Associativity::Left, precedence: 1 },
-            Token::Power => ShuntType::Operator { associativity: Associativity::Left, precedence: 2 },
-            Token::Root => ShuntType::Operator { associativity: Associativity::Left, precedence: 2 },
+            Token::Unit(_) => ShuntType::Operand,
+            Token::Temporal(_) => ShuntType::Operand,
+            // Logical operators bind most loosely, then comparisons, as in Python, so that
+            // `not a < b and c` is `(not (a < b)) and c`
+            Token::Or => ShuntType::Operator { associativity: Associativity::Left, precedence: 0 },
+            Token::And => ShuntType::Operator { associativity: Associativity::Left, precedence: 1 },
+            Token::Not => ShuntType::Prefix { precedence: 2 },
+            Token::Equal => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::NotEqual => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::Less => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::LessEqual => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::Greater => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::GreaterEqual => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            Token::Approx => ShuntType::Operator { associativity: Associativity::Left, precedence: 3 },
+            // Bitwise operators bind more loosely than arithmetic, in the order of C among themselves, but
+            // more tightly than comparisons as in Python, so `5 & 3 == 1` is `(5 & 3) == 1`
+            Token::BitOr => ShuntType::Operator { associativity: Associativity::Left, precedence: 4 },
+            Token::Xor => ShuntType::Operator { associativity: Associativity::Left, precedence: 5 },
+            Token::BitAnd => ShuntType::Operator { associativity: Associativity::Left, precedence: 6 },
+            Token::ShiftLeft => ShuntType::Operator { associativity: Associativity::Left, precedence: 7 },
+            Token::ShiftRight => ShuntType::Operator { associativity: Associativity::Left, precedence: 7 },
+            Token::Plus => ShuntType::Operator { associativity: Associativity::Left, precedence: 8 },
+            Token::Minus => ShuntType::Operator { associativity: Associativity::Left, precedence: 8 },
+            Token::Multiply => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            Token::Divide => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            Token::Percent => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            Token::PercentIncrease => ShuntType::Operator { associativity: Associativity::Left, precedence: 8 },
+            Token::PercentDecrease => ShuntType::Operator { associativity: Associativity::Left, precedence: 8 },
+            Token::Modulo => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            Token::Quotient => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            // Precedence 10 is the multiplication assumed before a unit, see `separator_precedence`
+            Token::Power => ShuntType::Operator { associativity: Associativity::Left, precedence: 11 },
+            Token::Root => ShuntType::Operator { associativity: Associativity::Left, precedence: 11 },
+            Token::Angle => ShuntType::Operator { associativity: Associativity::Left, precedence: 9 },
+            Token::Degree => ShuntType::Operator { associativity: Associativity::Left, precedence: 12 },
+            Token::Factorial => ShuntType::Operator { associativity: Associativity::Left, precedence: 12 },
+            Token::BitNot => ShuntType::Prefix { precedence: 12 },
+            // Below powers, so that -2^2 is -4, and above addition
+            Token::Negate => ShuntType::Prefix { precedence: 9 },
             Token::OpenBrace => ShuntType::OpenBrace,
             Token::CloseBrace => ShuntType::CloseBrace,
+            Token::OpenBracket => ShuntType::OpenBrace,
+            Token::CloseBracket => ShuntType::CloseBrace,
+            Token::Separator => ShuntType::Separator,
+            Token::Function(_, _) => ShuntType::Function,
+            Token::Vector(_) => ShuntType::Function,
+            Token::Then => ShuntType::Separator,
+            Token::Else => ShuntType::Separator,
         }
     }
 
     fn operand_separator() -> Option<Self> {
         Some(Self::Multiply)
     }
+
+    /// A unit binds to what is before it ahead of `*` and `/`, so `100 m / 2 s` is 50 m/s, but
+    /// after `^`, so `2 m^2` is two square metres
+    fn separator_precedence(&self) -> Option<u8> {
+        match self {
+            Token::Unit(_) => Some(10),
+            _ => None,
+        }
+    }
+
+    /// Only the branch of `if` that is taken is evaluated, so that `if(x == 0, 0, 1/x)` is 0 for 0
+    fn separate(&self, arguments: usize) -> Option<Self> {
+        match (self, arguments) {
+            (Token::Function(name, _), 1) if name == "if" => Some(Token::Then),
+            (Token::Function(name, _), 2) if name == "if" => Some(Token::Else),
+            _ => None,
+        }
+    }
+
+    fn close(self, arguments: usize) -> Option<Self> {
+        match self {
+            Token::Function(name, _) => Some(Token::Function(name, arguments)),
+            Token::OpenBracket => Some(Token::Vector(arguments)),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_conversion)]
 mod shunting_yard_integration_tests {
     use crate::shunting_yard::Shunt;
     use crate::tokenizer::Token::*;
@@ -289,6 +975,105 @@ mod shunting_yard_integration_tests {
             Plus,
         ], tokens.collect::<Vec<Token>>());
     }
+
+    #[test]
+    fn factorial_binds_before_power() {
+        let tokens = "2^3!".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("2".to_string()),
+            T("3".to_string()),
+            Factorial,
+            Power,
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn negation_binds_after_power() {
+        let tokens = "-2^2 + 1".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("2".to_string()),
+            T("2".to_string()),
+            Power,
+            Negate,
+            T("1".to_string()),
+            Plus,
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn logic_precedence() {
+        let tokens = "not 1 + 1 < 3 and 2 == 2".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("1".to_string()),
+            T("1".to_string()),
+            Plus,
+            T("3".to_string()),
+            Less,
+            Not,
+            T("2".to_string()),
+            T("2".to_string()),
+            Equal,
+            And,
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn bitwise_precedence() {
+        let tokens = "1 | 2 xor 3 & 4 << 5 + ~6".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("1".to_string()),
+            T("2".to_string()),
+            T("3".to_string()),
+            T("4".to_string()),
+            T("5".to_string()),
+            T("6".to_string()),
+            BitNot,
+            Plus,
+            ShiftLeft,
+            BitAnd,
+            Xor,
+            BitOr,
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn vector_literal() {
+        let tokens = "[1, 2+3]".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("1".to_string()),
+            T("2".to_string()),
+            T("3".to_string()),
+            Plus,
+            Vector(2),
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn if_branches_are_marked() {
+        let tokens = "if(1 < 2, 3, 4)".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("1".to_string()),
+            T("2".to_string()),
+            Less,
+            Then,
+            T("3".to_string()),
+            Else,
+            T("4".to_string()),
+            Function("if".to_string(), 3),
+        ], tokens.collect::<Vec<Token>>());
+    }
+
+    #[test]
+    fn function_arguments_are_counted() {
+        let tokens = "dot([1], [2])".chars().tokenize().shunt();
+        assert_eq!(vec![
+            T("1".to_string()),
+            Vector(1),
+            T("2".to_string()),
+            Vector(1),
+            Function("dot".to_string(), 2),
+        ], tokens.collect::<Vec<Token>>());
+    }
 }
 
 #[cfg(test)]
@@ -298,7 +1083,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn single_char() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         let tokens = tokenizer.complete();
         assert_eq!(vec![T("1".to_string())], tokens);
@@ -306,7 +1091,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn two_chars() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         tokenizer.push_char('2');
         let tokens = tokenizer.complete();
@@ -315,7 +1100,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn two_chars_with_separator() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         tokenizer.push_char('+');
         tokenizer.push_char('2');
@@ -329,7 +1114,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn numbers_followed_by_letters() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         tokenizer.push_char('2');
         tokenizer.push_char('a');
@@ -340,7 +1125,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn ignore_white_space() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         tokenizer.push_char(' ');
         tokenizer.push_char('2');
@@ -353,7 +1138,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn allow_period() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('1');
         tokenizer.push_char('.');
         tokenizer.push_char('2');
@@ -365,7 +1150,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn ignore_white_space_between_letters() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('a');
         tokenizer.push_char(' ');
         tokenizer.push_char('b');
@@ -378,7 +1163,7 @@ mod tokenizer_state_test {
 
     #[test]
     fn ignore_double_white_space_between_letters() {
-        let mut tokenizer = TokenizerState::new();
+        let mut tokenizer = TokenizerState::new(false);
         tokenizer.push_char('a');
         tokenizer.push_char(' ');
         tokenizer.push_char(' ');
@@ -0,0 +1,120 @@
+use std::cmp::Ordering;
+
+use crate::math::Math;
+
+/// How close two numbers must be to count as equal, within `relative` times the larger of them or
+/// within `absolute`, whichever is looser. Exact number types only use it for `≈` and `approx`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tolerance {
+    pub relative: f64,
+    pub absolute: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self {
+            relative: 1e-9,
+            absolute: 1e-12,
+        }
+    }
+}
+
+impl Tolerance {
+    /// Only numbers that are exactly equal
+    pub(crate) const NONE: Tolerance = Tolerance { relative: 0f64, absolute: 0f64 };
+
+    /// Applies the argument of a `:tolerance` command, e.g. `rel 1e-6`, `abs 1e-9`, `rel 1e-6 abs 0`,
+    /// `exact` or `default`
+    pub fn set(&mut self, setting: &str) -> Result<(), String> {
+        let words: Vec<&str> = setting.split_whitespace().collect();
+        match words.as_slice() {
+            ["default"] => *self = Tolerance::default(),
+            ["exact"] => *self = Tolerance::NONE,
+            [] => return Err("Expected rel, abs, exact or default".to_string()),
+            words => {
+                let mut tolerance = *self;
+                for pair in words.chunks(2) {
+                    let field = match pair[0] {
+                        "rel" => &mut tolerance.relative,
+                        "abs" => &mut tolerance.absolute,
+                        name => return Err(format!("Unknown tolerance \"{}\", expected rel, abs, exact or default", name)),
+                    };
+                    *field = match pair.get(1).map(|value| value.parse::<f64>()) {
+                        Some(Ok(value)) if value >= 0f64 => value,
+                        _ => return Err(format!("Expected a tolerance of at least 0 after \"{}\"", pair[0])),
+                    };
+                }
+                *self = tolerance;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether two numbers are equal within this tolerance, complex numbers by the distance between them
+    pub(crate) fn equal<T: Math<T>>(&self, left: &T, right: &T) -> bool {
+        let difference = (left.clone() - right.clone()).magnitude();
+        // Beyond the range of `f64` every difference would be within an infinite relative tolerance
+        difference.is_finite() && difference <= self.absolute.max(self.relative * left.magnitude().max(right.magnitude()))
+    }
+
+    /// Whether the imaginary part of a number is negligible next to the number
+    fn is_real<T: Math<T>>(&self, value: &T) -> bool {
+        value.clone().im().magnitude() <= self.absolute.max(self.relative * value.magnitude())
+    }
+
+    /// Orders two numbers, treating those within the tolerance as equal and imaginary parts within
+    /// it as zero. None for NaN and numbers with imaginary parts.
+    pub(crate) fn compare<T: Math<T>>(&self, left: &T, right: &T) -> Option<Ordering> {
+        if self.equal(left, right) {
+            return Some(Ordering::Equal);
+        }
+        if !self.is_real(left) || !self.is_real(right) {
+            return None;
+        }
+        left.clone().re().compare(&right.clone().re())
+    }
+}
+
+#[cfg(test)]
+mod tolerance_tests {
+    use crate::complex::Complex;
+
+    use super::*;
+
+    #[test]
+    fn settings() {
+        let mut tolerance = Tolerance::default();
+        tolerance.set("rel 1e-6").unwrap();
+        assert_eq!(Tolerance { relative: 1e-6, absolute: 1e-12 }, tolerance);
+        tolerance.set("abs 0.5 rel 0").unwrap();
+        assert_eq!(Tolerance { relative: 0f64, absolute: 0.5 }, tolerance);
+        tolerance.set("exact").unwrap();
+        assert_eq!(Tolerance::NONE, tolerance);
+        assert!(tolerance.set("rel").is_err());
+        assert!(tolerance.set("rel -1").is_err());
+        assert!(tolerance.set("near 1").is_err());
+        assert_eq!(Tolerance::NONE, tolerance);
+    }
+
+    #[test]
+    fn equality() {
+        let tolerance = Tolerance::default();
+        assert!(tolerance.equal(&(0.1 + 0.2), &0.3));
+        assert!(tolerance.equal(&1e20, &(1e20 + 1e5)));
+        assert!(tolerance.equal(&1e-13, &0f64));
+        assert!(!tolerance.equal(&1.001, &1f64));
+        assert!(!tolerance.equal(&f64::NAN, &f64::NAN));
+        let minus_one = Complex::new(-1f64, 1.2246467991473532e-16);
+        assert!(tolerance.equal(&minus_one, &Complex::real(-1f64)));
+    }
+
+    #[test]
+    fn order() {
+        let tolerance = Tolerance::default();
+        assert_eq!(Some(Ordering::Equal), tolerance.compare(&(0.1 + 0.2), &0.3));
+        assert_eq!(Some(Ordering::Less), tolerance.compare(&1f64, &2f64));
+        let almost_real = Complex::new(2f64, 1e-17);
+        assert_eq!(Some(Ordering::Greater), tolerance.compare(&almost_real, &Complex::real(1f64)));
+        assert_eq!(None, tolerance.compare(&Complex::new(2f64, 1f64), &Complex::real(1f64)));
+    }
+}
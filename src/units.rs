@@ -0,0 +1,433 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::calculator::{Calculator, FromStrValue};
+use crate::error::CalculatorError;
+use crate::format::Base;
+use crate::tokenizer::{Token, Tokenize};
+use crate::value::Value;
+
+/// Units that are not defined in terms of others, each is a dimension of its own, and whether
+/// they take SI prefixes. Mass is measured in kilograms, so it is the gram that takes prefixes.
+const BASE_UNITS: &[(&str, bool)] = &[
+    ("m", true),
+    ("kg", false),
+    ("s", true),
+    ("A", true),
+    ("K", true),
+    ("mol", true),
+    ("cd", true),
+    ("bit", true),
+];
+
+/// Units defined by an expression in other units, and whether they take SI prefixes
+const DERIVED_UNITS: &[(&str, &str, bool)] = &[
+    ("g", "1e-3 kg", true),
+    ("t", "1000 kg", false),
+    ("min", "60 s", false),
+    ("h", "3600 s", false),
+    ("d", "86400 s", false),
+    ("week", "7 d", false),
+    ("second", "1 s", false),
+    ("seconds", "1 s", false),
+    ("minute", "1 min", false),
+    ("minutes", "1 min", false),
+    ("hour", "1 h", false),
+    ("hours", "1 h", false),
+    ("day", "1 d", false),
+    ("days", "1 d", false),
+    ("weeks", "1 week", false),
+    ("yr", "365.25 d", false),
+    ("Hz", "1/s", true),
+    ("N", "kg m/s^2", true),
+    ("Pa", "N/m^2", true),
+    ("J", "N m", true),
+    ("W", "J/s", true),
+    ("C", "A s", true),
+    ("V", "W/A", true),
+    ("ohm", "V/A", true),
+    ("Ω", "V/A", true),
+    ("F", "C/V", true),
+    ("Wb", "V s", true),
+    ("T", "Wb/m^2", true),
+    ("L", "1e-3 m^3", true),
+    ("l", "1e-3 m^3", true),
+    ("eV", "1.602176634e-19 J", true),
+    ("cal", "4.184 J", true),
+    ("Wh", "3600 J", true),
+    ("bar", "1e5 Pa", true),
+    ("atm", "101325 Pa", false),
+    ("psi", "6894.757293168 Pa", false),
+    ("B", "8 bit", true),
+    // Imperial and US customary units. The inch is "inch" because "in" converts.
+    ("inch", "0.0254 m", false),
+    ("ft", "0.3048 m", false),
+    ("yd", "0.9144 m", false),
+    ("mi", "1609.344 m", false),
+    ("nmi", "1852 m", false),
+    ("mph", "mi/h", false),
+    ("kn", "nmi/h", false),
+    ("ha", "1e4 m^2", false),
+    ("acre", "4046.8564224 m^2", false),
+    ("lb", "0.45359237 kg", false),
+    ("oz", "lb/16", false),
+    ("gal", "3.785411784 L", false),
+];
+
+const PREFIXES: &[(&str, &str)] = &[
+    ("Y", "1e24"),
+    ("Z", "1e21"),
+    ("E", "1e18"),
+    ("P", "1e15"),
+    ("T", "1e12"),
+    ("G", "1e9"),
+    ("M", "1e6"),
+    ("k", "1e3"),
+    ("h", "1e2"),
+    ("d", "1e-1"),
+    ("c", "1e-2"),
+    ("m", "1e-3"),
+    ("µ", "1e-6"),
+    ("u", "1e-6"),
+    ("n", "1e-9"),
+    ("p", "1e-12"),
+    ("f", "1e-15"),
+    ("a", "1e-18"),
+    ("z", "1e-21"),
+    ("y", "1e-24"),
+];
+
+/// How a unit name is evaluated
+#[derive(Debug, PartialEq)]
+pub(crate) enum Unit {
+    /// A base unit, the dimension it is named after
+    Base(&'static str),
+    /// An expression in other units
+    Derived(String),
+}
+
+/// Finds a unit by its name, which may have an SI prefix, e.g. `km` is `1e3 m`
+pub(crate) fn lookup(name: &str) -> Option<Unit> {
+    if let Some((base, _)) = BASE_UNITS.iter().find(|(base, _)| *base == name) {
+        return Some(Unit::Base(base));
+    }
+    if let Some((_, definition, _)) = DERIVED_UNITS.iter().find(|(derived, _, _)| *derived == name) {
+        return Some(Unit::Derived(definition.to_string()));
+    }
+    PREFIXES.iter().find_map(|(prefix, factor)| {
+        let unit = name.strip_prefix(prefix)?;
+        let prefixed = BASE_UNITS.iter().any(|(base, prefixed)| *prefixed && *base == unit)
+            || DERIVED_UNITS.iter().any(|(derived, _, prefixed)| *prefixed && *derived == unit);
+        prefixed.then(|| Unit::Derived(format!("{} {}", factor, unit)))
+    })
+}
+
+pub(crate) fn is_unit(name: &str) -> bool {
+    lookup(name).is_some()
+}
+
+/// Reads a unit definition, `rack = 42 U`, which may also be written `unit rack = 42 U` or
+/// `1 rack = 42 U`, into the name and the expression that defines it
+pub(crate) fn parse_definition(statement: &str) -> Result<(String, String), CalculatorError> {
+    let parse = || CalculatorError::Parse(statement.trim().to_string());
+    let (name, definition) = statement.split_once('=').ok_or_else(parse)?;
+    let name = name.trim();
+    let name = name.strip_prefix("unit ").unwrap_or(name).trim();
+    let name = name.strip_prefix("1 ").unwrap_or(name).trim();
+    if name.is_empty() || definition.trim().is_empty() {
+        return Err(parse());
+    }
+    Ok((name.to_string(), definition.trim().to_string()))
+}
+
+/// Reads a unit file, one definition per line. Blank lines and lines starting with `#` are ignored,
+/// and a unit may only be defined once.
+pub(crate) fn parse_definitions(text: &str) -> Result<Vec<(String, String)>, CalculatorError> {
+    let mut definitions: Vec<(String, String)> = vec![];
+    for (index, line) in text.lines().map(str::trim).enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, definition) = parse_definition(line)?;
+        if definitions.iter().any(|(defined, _)| *defined == name) {
+            return Err(CalculatorError::DuplicateUnit { name, line: index + 1 });
+        }
+        definitions.push((name, definition));
+    }
+    Ok(definitions)
+}
+
+/// Names that would not be read back as a unit, because they are numbers, constants, functions,
+/// operators, built-in units or conversions
+fn is_valid_name(name: &str) -> bool {
+    let tokens: Vec<Token> = name.chars().tokenize().collect();
+    name.chars().all(char::is_alphabetic)
+        && tokens == [Token::T(name.to_string())]
+        && <f64 as FromStrValue>::from_str(name).is_err()
+        && !["i", "j", "x"].contains(&name)
+        && Base::parse(name).is_none()
+}
+
+impl Calculator {
+    /// Adds or replaces units, all or none of them. Definitions may refer to each other in any
+    /// order but not in a cycle, and a unit that is replaced must keep its dimension so that the
+    /// units defined in terms of it still mean the same thing.
+    pub(crate) fn define_units(&mut self, definitions: Vec<(String, String)>) -> Result<(), CalculatorError> {
+        let previous = self.units.clone();
+        let result = self.add_units(definitions, &previous);
+        if result.is_err() {
+            self.units = previous;
+        }
+        result
+    }
+
+    fn add_units(&mut self, definitions: Vec<(String, String)>, previous: &BTreeMap<String, String>) -> Result<(), CalculatorError> {
+        for (name, _) in &definitions {
+            if !previous.contains_key(name) && !is_valid_name(name) {
+                return Err(CalculatorError::UnitName(name.clone()));
+            }
+        }
+        let replaced = definitions.iter()
+            .filter(|(name, _)| previous.contains_key(name))
+            .map(|(name, _)| Ok((name.clone(), self.dimension(name)?)))
+            .collect::<Result<Vec<(String, Dimension)>, CalculatorError>>()?;
+        self.units.extend(definitions.iter().cloned());
+        for (name, _) in &definitions {
+            self.check_cycle(name, name, &mut vec![])?;
+        }
+        for (name, _) in &definitions {
+            self.dimension(name)?;
+        }
+        for (name, before) in replaced {
+            let dimension = self.dimension(&name)?;
+            if before != dimension {
+                return Err(CalculatorError::IncompatibleUnits(before.to_string(), dimension.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows the units that `unit` is defined in terms of, looking for `name`
+    fn check_cycle(&self, name: &str, unit: &str, visited: &mut Vec<String>) -> Result<(), CalculatorError> {
+        if visited.iter().any(|visited| visited == unit) {
+            return Ok(());
+        }
+        visited.push(unit.to_string());
+        let definition = &self.units[unit];
+        for token in definition.chars().tokenize() {
+            match token {
+                Token::T(dependency) if dependency == name => return Err(CalculatorError::UnitCycle(name.to_string())),
+                Token::T(dependency) if self.units.contains_key(&dependency) => self.check_cycle(name, &dependency, visited)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// The dimension of a unit, which must be a number or a quantity
+    fn dimension(&self, name: &str) -> Result<Dimension, CalculatorError> {
+        match self.evaluate::<f64>(name)? {
+            Value::Quantity(quantity) => Ok(quantity.dimension().clone()),
+            Value::Scalar(_) => Ok(Dimension::default()),
+            value => Err(CalculatorError::UnsupportedOperation(format!("{} unit", value.kind()))),
+        }
+    }
+}
+
+/// The powers of the base units a quantity is measured in, `kg m/s^2` for a force
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dimension(BTreeMap<String, i32>);
+
+impl Dimension {
+    pub fn base(unit: &str) -> Self {
+        Dimension(BTreeMap::from([(unit.to_string(), 1)]))
+    }
+
+    /// The power of the base unit `unit`, 0 if it is not part of this dimension
+    pub fn power_of(&self, unit: &str) -> i32 {
+        self.0.get(unit).copied().unwrap_or(0)
+    }
+
+    /// The base units of this dimension
+    pub fn units(&self) -> impl Iterator<Item=&str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Fails with [CalculatorError::Overflow] when a power is too large for `i32`, as are the other operations
+    pub fn multiply(&self, rhs: &Self) -> Result<Self, CalculatorError> {
+        let mut powers = self.0.clone();
+        for (unit, power) in &rhs.0 {
+            let sum = powers.entry(unit.clone()).or_insert(0);
+            *sum = sum.checked_add(*power).ok_or(CalculatorError::Overflow)?;
+        }
+        powers.retain(|_, power| *power != 0);
+        Ok(Dimension(powers))
+    }
+
+    pub fn divide(&self, rhs: &Self) -> Result<Self, CalculatorError> {
+        self.multiply(&rhs.power(-1)?)
+    }
+
+    pub fn power(&self, exponent: i32) -> Result<Self, CalculatorError> {
+        let mut powers = self.0.clone();
+        for power in powers.values_mut() {
+            *power = power.checked_mul(exponent).ok_or(CalculatorError::Overflow)?;
+        }
+        powers.retain(|_, power| *power != 0);
+        Ok(Dimension(powers))
+    }
+
+    /// The `index`th root, when every power is a multiple of it
+    pub fn root(&self, index: i64) -> Result<Self, CalculatorError> {
+        let fractional = || CalculatorError::UnsupportedOperation(format!("{} to the power 1/{}", self, index));
+        let index = i32::try_from(index).map_err(|_| fractional())?;
+        if index == 0 || self.0.values().any(|power| power % index != 0) {
+            return Err(fractional());
+        }
+        Ok(Dimension(self.0.iter().map(|(unit, power)| (unit.clone(), power / index)).collect()))
+    }
+}
+
+/// Positive powers before a slash and negative powers after it, `kg·m/s^2` or `kg/(m·s^2)`, or
+/// negative powers alone when there are no positive ones, `s^-1`. Dimensionless is 1.
+impl Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        type Powers<'a> = Vec<(&'a String, i32)>;
+        fn join(powers: &[(&String, i32)]) -> String {
+            let units: Vec<String> = powers.iter()
+                .map(|(unit, power)| if *power == 1 { unit.to_string() } else { format!("{}^{}", unit, power) })
+                .collect();
+            units.join("·")
+        }
+        let (positive, negative): (Powers, Powers) =
+            self.0.iter().map(|(unit, power)| (unit, *power)).partition(|(_, power)| *power > 0);
+        if self.is_dimensionless() {
+            return f.write_str("1");
+        }
+        if positive.is_empty() {
+            return f.write_str(&join(&negative));
+        }
+        f.write_str(&join(&positive))?;
+        let denominator: Powers = negative.into_iter().map(|(unit, power)| (unit, -power)).collect();
+        match denominator.len() {
+            0 => Ok(()),
+            1 => write!(f, "/{}", join(&denominator)),
+            _ => write!(f, "/({})", join(&denominator)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod units_tests {
+    use crate::format::FormatOptions;
+    use crate::rational::Rational;
+
+    use super::*;
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(Some(Unit::Derived("1e3 m".to_string())), lookup("km"));
+        assert_eq!(Some(Unit::Derived("1e-3 g".to_string())), lookup("mg"));
+        assert_eq!(Some(Unit::Derived("1e6 Pa".to_string())), lookup("MPa"));
+        assert_eq!(Some(Unit::Derived("60 s".to_string())), lookup("min"));
+        assert_eq!(Some(Unit::Base("kg")), lookup("kg"));
+        assert_eq!(None, lookup("kkg"));
+        assert_eq!(None, lookup("kmi"));
+        assert_eq!(None, lookup("pi"));
+        assert_eq!(None, lookup("e"));
+    }
+
+    fn defined(definitions: &[(&str, &str)]) -> Result<Calculator, CalculatorError> {
+        let mut calculator = Calculator::default();
+        let definitions = definitions.iter().map(|(name, definition)| (name.to_string(), definition.to_string())).collect();
+        calculator.define_units(definitions)?;
+        Ok(calculator)
+    }
+
+    #[test]
+    fn definitions() {
+        let definition = |name: &str, definition: &str| (name.to_string(), definition.to_string());
+        assert_eq!(Ok(definition("rack", "42 U")), parse_definition("unit rack = 42 U"));
+        assert_eq!(Ok(definition("U", "44.45 mm")), parse_definition("1 U = 44.45 mm"));
+        assert_eq!(Err(CalculatorError::Parse("rack 42 U".to_string())), parse_definition("rack 42 U"));
+        assert_eq!(Err(CalculatorError::Parse("rack =".to_string())), parse_definition("rack ="));
+        let file = "# Server racks\n\nrack = 42 U\n  U = 44.45 mm\n";
+        assert_eq!(Ok(vec![definition("rack", "42 U"), definition("U", "44.45 mm")]), parse_definitions(file));
+        assert_eq!(
+            Err(CalculatorError::DuplicateUnit { name: "rack".to_string(), line: 5 }),
+            parse_definitions("# Server racks\n\nrack = 42 U\nU = 44.45 mm\nunit rack = 48 U\n")
+        );
+    }
+
+    #[test]
+    fn user_defined_units() {
+        // In any order
+        let calculator = defined(&[("rack", "42 U"), ("U", "44.45 mm")]).unwrap();
+        let result = |expression: &str| calculator.evaluate::<Rational>(expression).map(|value| value.to_string());
+        assert_eq!(Ok("18669/10000 m".to_string()), result("1 rack"));
+        assert_eq!(Ok("126 U".to_string()), calculator.evaluate_conversion::<Rational>("3 rack to U", &FormatOptions::default())
+            .map(|(value, _)| value.to_string()));
+    }
+
+    #[test]
+    fn cycles() {
+        assert_eq!(Err(CalculatorError::UnitCycle("a".to_string())), defined(&[("a", "2 b"), ("b", "3 a")]).map(|_| ()));
+        assert_eq!(Err(CalculatorError::UnitCycle("a".to_string())), defined(&[("a", "2 a")]).map(|_| ()));
+        let mut calculator = defined(&[("a", "2 m"), ("b", "3 a")]).unwrap();
+        assert_eq!(
+            Err(CalculatorError::UnitCycle("a".to_string())),
+            calculator.define_units(vec![("a".to_string(), "b/3".to_string())])
+        );
+        assert_eq!(Some(&"2 m".to_string()), calculator.units.get("a"));
+    }
+
+    #[test]
+    fn unit_names() {
+        for name in ["m", "km", "pi", "e", "i", "sin", "hex", "x", "mod", "U2", "inf"] {
+            assert_eq!(Err(CalculatorError::UnitName(name.to_string())), defined(&[(name, "1 m")]).map(|_| ()));
+        }
+    }
+
+    #[test]
+    fn dimension_consistency() {
+        let mut calculator = defined(&[("U", "44.45 mm"), ("rack", "42 U")]).unwrap();
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            calculator.define_units(vec![("U".to_string(), "1 s".to_string())])
+        );
+        assert_eq!(Some(&"44.45 mm".to_string()), calculator.units.get("U"));
+        assert_eq!(Ok(()), calculator.define_units(vec![("U".to_string(), "1.75 inch".to_string())]));
+        assert_eq!(
+            Err(CalculatorError::IncompatibleUnits("m".to_string(), "s".to_string())),
+            defined(&[("bad", "1 m + 1 s")]).map(|_| ())
+        );
+        assert_eq!(Err(CalculatorError::Parse("furlong".to_string())), defined(&[("bad", "2 furlong")]).map(|_| ()));
+    }
+
+    #[test]
+    fn dimensions() {
+        let (m, kg, s) = (Dimension::base("m"), Dimension::base("kg"), Dimension::base("s"));
+        let newton = kg.multiply(&m).unwrap().divide(&s.power(2).unwrap()).unwrap();
+        assert_eq!("kg·m/s^2", newton.to_string());
+        assert_eq!("kg/(m·s^2)", newton.divide(&m.power(2).unwrap()).unwrap().to_string());
+        assert_eq!("s^-1", s.power(-1).unwrap().to_string());
+        assert_eq!("1", m.divide(&m).unwrap().to_string());
+        assert!(m.divide(&m).unwrap().is_dimensionless());
+        assert_eq!(Ok(m.clone()), m.power(2).unwrap().root(2));
+        assert!(m.root(2).is_err());
+    }
+
+    #[test]
+    fn dimension_overflow() {
+        let m = Dimension::base("m");
+        let large = m.power(100_000).unwrap();
+        assert_eq!(Err(CalculatorError::Overflow), large.power(100_000));
+        let largest = m.power(i32::MAX).unwrap();
+        assert_eq!(Err(CalculatorError::Overflow), largest.multiply(&m));
+        assert_eq!(Err(CalculatorError::Overflow), m.power(i32::MIN).unwrap().divide(&m));
+    }
+}
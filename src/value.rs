@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+use crate::error::CalculatorError;
+use crate::expression::Expression;
+use crate::factors::Factors;
+use crate::format::{Format, FormatOptions};
+use crate::math::Math;
+use crate::matrix::Matrix;
+use crate::quantity::Quantity;
+use crate::temporal::DateTime;
+use crate::tolerance::Tolerance;
+use crate::tokenizer::Token;
+use crate::units::Dimension;
+use crate::vector::Vector;
+
+/// A value on the calculator's stack, built from numbers of type `T`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<T> {
+    Scalar(T),
+    Vector(Vector<T>),
+    Matrix(Matrix<T>),
+    /// The prime factors of a number, from `factor(n)`, which is otherwise used as the number
+    Factors(Factors<T>),
+    /// A number with a unit, e.g. `3 m`
+    Quantity(Quantity<T>),
+    /// A date or a date and time, e.g. `2026-10-17`
+    Temporal(DateTime),
+    /// The result of a comparison, e.g. `1 < 2`
+    Boolean(bool),
+    /// A symbolic result with variables in it, e.g. `2*x` from `diff(x^2, x)`
+    Expression(Expression),
+}
+
+impl<T> Value<T> {
+    /// Builds a vector from the values of a literal when they are scalars, or a matrix when they are vectors
+    pub(crate) fn vector(elements: Vec<Value<T>>) -> Result<Self, CalculatorError> {
+        if let Some(Value::Vector(_)) = elements.first() {
+            let rows = elements.into_iter()
+                .map(|element| match element {
+                    Value::Vector(row) => Ok(row.into_elements()),
+                    element => Err(CalculatorError::UnsupportedOperation(format!("{} within a matrix", element.kind()))),
+                })
+                .collect::<Result<Vec<Vec<T>>, CalculatorError>>()?;
+            return Ok(Value::Matrix(Matrix::new(rows)?));
+        }
+        let elements = elements.into_iter()
+            .map(|element| match element {
+                Value::Scalar(value) => Ok(value),
+                element => Err(CalculatorError::UnsupportedOperation(format!("{} within a vector", element.kind()))),
+            })
+            .collect::<Result<Vec<T>, CalculatorError>>()?;
+        Ok(Value::Vector(Vector::new(elements)?))
+    }
+
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::Scalar(_) => "scalar",
+            Value::Vector(_) => "vector",
+            Value::Matrix(_) => "matrix",
+            Value::Factors(_) => "factors",
+            Value::Quantity(_) => "quantity",
+            Value::Temporal(_) => "date",
+            Value::Boolean(_) => "boolean",
+            Value::Expression(_) => "expression",
+        }
+    }
+}
+
+impl<T: Math<T>> Value<T> {
+    /// Factors as the number they are the factors of, other values unchanged
+    pub(crate) fn expand(self) -> Self {
+        match self {
+            Value::Factors(factors) => Value::Scalar(factors.product()),
+            value => value,
+        }
+    }
+
+    /// Applies a function of a single number, element-wise on vectors and matrices
+    pub(crate) fn map(self, operation: impl Fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        match self.expand() {
+            Value::Scalar(value) => Ok(Value::Scalar(operation(value)?)),
+            Value::Vector(vector) => Ok(Value::Vector(vector.try_map(operation)?)),
+            Value::Matrix(matrix) => Ok(Value::Matrix(matrix.try_map(operation)?)),
+            Value::Factors(_) => unreachable!("Factors are expanded"),
+            Value::Quantity(quantity) => Ok(Value::Quantity(quantity.map(operation)?)),
+            Value::Temporal(_) => Err(CalculatorError::UnsupportedOperation("function of a date".to_string())),
+            Value::Boolean(_) => Err(CalculatorError::UnsupportedOperation("function of a boolean".to_string())),
+            Value::Expression(_) => Err(CalculatorError::UnsupportedOperation("function of an expression".to_string())),
+        }
+    }
+
+    /// Applies the unary operator `t`, of which only negation and percentages are defined for quantities
+    pub(crate) fn unary(self, t: &Token, operation: fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        match (t, self) {
+            (t, value @ (Value::Temporal(_) | Value::Boolean(_) | Value::Expression(_))) =>
+                Err(CalculatorError::UnsupportedOperation(format!("{} {}", t, value.kind()))),
+            (Token::Negate | Token::Percent, value) => value.map(operation),
+            (t, Value::Quantity(_)) => Err(CalculatorError::UnsupportedOperation(format!("{} quantity", t))),
+            (_, value) => value.map(operation),
+        }
+    }
+
+    /// Applies the binary operator `t`, where `operation` is how it combines two numbers
+    pub(crate) fn combine(self, t: &Token, rhs: Self, operation: fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        match (t, self.expand(), rhs.expand()) {
+            (_, Value::Scalar(left), Value::Scalar(right)) => Ok(Value::Scalar(operation(left, right)?)),
+            (Token::Plus, Value::Vector(left), Value::Vector(right)) => Ok(Value::Vector((left + right)?)),
+            (Token::Minus, Value::Vector(left), Value::Vector(right)) => Ok(Value::Vector((left - right)?)),
+            (Token::Multiply, Value::Vector(left), Value::Scalar(right)) => Ok(Value::Vector((left * right)?)),
+            (Token::Multiply, Value::Scalar(left), Value::Vector(right)) =>
+                Ok(Value::Vector(right.try_map(|element| left.clone().checked_times(element))?)),
+            (Token::Divide, Value::Vector(left), Value::Scalar(right)) => Ok(Value::Vector((left / right)?)),
+            (Token::Plus, Value::Matrix(left), Value::Matrix(right)) => Ok(Value::Matrix((left + right)?)),
+            (Token::Minus, Value::Matrix(left), Value::Matrix(right)) => Ok(Value::Matrix((left - right)?)),
+            (Token::Multiply, Value::Matrix(left), Value::Matrix(right)) => Ok(Value::Matrix((left * right)?)),
+            (Token::Multiply, Value::Matrix(left), Value::Vector(right)) => Ok(Value::Vector(left.multiply_vector(right)?)),
+            (Token::Multiply, Value::Vector(left), Value::Matrix(right)) =>
+                Ok(Value::Vector(right.transpose().multiply_vector(left)?)),
+            (Token::Multiply, Value::Matrix(left), Value::Scalar(right)) => Ok(Value::Matrix((left * right)?)),
+            (Token::Multiply, Value::Scalar(left), Value::Matrix(right)) =>
+                Ok(Value::Matrix(right.try_map(|element| left.clone().checked_times(element))?)),
+            (Token::Divide, Value::Matrix(left), Value::Scalar(right)) => Ok(Value::Matrix((left / right)?)),
+            (Token::Power, Value::Matrix(left), Value::Scalar(right)) => match right.to_integer() {
+                Some(exponent) => Ok(Value::Matrix(left.power(exponent)?)),
+                None => Err(CalculatorError::UnsupportedOperation("matrix ^ non-integer".to_string())),
+            },
+            (t, Value::Quantity(left), Value::Quantity(right)) => left.combine(t, right, operation),
+            (t, Value::Quantity(left), Value::Scalar(right)) => left.combine(t, Quantity::dimensionless(right), operation),
+            (t, Value::Scalar(left), Value::Quantity(right)) => Quantity::dimensionless(left).combine(t, right, operation),
+            (Token::Plus, Value::Temporal(left), Value::Quantity(right)) => Ok(Value::Temporal(left.add(right.seconds()?)?)),
+            (Token::Plus, Value::Quantity(left), Value::Temporal(right)) => Ok(Value::Temporal(right.add(left.seconds()?)?)),
+            (Token::Minus, Value::Temporal(left), Value::Quantity(right)) =>
+                Ok(Value::Temporal(left.add(right.seconds()?.checked_neg().ok_or(CalculatorError::Overflow)?)?)),
+            (Token::Minus, Value::Temporal(left), Value::Temporal(right)) => {
+                let seconds = T::from_big_integer(left.since(&right)?.into())?;
+                Ok(Value::Quantity(Quantity::new(seconds, Dimension::base("s"))))
+            }
+            (t, left, right) =>
+                Err(CalculatorError::UnsupportedOperation(format!("{} {} {}", left.kind(), t, right.kind()))),
+        }
+    }
+}
+
+impl<T: Math<T>> Value<T> {
+    /// Compares two values with the comparison operator `t`. Numbers must be real and quantities
+    /// of the same dimension to be ordered, booleans can only be equal or not. Inexact numbers, and
+    /// all numbers with `≈`, are equal within `tolerance`.
+    pub(crate) fn compare(self, t: &Token, rhs: Self, tolerance: &Tolerance) -> Result<Self, CalculatorError> {
+        let order = |left: &T, right: &T| match T::EXACT && *t != Token::Approx {
+            true => left.compare(right).or_else(|| Tolerance::NONE.compare(left, right)),
+            false => tolerance.compare(left, right),
+        };
+        let ordering = match (self.expand(), rhs.expand()) {
+            (Value::Scalar(left), Value::Scalar(right)) => order(&left, &right),
+            (Value::Quantity(left), Value::Quantity(right)) => left.compare(&right, order)?,
+            (Value::Quantity(left), Value::Scalar(right)) => left.compare(&Quantity::dimensionless(right), order)?,
+            (Value::Scalar(left), Value::Quantity(right)) => Quantity::dimensionless(left).compare(&right, order)?,
+            (Value::Temporal(left), Value::Temporal(right)) => Some(left.since(&right)?.cmp(&0)),
+            (Value::Boolean(left), Value::Boolean(right)) if matches!(t, Token::Equal | Token::NotEqual | Token::Approx) =>
+                Some(left.cmp(&right)),
+            (left, right) => return Err(CalculatorError::UnsupportedOperation(format!("{} {} {}", left.kind(), t, right.kind()))),
+        };
+        // Complex numbers and NaN have no order, but are equal or not
+        if matches!(t, Token::Equal | Token::NotEqual | Token::Approx) {
+            return Ok(Value::Boolean((ordering == Some(Ordering::Equal)) != (*t == Token::NotEqual)));
+        }
+        let ordering = ordering.ok_or_else(|| CalculatorError::Unordered(t.to_string()))?;
+        Ok(Value::Boolean(match t {
+            Token::Less => ordering == Ordering::Less,
+            Token::LessEqual => ordering != Ordering::Greater,
+            Token::Greater => ordering == Ordering::Greater,
+            Token::GreaterEqual => ordering != Ordering::Less,
+            t => unreachable!("{} is not a comparison", t),
+        }))
+    }
+
+    /// Applies `and` or `or` to two booleans
+    pub(crate) fn logic(self, t: &Token, rhs: Self) -> Result<Self, CalculatorError> {
+        match (t, self, rhs) {
+            (Token::And, Value::Boolean(left), Value::Boolean(right)) => Ok(Value::Boolean(left && right)),
+            (Token::Or, Value::Boolean(left), Value::Boolean(right)) => Ok(Value::Boolean(left || right)),
+            (t, left, right) => Err(CalculatorError::UnsupportedOperation(format!("{} {} {}", left.kind(), t, right.kind()))),
+        }
+    }
+
+    pub(crate) fn not(self) -> Result<Self, CalculatorError> {
+        match self {
+            Value::Boolean(value) => Ok(Value::Boolean(!value)),
+            value => Err(CalculatorError::UnsupportedOperation(format!("not {}", value.kind()))),
+        }
+    }
+}
+
+/// With `{:#}` matrices are shown a row per line
+impl<T: Format + Math<T>> Format for Value<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        match self {
+            Value::Scalar(value) => value.format(f, options),
+            Value::Vector(vector) => vector.format(f, options),
+            Value::Matrix(matrix) => matrix.format(f, options),
+            Value::Factors(factors) => factors.format(f, options),
+            Value::Quantity(quantity) => quantity.format(f, options),
+            Value::Temporal(date) => write!(f, "{} ({})", date, date.human()),
+            Value::Boolean(value) => write!(f, "{}", value),
+            Value::Expression(expression) => write!(f, "{}", expression),
+        }
+    }
+}
+
+impl<T: Format + Math<T>> Display for Value<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f, &FormatOptions::default())
+    }
+}
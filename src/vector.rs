@@ -0,0 +1,233 @@
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Sub};
+
+use crate::error::CalculatorError;
+use crate::format::{Format, FormatOptions};
+use crate::math::{Math, Percent};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vector<T> {
+    elements: Vec<T>,
+}
+
+impl<T> Vector<T> {
+    pub fn new(elements: Vec<T>) -> Result<Self, CalculatorError> {
+        if elements.is_empty() {
+            return Err(CalculatorError::EmptyVector);
+        }
+        Ok(Self { elements })
+    }
+
+    pub fn into_elements(self) -> Vec<T> {
+        self.elements
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.elements.len()
+    }
+
+    fn check_dimension(&self, rhs: &Self) -> Result<(), CalculatorError> {
+        if self.dimension() != rhs.dimension() {
+            return Err(CalculatorError::DimensionMismatch { expected: self.dimension(), found: rhs.dimension() });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Math<T>> Vector<T> {
+    /// Applies `operation` to each element
+    pub fn map(self, operation: impl Fn(T) -> T) -> Self {
+        Self {
+            elements: self.elements.into_iter().map(operation).collect()
+        }
+    }
+
+    /// Applies `operation` to each element, stopping at the first error
+    pub fn try_map(self, operation: impl Fn(T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        Ok(Self {
+            elements: self.elements.into_iter().map(operation).collect::<Result<Vec<T>, CalculatorError>>()?
+        })
+    }
+
+    /// Combines corresponding elements of two vectors of the same dimension, stopping at the first error
+    fn zip(self, rhs: Self, operation: impl Fn(T, T) -> Result<T, CalculatorError>) -> Result<Self, CalculatorError> {
+        self.check_dimension(&rhs)?;
+        Ok(Self {
+            elements: self.elements.into_iter().zip(rhs.elements).map(|(l, r)| operation(l, r)).collect::<Result<Vec<T>, CalculatorError>>()?
+        })
+    }
+
+    fn sum(self) -> Result<T, CalculatorError> {
+        self.elements.into_iter().map(Ok).reduce(|l, r| l?.checked_plus(r?)).expect("Vectors are never empty")
+    }
+
+    /// Reports overflows of the fixed width integer types, as matrix products are built from it
+    pub fn dot(self, rhs: Self) -> Result<T, CalculatorError> {
+        self.check_dimension(&rhs)?;
+        self.elements.into_iter()
+            .zip(rhs.elements)
+            .map(|(l, r)| l.checked_times(r))
+            .reduce(|sum, product| sum?.checked_plus(product?))
+            .expect("Vectors are never empty")
+    }
+
+    pub fn cross(self, rhs: Self) -> Result<Self, CalculatorError> {
+        for vector in [&self, &rhs] {
+            if vector.dimension() != 3 {
+                return Err(CalculatorError::DimensionMismatch { expected: 3, found: vector.dimension() });
+            }
+        }
+        let [a1, a2, a3]: [T; 3] = self.elements.try_into().ok().unwrap();
+        let [b1, b2, b3]: [T; 3] = rhs.elements.try_into().ok().unwrap();
+        let difference = |a: T, b: T, c: T, d: T| a.checked_times(b)?.checked_minus(c.checked_times(d)?);
+        Self::new(vec![
+            difference(a2.clone(), b3.clone(), a3.clone(), b2.clone())?,
+            difference(a3, b1.clone(), a1.clone(), b3)?,
+            difference(a1, b2, a2, b1)?,
+        ])
+    }
+
+    /// The euclidean length
+    pub fn norm(self) -> Result<T, CalculatorError> {
+        Ok(self.try_map(|element| {
+            let magnitude = element.abs()?;
+            magnitude.clone().checked_times(magnitude)
+        })?.sum()?.sqrt())
+    }
+}
+
+impl<T: Math<T>> Add for Vector<T> {
+    type Output = Result<Vector<T>, CalculatorError>;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, T::checked_plus)
+    }
+}
+
+impl<T: Math<T>> Sub for Vector<T> {
+    type Output = Result<Vector<T>, CalculatorError>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.zip(rhs, T::checked_minus)
+    }
+}
+
+impl<T: Math<T>> Mul<T> for Vector<T> {
+    type Output = Result<Vector<T>, CalculatorError>;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        self.try_map(|element| element.checked_times(rhs.clone()))
+    }
+}
+
+impl<T: Math<T>> Div<T> for Vector<T> {
+    type Output = Result<Vector<T>, CalculatorError>;
+
+    fn div(self, rhs: T) -> Self::Output {
+        self.try_map(|element| element.checked_div(rhs.clone()))
+    }
+}
+
+impl<T: Math<T>> Percent for Vector<T> {
+    fn percent(self) -> Self {
+        self.map(T::percent)
+    }
+}
+
+impl<T: Format> Format for Vector<T> {
+    fn format(&self, f: &mut Formatter<'_>, options: &FormatOptions) -> std::fmt::Result {
+        f.write_str("[")?;
+        for (index, element) in self.elements.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            element.format(f, options)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl<T: Format> Display for Vector<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.format(f, &FormatOptions::default())
+    }
+}
+
+#[cfg(test)]
+mod vector_tests {
+    use super::*;
+
+    fn vector(elements: &[f64]) -> Vector<f64> {
+        Vector::new(elements.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(Err(CalculatorError::EmptyVector), Vector::<f64>::new(vec![]));
+    }
+
+    #[test]
+    fn add() {
+        assert_eq!(Ok(vector(&[5f64, 7f64, 9f64])), vector(&[1f64, 2f64, 3f64]) + vector(&[4f64, 5f64, 6f64]));
+    }
+
+    #[test]
+    fn add_mismatched_dimensions() {
+        assert_eq!(
+            Err(CalculatorError::DimensionMismatch { expected: 2, found: 3 }),
+            vector(&[1f64, 2f64]) + vector(&[4f64, 5f64, 6f64])
+        );
+    }
+
+    #[test]
+    fn subtract() {
+        assert_eq!(Ok(vector(&[-3f64, -3f64])), vector(&[1f64, 2f64]) - vector(&[4f64, 5f64]));
+    }
+
+    #[test]
+    fn scalar_multiply() {
+        assert_eq!(Ok(vector(&[2f64, 4f64])), vector(&[1f64, 2f64]) * 2f64);
+    }
+
+    #[test]
+    fn dot() {
+        assert_eq!(Ok(32f64), vector(&[1f64, 2f64, 3f64]).dot(vector(&[4f64, 5f64, 6f64])));
+    }
+
+    #[test]
+    fn cross() {
+        assert_eq!(
+            Ok(vector(&[-3f64, 6f64, -3f64])),
+            vector(&[1f64, 2f64, 3f64]).cross(vector(&[4f64, 5f64, 6f64]))
+        );
+    }
+
+    #[test]
+    fn cross_needs_three_dimensions() {
+        assert_eq!(
+            Err(CalculatorError::DimensionMismatch { expected: 3, found: 2 }),
+            vector(&[1f64, 2f64]).cross(vector(&[4f64, 5f64, 6f64]))
+        );
+    }
+
+    #[test]
+    fn norm() {
+        assert_eq!(Ok(5f64), vector(&[3f64, -4f64]).norm());
+    }
+
+    #[test]
+    fn integer_overflow() {
+        let vector = |elements: &[i32]| Vector::new(elements.to_vec()).unwrap();
+        assert_eq!(Err(CalculatorError::Overflow), vector(&[i32::MAX]) + vector(&[1]));
+        assert_eq!(Err(CalculatorError::Overflow), vector(&[i32::MIN]) - vector(&[1]));
+        assert_eq!(Err(CalculatorError::Overflow), vector(&[i32::MAX]) * 2);
+        assert_eq!(Err(CalculatorError::DivisionByZero), vector(&[1, 2]) / 0);
+        assert_eq!(Err(CalculatorError::Overflow), vector(&[65536, 0, 0]).cross(vector(&[0, 65536, 0])));
+        assert_eq!(Err(CalculatorError::Overflow), vector(&[65536, 65536]).norm());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("[1, 2.5, 3]", vector(&[1f64, 2.5f64, 3f64]).to_string());
+    }
+}
@@ -0,0 +1,396 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use num::{BigInt, Integer, ToPrimitive};
+
+use crate::calculator::{FromStrValue, radix_literal};
+use crate::combinatorics;
+use crate::error::CalculatorError;
+use crate::rounding::{self, Rounding};
+use crate::math::{shift_amount, BigInteger, Bitwise, Checked, CheckedDiv, Compare, Elementary, Gamma, Inv, Magnitude, Modulo, One, Percent, Polar, Pow, Round, ToInteger, Zero};
+
+/// An integer of `BITS` bits, at most 64, that wraps around when it overflows, as in C. Signed words
+/// use two's complement, so in 8 bits `127 + 1` is -128 while unsigned `255 + 1` is 0.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Word<const BITS: u32, const SIGNED: bool>(i128);
+
+impl<const BITS: u32, const SIGNED: bool> Word<BITS, SIGNED> {
+    /// The word with the same lowest `BITS` bits as `value`
+    pub fn new(value: i128) -> Self {
+        let bits = value & Self::mask();
+        if SIGNED && bits >> (BITS - 1) == 1 {
+            Word(bits - (1 << BITS))
+        } else {
+            Word(bits)
+        }
+    }
+
+    fn mask() -> i128 {
+        (1 << BITS) - 1
+    }
+
+    /// The bits of the word as an unsigned number, e.g. 255 for -1 in 8 bits
+    pub fn bits(&self) -> i128 {
+        self.0 & Self::mask()
+    }
+
+    pub fn value(&self) -> i128 {
+        self.0
+    }
+
+    /// Calculates a function without an integer form through `f64`, truncating the result
+    fn through_f64(self, function: fn(f64) -> f64) -> Self {
+        let result = function(self.0 as f64);
+        if result.is_finite() {
+            Self::new(result.trunc() as i128)
+        } else {
+            Self::zero()
+        }
+    }
+
+    /// The amount to rotate by, within the width of the word
+    fn rotation(amount: Self) -> u32 {
+        amount.0.rem_euclid(i128::from(BITS)) as u32
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Add for Word<BITS, SIGNED> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.0.wrapping_add(rhs.0))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Sub for Word<BITS, SIGNED> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.0.wrapping_sub(rhs.0))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Mul for Word<BITS, SIGNED> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(self.0.wrapping_mul(rhs.0))
+    }
+}
+
+/// Truncates, panicking when dividing by zero, [CheckedDiv] reports it instead
+impl<const BITS: u32, const SIGNED: bool> Div for Word<BITS, SIGNED> {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self::new(self.0 / rhs.0)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Neg for Word<BITS, SIGNED> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.0.wrapping_neg())
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> CheckedDiv for Word<BITS, SIGNED> {
+    fn checked_div(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs.0 == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(self / rhs)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Zero for Word<BITS, SIGNED> {
+    fn zero() -> Self {
+        Word(0)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> One for Word<BITS, SIGNED> {
+    fn one() -> Self {
+        Word(1)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Inv for Word<BITS, SIGNED> {
+    fn inv(self) -> Self {
+        Self::one() / self
+    }
+}
+
+/// Wraps like repeated multiplication, negative powers truncate to zero except of one and minus one
+impl<const BITS: u32, const SIGNED: bool> Pow for Word<BITS, SIGNED> {
+    fn pow(self, rhs: Self) -> Self {
+        if rhs.0 < 0 {
+            return match self.0 {
+                -1 if rhs.0 % 2 != 0 => self,
+                1 | -1 => Self::one(),
+                _ => Self::zero(),
+            };
+        }
+        let (mut base, mut exponent, mut result) = (self, rhs.0, Self::one());
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exponent /= 2;
+        }
+        result
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Percent for Word<BITS, SIGNED> {
+    fn percent(self) -> Self {
+        self / Word(100)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Elementary for Word<BITS, SIGNED> {
+    fn sqrt(self) -> Self {
+        self.through_f64(f64::sqrt)
+    }
+
+    fn exp(self) -> Self {
+        self.through_f64(f64::exp)
+    }
+
+    fn ln(self) -> Self {
+        self.through_f64(f64::ln)
+    }
+
+    fn sin(self) -> Self {
+        self.through_f64(f64::sin)
+    }
+
+    fn cos(self) -> Self {
+        self.through_f64(f64::cos)
+    }
+
+    fn tan(self) -> Self {
+        self.through_f64(f64::tan)
+    }
+
+    fn abs(self) -> Result<Self, CalculatorError> {
+        Ok(Self::new(self.0.abs()))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Polar for Word<BITS, SIGNED> {
+    fn re(self) -> Self {
+        self
+    }
+
+    fn im(self) -> Self {
+        Self::zero()
+    }
+
+    fn arg(self) -> Self {
+        self.through_f64(|value| 0f64.atan2(value))
+    }
+
+    fn conj(self) -> Self {
+        self
+    }
+
+    fn cis(self) -> Result<Self, CalculatorError> {
+        if self.0 != 0 {
+            return Err(CalculatorError::ComplexResult);
+        }
+        Ok(Self::one())
+    }
+
+    fn degrees(self) -> Self {
+        self.through_f64(f64::to_radians)
+    }
+}
+
+/// Keeps the lowest bits of integers that are too large, as arithmetic does
+impl<const BITS: u32, const SIGNED: bool> BigInteger for Word<BITS, SIGNED> {
+    fn to_big_integer(&self) -> Option<BigInt> {
+        Some(BigInt::from(self.0))
+    }
+
+    fn from_big_integer(value: BigInt) -> Result<Self, CalculatorError> {
+        let bits = value.mod_floor(&(BigInt::from(1) << BITS));
+        Ok(Self::new(bits.to_i128().unwrap_or_default()))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Compare for Word<BITS, SIGNED> {
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Round for Word<BITS, SIGNED> {
+    fn round_to(self, digits: i32, rounding: Rounding) -> Result<Self, CalculatorError> {
+        Self::from_big_integer(rounding::round_integer(BigInt::from(self.0), digits, rounding))
+    }
+
+    fn sign(self) -> Self {
+        Self::new(self.0.signum())
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Gamma for Word<BITS, SIGNED> {
+    fn gamma(self) -> Result<Self, CalculatorError> {
+        combinatorics::factorial(self - Self::one())
+    }
+
+    fn ln_gamma(self) -> Result<Self, CalculatorError> {
+        Ok(self.through_f64(|value| Gamma::ln_gamma(value).unwrap_or(f64::NAN)))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Modulo for Word<BITS, SIGNED> {
+    fn modulo(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs.0 == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(Self::new(self.0.rem_euclid(rhs.0)))
+    }
+
+    fn quotient(self, rhs: Self) -> Result<Self, CalculatorError> {
+        if rhs.0 == 0 {
+            return Err(CalculatorError::DivisionByZero);
+        }
+        Ok(Self::new(self.0.div_euclid(rhs.0)))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Checked for Word<BITS, SIGNED> {}
+
+impl<const BITS: u32, const SIGNED: bool> Bitwise for Word<BITS, SIGNED> {
+    fn bit_and(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Self::new(self.0 & rhs.0))
+    }
+
+    fn bit_or(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Self::new(self.0 | rhs.0))
+    }
+
+    fn bit_xor(self, rhs: Self) -> Result<Self, CalculatorError> {
+        Ok(Self::new(self.0 ^ rhs.0))
+    }
+
+    fn bit_not(self) -> Result<Self, CalculatorError> {
+        Ok(Self::new(!self.0))
+    }
+
+    fn shift_left(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let amount = shift_amount(rhs.to_integer().unwrap_or(i64::MAX))?;
+        Ok(Self::new(self.0 << amount.min(BITS)))
+    }
+
+    /// Arithmetic for signed words, which keep their sign, and logical for unsigned
+    fn shift_right(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let amount = shift_amount(rhs.to_integer().unwrap_or(i64::MAX))?;
+        Ok(Self::new(self.0 >> amount.min(BITS)))
+    }
+
+    fn rotate_left(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let amount = Self::rotation(rhs);
+        Ok(Self::new(self.bits() << amount | self.bits() >> (BITS - amount)))
+    }
+
+    fn rotate_right(self, rhs: Self) -> Result<Self, CalculatorError> {
+        let amount = Self::rotation(rhs);
+        Ok(Self::new(self.bits() >> amount | self.bits() << (BITS - amount)))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Magnitude for Word<BITS, SIGNED> {
+    const INTEGER: bool = true;
+    const WRAPPING: bool = true;
+
+    fn magnitude(&self) -> f64 {
+        self.0.abs() as f64
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> ToInteger for Word<BITS, SIGNED> {
+    fn to_integer(&self) -> Option<i64> {
+        i64::try_from(self.0).ok()
+    }
+}
+
+/// Parses decimal integers, or integers in other bases such as `0xFF`, keeping their lowest bits
+impl<const BITS: u32, const SIGNED: bool> FromStr for Word<BITS, SIGNED> {
+    type Err = CalculatorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (radix, digits) = radix_literal(s).unwrap_or((10, s));
+        if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+            return Err(CalculatorError::Parse(s.to_string()));
+        }
+        u128::from_str_radix(digits, radix)
+            .map(|value| Self::new(value as i128))
+            .map_err(|_| CalculatorError::Parse(s.to_string()))
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> FromStrValue for Word<BITS, SIGNED> {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Display for Word<BITS, SIGNED> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod word_tests {
+    use super::*;
+
+    type I8 = Word<8, true>;
+    type U8 = Word<8, false>;
+
+    #[test]
+    fn wrap_around() {
+        assert_eq!(I8::new(-128), I8::new(127) + I8::one());
+        assert_eq!(U8::zero(), U8::new(255) + U8::one());
+        assert_eq!(U8::new(255), U8::zero() - U8::one());
+        assert_eq!(Word::<64, false>::new(u64::MAX as i128), Word::<64, false>::zero() - Word::one());
+        assert_eq!(Word::<16, true>::zero(), Word::<16, true>::new(2).pow(Word::new(16)));
+    }
+
+    #[test]
+    fn parse_keeps_lowest_bits() {
+        assert_eq!(Ok(I8::new(-1)), "0xFF".parse());
+        assert_eq!(Ok(U8::new(0x34)), "0x1234".parse());
+        assert_eq!(Err(CalculatorError::Parse("1.5".to_string())), "1.5".parse::<U8>());
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(Ok(U8::new(0xF0)), U8::new(0xFF).shift_left(U8::new(4)));
+        assert_eq!(Ok(U8::new(0x0F)), U8::new(0xFF).shift_right(U8::new(4)));
+        assert_eq!(Ok(I8::new(-1)), I8::new(-1).shift_right(I8::new(4)));
+        assert_eq!(Ok(U8::zero()), U8::new(1).shift_left(U8::new(8)));
+        assert_eq!(Ok(I8::new(-1)), I8::new(-128).shift_right(I8::new(100)));
+    }
+
+    #[test]
+    fn rotations() {
+        assert_eq!(Ok(U8::new(0x03)), U8::new(0x81).rotate_left(U8::new(1)));
+        assert_eq!(Ok(U8::new(0xC0)), U8::new(0x81).rotate_right(U8::new(1)));
+        assert_eq!(Ok(I8::new(0x18)), I8::new(-127).rotate_left(I8::new(4)));
+        assert_eq!(Ok(U8::new(0x81)), U8::new(0x81).rotate_left(U8::new(8)));
+    }
+
+    #[test]
+    fn complement() {
+        assert_eq!(Ok(U8::new(0xF0)), U8::new(0x0F).bit_not());
+        assert_eq!(Ok(I8::new(-16)), I8::new(0x0F).bit_not());
+    }
+}